@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use tauri::Manager;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::models::{AppConfig, ProxyServerConfig, RoutingConfig, RoutingStrategy};
 
@@ -45,6 +47,159 @@ pub fn load_config(app_handle: &tauri::AppHandle) -> Result<AppConfig> {
     }
 }
 
+/// Prefix of environment variables overriding `config.json` values, e.g.
+/// `CODEX_MANAGER_PROXY_BIND_ADDR` for `proxy.bind_addr`
+pub const ENV_PREFIX: &str = "CODEX_MANAGER_";
+
+/// Where an effective configuration value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+}
+
+/// One resolved configuration value and its source
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfigValue {
+    pub key: String,
+    pub value: Value,
+    pub source: ConfigSource,
+}
+
+/// Load the configuration with `CODEX_MANAGER_*` environment overrides
+/// applied; the file itself is left untouched
+pub fn load_effective_config(
+    app_handle: &tauri::AppHandle,
+) -> Result<(AppConfig, Vec<EffectiveConfigValue>)> {
+    let config_path = get_config_path(app_handle)?;
+    let content = if config_path.exists() {
+        Some(fs::read_to_string(&config_path)?)
+    } else {
+        None
+    };
+
+    let env: HashMap<String, String> = std::env::vars().collect();
+    resolve_effective_config(content.as_deref(), &env)
+}
+
+/// `config` with the `CODEX_MANAGER_*` environment overrides layered on top
+pub fn apply_env_overrides(
+    config: &AppConfig,
+    env: &HashMap<String, String>,
+) -> Result<AppConfig> {
+    let content = serde_json::to_string(config)?;
+    resolve_effective_config(Some(&content), env).map(|(config, _)| config)
+}
+
+/// Proxy settings handed over by the frontend, with the same environment
+/// overrides the rest of the app runs with
+pub fn proxy_with_env_overrides(proxy: ProxyServerConfig) -> Result<ProxyServerConfig> {
+    let config = AppConfig { proxy, ..AppConfig::default() };
+    let env: HashMap<String, String> = std::env::vars().collect();
+    Ok(apply_env_overrides(&config, &env)?.proxy)
+}
+
+/// Layer defaults, the file contents and environment variables, later layers
+/// winning. Environment variables only override keys the defaults or the
+/// file define.
+pub fn resolve_effective_config(
+    file: Option<&str>,
+    env: &HashMap<String, String>,
+) -> Result<(AppConfig, Vec<EffectiveConfigValue>)> {
+    let mut merged = serde_json::to_value(AppConfig::default())?;
+    let mut sources = BTreeMap::new();
+    for (key, _) in leaves(&merged) {
+        sources.insert(key, ConfigSource::Default);
+    }
+
+    if let Some(content) = file {
+        let file_value: Value = serde_json::from_str(content)
+            .context("Failed to parse configuration file")?;
+        for (key, value) in leaves(&file_value) {
+            set_path(&mut merged, &key, value.clone());
+            sources.insert(key, ConfigSource::File);
+        }
+    }
+
+    let keys: Vec<String> = sources.keys().cloned().collect();
+    for key in keys {
+        let name = format!("{}{}", ENV_PREFIX, key.replace('.', "_").to_uppercase());
+        let Some(raw) = env.get(&name) else {
+            continue;
+        };
+        // Strings are taken verbatim; anything else is parsed as JSON
+        let value = match lookup(&merged, &key) {
+            Some(Value::String(_)) | None => Value::String(raw.clone()),
+            Some(_) => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone())),
+        };
+        set_path(&mut merged, &key, value);
+        sources.insert(key, ConfigSource::Env);
+    }
+
+    let config: AppConfig =
+        serde_json::from_value(merged).context("Invalid configuration override")?;
+    let resolved = serde_json::to_value(&config)?;
+    let values = leaves(&resolved)
+        .into_iter()
+        .map(|(key, value)| EffectiveConfigValue {
+            source: sources.get(&key).copied().unwrap_or(ConfigSource::File),
+            key,
+            value: value.clone(),
+        })
+        .collect();
+
+    Ok((config, values))
+}
+
+/// Non-object values by dotted path
+fn leaves(value: &Value) -> Vec<(String, &Value)> {
+    let mut out = Vec::new();
+    if let Value::Object(map) = value {
+        for (key, value) in map {
+            match value {
+                Value::Object(_) => {
+                    for (inner_key, inner_value) in leaves(value) {
+                        out.push((format!("{}.{}", key, inner_key), inner_value));
+                    }
+                }
+                _ => out.push((key.clone(), value)),
+            }
+        }
+    }
+    out
+}
+
+fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(value, |value, part| value.get(part))
+}
+
+fn set_path(value: &mut Value, key: &str, new_value: Value) {
+    let (parents, last) = match key.rsplit_once('.') {
+        Some((parents, last)) => (Some(parents), last),
+        None => (None, key),
+    };
+
+    let mut target = value;
+    for part in parents.into_iter().flat_map(|p| p.split('.')) {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        target = target
+            .as_object_mut()
+            .expect("just made an object")
+            .entry(part)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = target {
+        map.insert(last.to_string(), new_value);
+    }
+}
+
 /// Save configuration to file
 pub fn save_config(app_handle: &tauri::AppHandle, config: &AppConfig) -> Result<()> {
     let config_path = get_config_path(app_handle)?;
@@ -54,11 +209,97 @@ pub fn save_config(app_handle: &tauri::AppHandle, config: &AppConfig) -> Result<
     Ok(())
 }
 
+/// Serializes writes and read-modify-write cycles of `config.json` within
+/// the app
+static CONFIG_WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Overwrite the stored configuration with the defaults
+pub fn reset_config(app_handle: &tauri::AppHandle) -> Result<()> {
+    let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    save_config(app_handle, &AppConfig::default())
+}
+
+/// Apply an RFC 7386 JSON merge patch to the stored configuration and save it.
+///
+/// Only the fields named in the patch change, so concurrent edits of
+/// unrelated fields don't clobber each other.
+pub fn patch_config(app_handle: &tauri::AppHandle, patch: &Value) -> Result<AppConfig> {
+    let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = apply_config_patch(&load_config(app_handle)?, patch)?;
+    save_config(app_handle, &config)?;
+    Ok(config)
+}
+
+/// The configuration resulting from a merge patch, validated
+pub fn apply_config_patch(config: &AppConfig, patch: &Value) -> Result<AppConfig> {
+    let mut value = serde_json::to_value(config)?;
+    merge_patch(&mut value, patch);
+
+    let patched: AppConfig =
+        serde_json::from_value(value).context("Patched configuration is invalid")?;
+    validate_config(&patched)?;
+    Ok(patched)
+}
+
+/// RFC 7386: objects merge recursively, `null` removes a member, and any
+/// other value replaces the target outright
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(key);
+            } else {
+                merge_patch(map.entry(key.as_str()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Check values the types alone don't constrain
+fn validate_config(config: &AppConfig) -> Result<()> {
+    config
+        .proxy
+        .bind_addr
+        .parse::<std::net::SocketAddr>()
+        .with_context(|| format!("Invalid proxy.bind_addr '{}'", config.proxy.bind_addr))?;
+    if config.proxy.api_key.trim().is_empty() {
+        anyhow::bail!("proxy.api_key cannot be empty");
+    }
+    if !config.proxy.openai_base_url.starts_with("http://")
+        && !config.proxy.openai_base_url.starts_with("https://")
+    {
+        anyhow::bail!("proxy.openai_base_url must be an http(s) URL");
+    }
+    Ok(())
+}
+
+/// Prefix of generated proxy client keys
+pub const PROXY_API_KEY_PREFIX: &str = "sk-cam-";
+
+/// 43 base62 characters carry at least 256 bits
+const PROXY_API_KEY_RANDOM_LEN: usize = 43;
+
+/// A fresh proxy client key drawn from the OS random number generator
+pub fn new_proxy_api_key() -> String {
+    use rand::distributions::{Alphanumeric, DistString};
+
+    let random = Alphanumeric.sample_string(&mut rand::rngs::OsRng, PROXY_API_KEY_RANDOM_LEN);
+    format!("{PROXY_API_KEY_PREFIX}{random}")
+}
+
 /// Update proxy configuration
 pub fn update_proxy_config(
     app_handle: &tauri::AppHandle,
     proxy_config: ProxyServerConfig,
 ) -> Result<()> {
+    let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = load_config(app_handle)?;
     config.proxy = proxy_config;
     save_config(app_handle, &config)
@@ -69,13 +310,17 @@ pub fn update_routing_config(
     app_handle: &tauri::AppHandle,
     routing_config: RoutingConfig,
 ) -> Result<()> {
+    let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut config = load_config(app_handle)?;
     config.routing = routing_config;
     save_config(app_handle, &config)
 }
 
-/// Get the master key from secure storage or environment
-pub fn get_master_key(app_handle: &tauri::AppHandle) -> Result<String> {
+/// Get the master key from secure storage or environment.
+///
+/// Without either, the built-in default key is only used when
+/// `allow_default`; otherwise startup fails.
+pub fn get_master_key(app_handle: &tauri::AppHandle, allow_default: bool) -> Result<String> {
     // Try to get from secure storage first
     if let Ok(store) = app_handle.store("codex-manager.secrets") {
         if let Some(key) = store.get("master_key") {
@@ -90,7 +335,15 @@ pub fn get_master_key(app_handle: &tauri::AppHandle) -> Result<String> {
         return Ok(key);
     }
 
-    // Generate a default key (for development only)
+    if !allow_default {
+        anyhow::bail!(
+            "No master key configured. Set CODEX_MANAGER_MASTER_KEY \
+             (or allow_default_master_key in config.json to use the insecure built-in key)"
+        );
+    }
+
+    // Default key (for development only)
+    warn!("Using default master key - set CODEX_MANAGER_MASTER_KEY!");
     Ok("codex-manager-default-key".to_string())
 }
 
@@ -109,15 +362,55 @@ pub async fn load_app_config(app_handle: tauri::AppHandle) -> Result<AppConfig,
     load_config(&app_handle).map_err(|e| e.to_string())
 }
 
+/// Tauri command: Resolved configuration with the source of each value
+#[tauri::command]
+pub async fn get_effective_config(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<EffectiveConfigValue>, String> {
+    load_effective_config(&app_handle)
+        .map(|(_, values)| values)
+        .map_err(|e| e.to_string())
+}
+
 /// Tauri command: Save configuration
 #[tauri::command]
 pub async fn save_app_config(
     app_handle: tauri::AppHandle,
     config: AppConfig,
 ) -> Result<(), String> {
+    let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     save_config(&app_handle, &config).map_err(|e| e.to_string())
 }
 
+/// Tauri command: Merge-patch the configuration, returning the saved result
+#[tauri::command]
+pub async fn patch_app_config(
+    app_handle: tauri::AppHandle,
+    patch: Value,
+) -> Result<AppConfig, String> {
+    patch_config(&app_handle, &patch).map_err(|e| e.to_string())
+}
+
+/// Tauri command: Generate a new proxy API key and save it to the config.
+///
+/// A running proxy switches to the new key right away, accepting the old
+/// one for the usual rotation grace period. The key is only returned here.
+#[tauri::command]
+pub async fn generate_proxy_api_key(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let key = new_proxy_api_key();
+    {
+        let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut config = load_config(&app_handle).map_err(|e| e.to_string())?;
+        config.proxy.api_key = key.clone();
+        save_config(&app_handle, &config).map_err(|e| e.to_string())?;
+    }
+
+    if crate::proxy::rotate_proxy_key(key.clone(), None).await.is_err() {
+        info!("Generated new proxy API key; it applies when the proxy next starts");
+    }
+    Ok(key)
+}
+
 /// Tauri command: Update proxy configuration
 #[tauri::command]
 pub async fn update_proxy_configuration(
@@ -151,3 +444,75 @@ pub async fn get_data_directory(app_handle: tauri::AppHandle) -> Result<String,
     let path = get_data_dir(&app_handle).map_err(|e| e.to_string())?;
     Ok(path.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_patch_changes_only_targeted_field() {
+        let config = AppConfig::default();
+        let patched =
+            apply_config_patch(&config, &json!({ "proxy": { "bind_addr": "0.0.0.0:9090" } }))
+                .unwrap();
+
+        assert_eq!(patched.proxy.bind_addr, "0.0.0.0:9090");
+        assert_eq!(patched.proxy.api_key, config.proxy.api_key);
+        assert_eq!(patched.proxy.openai_base_url, config.proxy.openai_base_url);
+        assert_eq!(patched.routing.strategy, config.routing.strategy);
+        assert_eq!(
+            patched.routing.min_request_interval_ms,
+            config.routing.min_request_interval_ms
+        );
+        assert_eq!(patched.allow_default_master_key, config.allow_default_master_key);
+    }
+
+    #[test]
+    fn test_patch_rejects_invalid_results() {
+        let config = AppConfig::default();
+
+        assert!(apply_config_patch(&config, &json!({ "proxy": { "bind_addr": "nowhere" } })).is_err());
+        assert!(apply_config_patch(&config, &json!({ "routing": { "strategy": "fastest" } })).is_err());
+        assert!(apply_config_patch(&config, &json!({ "proxy": null })).is_err());
+    }
+
+    #[test]
+    fn test_generated_proxy_keys_are_unique_and_base62() {
+        let keys: std::collections::HashSet<String> =
+            (0..100).map(|_| new_proxy_api_key()).collect();
+        assert_eq!(keys.len(), 100);
+
+        for key in &keys {
+            let random = key.strip_prefix(PROXY_API_KEY_PREFIX).unwrap();
+            assert_eq!(random.len(), PROXY_API_KEY_RANDOM_LEN);
+            assert!(random.chars().all(|c| c.is_ascii_alphanumeric()));
+        }
+    }
+
+    #[test]
+    fn test_env_overrides_apply_on_top_of_a_given_config() {
+        let mut config = AppConfig::default();
+        config.proxy.bind_addr = "127.0.0.1:9000".to_string();
+        config.proxy.api_key = "sk-from-the-ui".to_string();
+
+        let env: HashMap<String, String> = [(
+            "CODEX_MANAGER_PROXY_BIND_ADDR".to_string(),
+            "0.0.0.0:8080".to_string(),
+        )]
+        .into_iter()
+        .collect();
+        let effective = apply_env_overrides(&config, &env).unwrap();
+
+        assert_eq!(effective.proxy.bind_addr, "0.0.0.0:8080");
+        assert_eq!(effective.proxy.api_key, "sk-from-the-ui");
+        assert_eq!(apply_env_overrides(&config, &HashMap::new()).unwrap().proxy.bind_addr, "127.0.0.1:9000");
+    }
+
+    #[test]
+    fn test_merge_patch_removes_nulls() {
+        let mut value = json!({ "a": 1, "b": { "c": 2, "d": 3 } });
+        merge_patch(&mut value, &json!({ "b": { "c": null, "e": 4 } }));
+        assert_eq!(value, json!({ "a": 1, "b": { "d": 3, "e": 4 } }));
+    }
+}
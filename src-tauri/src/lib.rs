@@ -10,33 +10,35 @@ pub mod usage;
 
 use std::sync::Arc;
 use std::collections::HashMap;
-use tauri::Manager;
-use tracing::{info, error};
-use uuid::Uuid;
+use tauri::{Emitter, Manager};
+use tracing::{info, error, warn};
 
 use crate::models::{
-    Account, AccountExport, AccountId, AccountStatus, AppConfig, CreateAccountRequest,
-    ProxyServerConfig, ProxyStatus, RoutingConfig, RoutingStats, RoutingStrategy, UpdateAccountRequest,
-    UsageSnapshot, ValidationResult,
+    Account, AccountExport, AccountId, AccountStatus, AppConfig, CommandError, CreateAccountRequest,
+    ProxyServerConfig, ProxyStatus, PurgeSummary, RoutingConfig, RoutingStats, RoutingStrategy,
+    SessionInfo,
+    UpdateAccountRequest, UsageRefreshProgress, UsageSnapshot, ValidationResult,
 };
 use crate::routing::RoutingEngine;
 use crate::storage::EncryptedStore;
-use crate::usage::{OpenAIClient, UsagePoller};
+use crate::usage::{OpenAIClient, UsagePoller, UsageRange};
 
 /// Application state shared across Tauri commands
 pub struct AppState {
     pub store: Arc<EncryptedStore>,
     pub routing_engine: Arc<RoutingEngine>,
     pub usage_poller: Arc<UsagePoller>,
+    /// Per-account progress of bulk usage refreshes, forwarded to the frontend
+    pub usage_progress: tokio::sync::broadcast::Sender<UsageRefreshProgress>,
 }
 
 // Re-export commands from modules
 pub use config::{
-    load_app_config, save_app_config, update_proxy_configuration, 
-    update_routing_configuration, set_master_key, get_data_directory,
+    load_app_config, save_app_config, patch_app_config, get_effective_config, generate_proxy_api_key,
+    update_proxy_configuration, update_routing_configuration, set_master_key, get_data_directory,
 };
 pub use proxy::{
-    start_proxy_server, stop_proxy_server, get_proxy_status,
+    start_proxy_server, stop_proxy_server, get_proxy_status, rotate_proxy_key, get_recent_requests,
 };
 pub use usage::{
     fetch_account_usage, validate_api_key as validate_api_key_usage,
@@ -51,21 +53,9 @@ pub use usage::{
 pub async fn add_account(
     state: tauri::State<'_, AppState>,
     request: CreateAccountRequest,
-) -> Result<Account, String> {
-    let mut account = Account::new(request.label, request.api_key);
-    
-    if let Some(org_id) = request.org_id {
-        account = account.with_org_id(org_id);
-    }
-    if let Some(models) = request.model_scope {
-        account = account.with_model_scope(models);
-    }
-    if let Some(daily) = request.daily_limit {
-        account = account.with_limits(Some(daily), request.monthly_limit);
-    }
-    if let Some(priority) = request.priority {
-        account = account.with_priority(priority);
-    }
+) -> Result<Account, CommandError> {
+    let account = request.into_account();
+    validate_account(&account)?;
 
     state.store.save_account(&account)
         .map_err(|e| format!("Failed to save account: {}", e))?;
@@ -82,10 +72,11 @@ pub async fn add_account(
 pub async fn update_account(
     state: tauri::State<'_, AppState>,
     request: UpdateAccountRequest,
-) -> Result<Account, String> {
-    let mut account = state.store.load_account(request.id)
+) -> Result<Account, CommandError> {
+    let original = state.store.load_account(request.id)
         .map_err(|e| format!("Failed to load account: {}", e))?
         .ok_or_else(|| "Account not found".to_string())?;
+    let mut account = original.clone();
 
     if let Some(label) = request.label {
         account.label = label;
@@ -111,11 +102,81 @@ pub async fn update_account(
 
     account.updated_at = chrono::Utc::now();
 
+    original.validate_edit(&account)?;
+    validate_account(&account)?;
+
     state.store.save_account(&account)
         .map_err(|e| format!("Failed to save account: {}", e))?;
 
-    refresh_routing_engine(&state).await?;
+    refresh_routing_account(&state, &account).await;
+
+    Ok(account)
+}
+
+/// Replace an account's API key in place, keeping its usage history.
+///
+/// With `validate`, the new key is checked against the API before saving.
+#[tauri::command]
+pub async fn update_account_key(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: AccountId,
+    new_key: String,
+    validate: bool,
+) -> Result<Account, CommandError> {
+    let original = state.store.load_account(id)
+        .map_err(|e| format!("Failed to load account: {}", e))?
+        .ok_or_else(|| "Account not found".to_string())?;
+    let mut account = original.clone();
+    account.api_key = new_key;
+
+    original.validate_edit(&account)?;
+    validate_account(&account)?;
+
+    if validate {
+        let result = configured_client(&app_handle)?
+            .validate_key(&account.api_key, account.org_id.as_deref())
+            .await
+            .map_err(|e| format!("Failed to validate key: {}", e))?;
+        if !result.valid {
+            return Err(format!(
+                "New key was rejected: {}",
+                result.error.unwrap_or_else(|| "invalid key".to_string())
+            )
+            .into());
+        }
+    }
 
+    state.store.update_account_key(id, &account.api_key)
+        .map_err(|e| format!("Failed to save account: {}", e))?;
+    let account = state.store.load_account(id)
+        .map_err(|e| format!("Failed to load account: {}", e))?
+        .ok_or_else(|| "Account not found".to_string())?;
+
+    refresh_routing_account(&state, &account).await;
+
+    info!("Rotated API key for account: {} ({})", account.label, id);
+    Ok(account)
+}
+
+/// Lock or unlock an account; locked accounts only accept enable/disable
+#[tauri::command]
+pub async fn set_account_locked(
+    state: tauri::State<'_, AppState>,
+    id: AccountId,
+    locked: bool,
+) -> Result<Account, String> {
+    let mut account = state.store.load_account(id)
+        .map_err(|e| format!("Failed to load account: {}", e))?
+        .ok_or_else(|| "Account not found".to_string())?;
+
+    account.locked = locked;
+    account.updated_at = chrono::Utc::now();
+
+    state.store.save_account(&account)
+        .map_err(|e| format!("Failed to save account: {}", e))?;
+
+    info!("{} account: {} ({})", if locked { "Locked" } else { "Unlocked" }, account.label, id);
     Ok(account)
 }
 
@@ -124,7 +185,9 @@ pub async fn update_account(
 pub async fn remove_account(
     state: tauri::State<'_, AppState>,
     id: AccountId,
-) -> Result<bool, String> {
+) -> Result<bool, CommandError> {
+    ensure_unlocked(&state, id)?;
+
     let deleted = state.store.delete_account(id)
         .map_err(|e| format!("Failed to delete account: {}", e))?;
 
@@ -136,6 +199,25 @@ pub async fn remove_account(
     Ok(deleted)
 }
 
+/// Merge a duplicate account into another: its usage history moves to
+/// `target_id` and the source account is removed
+#[tauri::command]
+pub async fn merge_accounts(
+    state: tauri::State<'_, AppState>,
+    source_id: AccountId,
+    target_id: AccountId,
+) -> Result<usize, CommandError> {
+    ensure_unlocked(&state, source_id)?;
+
+    let moved = state.store.merge_accounts(source_id, target_id)
+        .map_err(|e| format!("Failed to merge accounts: {}", e))?;
+
+    refresh_routing_engine(&state).await?;
+    info!("Merged account {} into {} ({} usage snapshots)", source_id, target_id, moved);
+
+    Ok(moved)
+}
+
 /// Get a single account by ID
 #[tauri::command]
 pub async fn get_account(
@@ -163,9 +245,11 @@ pub async fn list_account_statuses(
     state.routing_engine.get_account_statuses().await
 }
 
-/// Toggle account enabled state
+/// Toggle account enabled state, warming the account up on enable when
+/// `routing.warmup_on_enable` is set
 #[tauri::command]
 pub async fn toggle_account_enabled(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     id: AccountId,
 ) -> Result<Account, String> {
@@ -179,7 +263,16 @@ pub async fn toggle_account_enabled(
     state.store.save_account(&account)
         .map_err(|e| format!("Failed to save account: {}", e))?;
 
-    refresh_routing_engine(&state).await?;
+    refresh_routing_account(&state, &account).await;
+
+    let config = config::load_effective_config(&app_handle).ok().map(|(config, _)| config);
+    if config.is_some_and(|c| account.enabled && c.routing.warmup_on_enable) {
+        let client = configured_client(&app_handle)?;
+        match proxy::warm_up_account(&client, &state.routing_engine, &account).await {
+            Ok(latency) => info!("Warmed up {} in {} ms", account.label, latency.as_millis()),
+            Err(e) => warn!("Warmup failed for {}: {}", account.label, e),
+        }
+    }
 
     Ok(account)
 }
@@ -198,30 +291,30 @@ pub async fn get_account_usage(
         .map_err(|e| e.to_string())
 }
 
+/// Usage and validation client pointed at the configured upstream, so keys
+/// are checked against the same API the proxy forwards to
+fn configured_client(app_handle: &tauri::AppHandle) -> Result<OpenAIClient, String> {
+    let (config, _) = config::load_effective_config(app_handle)
+        .map_err(|e| format!("Failed to load config: {}", e))?;
+    Ok(OpenAIClient::new().with_base_url(config.proxy.openai_base_url))
+}
+
 /// Refresh usage data for all accounts
 #[tauri::command]
 pub async fn refresh_all_usage(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<(AccountId, Result<UsageSnapshot, String>)>, String> {
     let accounts = state.store.load_accounts()
         .map_err(|e| e.to_string())?;
 
-    let client = OpenAIClient::new();
-    let mut results = Vec::new();
-
-    for account in accounts {
-        let result = match client.fetch_usage(&account).await {
-            Ok(usage) => {
-                if let Err(e) = state.store.save_usage_snapshot(&usage) {
-                    Err(format!("Failed to save usage: {}", e))
-                } else {
-                    Ok(usage)
-                }
-            }
-            Err(e) => Err(e.to_string()),
-        };
-        results.push((account.id, result));
-    }
+    let client = configured_client(&app_handle)?;
+    let results = usage::refresh_usage_with_progress(
+        &client,
+        &state.store,
+        accounts,
+        &state.usage_progress,
+    ).await;
 
     // Refresh routing engine with new usage data
     refresh_routing_engine(&state).await?;
@@ -232,6 +325,7 @@ pub async fn refresh_all_usage(
 /// Refresh usage for a single account
 #[tauri::command]
 pub async fn refresh_account_usage(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     id: AccountId,
 ) -> Result<UsageSnapshot, String> {
@@ -239,7 +333,7 @@ pub async fn refresh_account_usage(
         .map_err(|e| format!("Failed to load account: {}", e))?
         .ok_or_else(|| "Account not found".to_string())?;
 
-    let client = OpenAIClient::new();
+    let client = configured_client(&app_handle)?;
     let usage = client.fetch_usage(&account).await
         .map_err(|e| e.to_string())?;
 
@@ -251,6 +345,36 @@ pub async fn refresh_account_usage(
     Ok(usage)
 }
 
+/// Fetch an account's spend (dollars) for a `YYYY-MM-DD` date range; `until` defaults to today
+#[tauri::command]
+pub async fn fetch_usage_range(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: AccountId,
+    since: String,
+    until: Option<String>,
+) -> Result<f64, String> {
+    let parse = |s: &str| {
+        chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", s, e))
+    };
+    let start = parse(&since)?;
+    let end = match until {
+        Some(until) => parse(&until)?,
+        None => chrono::Utc::now().date_naive(),
+    };
+    let range = UsageRange::new(start, end).map_err(|e| e.to_string())?;
+
+    let account = state.store.load_account(id)
+        .map_err(|e| format!("Failed to load account: {}", e))?
+        .ok_or_else(|| "Account not found".to_string())?;
+
+    configured_client(&app_handle)?
+        .fetch_usage_range(&account, range)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Routing Commands
 // ============================================================================
@@ -285,6 +409,34 @@ pub async fn clear_routing_sessions(
     Ok(())
 }
 
+/// List sticky sessions with the account each is pinned to
+#[tauri::command]
+pub async fn list_routing_sessions(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SessionInfo>, String> {
+    Ok(state.routing_engine.sessions().await)
+}
+
+/// Unpin one sticky session by its id or a unique prefix of it
+#[tauri::command]
+pub async fn revoke_routing_session(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+) -> Result<String, String> {
+    let revoked = state.routing_engine.revoke_session(&session_id).map_err(|e| e.to_string())?;
+    info!("Revoked sticky session {}", revoked);
+    Ok(revoked)
+}
+
+/// Zero the per-account request and error counters shown in account statuses
+#[tauri::command]
+pub async fn reset_account_counters(
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state.routing_engine.reset_traffic_counters();
+    Ok(())
+}
+
 // ============================================================================
 // Import/Export Commands
 // ============================================================================
@@ -304,28 +456,86 @@ pub async fn export_accounts(
     })
 }
 
+/// Export accounts encrypted with a passphrase; only those in `ids` when
+/// given, otherwise all of them.
+///
+/// The returned string is safe to write to disk: api_keys are only readable
+/// with the passphrase (AES-256-GCM, key derived with Argon2id).
+#[tauri::command]
+pub async fn export_accounts_encrypted(
+    state: tauri::State<'_, AppState>,
+    passphrase: String,
+    ids: Option<Vec<AccountId>>,
+) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".to_string());
+    }
+
+    let mut export = export_accounts(state).await?;
+    if let Some(ids) = ids {
+        export.accounts.retain(|account| ids.contains(&account.id));
+        if export.accounts.is_empty() {
+            return Err("None of the selected accounts exist".to_string());
+        }
+    }
+    let json = serde_json::to_string(&export)
+        .map_err(|e| format!("Failed to serialize export: {}", e))?;
+
+    EncryptedStore::encrypt_with_passphrase(&json, &passphrase)
+        .map_err(|e| e.to_string())
+}
+
 /// Import accounts from a JSON structure
 #[tauri::command]
 pub async fn import_accounts(
     state: tauri::State<'_, AppState>,
     export: AccountExport,
+    preserve_ids: Option<bool>,
+) -> Result<Vec<Account>, String> {
+    import_account_export(&state, export, preserve_ids.unwrap_or(false)).await
+}
+
+/// Import accounts from a blob produced by `export_accounts_encrypted`
+#[tauri::command]
+pub async fn import_accounts_encrypted(
+    state: tauri::State<'_, AppState>,
+    data: String,
+    passphrase: String,
+    preserve_ids: Option<bool>,
+) -> Result<Vec<Account>, String> {
+    let json = EncryptedStore::decrypt_with_passphrase(&data, &passphrase)
+        .map_err(|e| e.to_string())?;
+    let export: AccountExport = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid export data: {}", e))?;
+
+    import_account_export(&state, export, preserve_ids.unwrap_or(false)).await
+}
+
+/// Save every account of an export, under a fresh ID unless `preserve_ids`
+/// is set and the original ID is still free
+async fn import_account_export(
+    state: &AppState,
+    export: AccountExport,
+    preserve_ids: bool,
 ) -> Result<Vec<Account>, String> {
     let mut imported = Vec::new();
 
     for mut account in export.accounts {
-        // Generate new ID to avoid conflicts
-        account.id = Uuid::new_v4();
         account.created_at = chrono::Utc::now();
         account.updated_at = chrono::Utc::now();
         account.last_used = None;
 
-        state.store.save_account(&account)
+        validate_account(&account)
             .map_err(|e| format!("Failed to import account {}: {}", account.label, e))?;
-        
+
+        let label = account.label.clone();
+        let account = state.store.import_account(account, preserve_ids)
+            .map_err(|e| format!("Failed to import account {}: {}", label, e))?;
+
         imported.push(account);
     }
 
-    refresh_routing_engine(&state).await?;
+    refresh_routing_engine(state).await?;
 
     info!("Imported {} accounts", imported.len());
     Ok(imported)
@@ -334,18 +544,79 @@ pub async fn import_accounts(
 /// Validate an API key without saving
 #[tauri::command]
 pub async fn validate_api_key(
+    app_handle: tauri::AppHandle,
     api_key: String,
     org_id: Option<String>,
 ) -> Result<ValidationResult, String> {
-    let client = OpenAIClient::new();
+    let client = configured_client(&app_handle)?;
     client.validate_key(&api_key, org_id.as_deref()).await
         .map_err(|e| e.to_string())
 }
 
+// ============================================================================
+// Maintenance Commands
+// ============================================================================
+
+/// Compact the database, returning the number of bytes reclaimed
+#[tauri::command]
+pub async fn vacuum_database(state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    let reclaimed = state.store.vacuum().map_err(|e| e.to_string())?;
+    info!("Vacuumed database, reclaimed {} bytes", reclaimed);
+    Ok(reclaimed)
+}
+
+/// Delete all accounts, usage snapshots and routing state for a clean
+/// reinstall. Does nothing unless `confirm` is set; the configuration is
+/// reset to defaults unless `keep_config` is set.
+#[tauri::command]
+pub async fn factory_reset(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    confirm: bool,
+    keep_config: bool,
+) -> Result<PurgeSummary, String> {
+    if !confirm {
+        return Err("Factory reset not confirmed".to_string());
+    }
+
+    let purged = state.store.purge_all()
+        .map_err(|e| format!("Failed to reset database: {}", e))?;
+
+    state.routing_engine.update_accounts(Vec::new(), HashMap::new()).await;
+    state.routing_engine.clear_sessions();
+    state.routing_engine.reset_traffic_counters();
+
+    if !keep_config {
+        config::reset_config(&app_handle).map_err(|e| e.to_string())?;
+    }
+
+    warn!(
+        "Factory reset: deleted {} accounts and {} usage snapshots",
+        purged.accounts, purged.usage_snapshots
+    );
+    Ok(purged)
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+/// Reject an account with invalid fields before it is persisted
+fn validate_account(account: &Account) -> Result<(), CommandError> {
+    account.validate().map_err(CommandError::from)
+}
+
+/// Refuse to remove a locked account; missing accounts pass through
+fn ensure_unlocked(state: &AppState, id: AccountId) -> Result<(), CommandError> {
+    let account = state.store.load_account(id)
+        .map_err(|e| format!("Failed to load account: {}", e))?;
+
+    match account {
+        Some(account) => account.validate_removal().map_err(CommandError::from),
+        None => Ok(()),
+    }
+}
+
 /// Refresh the routing engine with current accounts and usage
 async fn refresh_routing_engine(state: &AppState) -> Result<(), String> {
     let accounts = state.store.load_accounts()
@@ -362,6 +633,31 @@ async fn refresh_routing_engine(state: &AppState) -> Result<(), String> {
     Ok(())
 }
 
+/// Event telling the frontend to re-fetch accounts
+pub const RELOAD_EVENT: &str = "reload";
+
+/// How often circuits are checked for a reset that needs a warmup
+const CIRCUIT_WARMUP_SECS: u64 = 5;
+
+/// Reload accounts changed outside the GUI, e.g. added from the CLI while
+/// it was open. Runs when a second instance is launched.
+async fn reload_accounts(state: &AppState) -> Result<(), String> {
+    refresh_routing_engine(state).await?;
+    info!("Reloaded accounts after a second launch");
+    Ok(())
+}
+
+/// Patch a single account in the routing engine after an edit.
+///
+/// Cheaper than `refresh_routing_engine`, which reloads every account; use
+/// that one for bulk operations such as import.
+async fn refresh_routing_account(state: &AppState, account: &Account) {
+    let usage = state.store.load_latest_usage(account.id).ok().flatten();
+    state.routing_engine
+        .update_single_account(account.clone(), usage)
+        .await;
+}
+
 // ============================================================================
 // Tauri Plugin Setup
 // ============================================================================
@@ -384,6 +680,19 @@ pub fn run() {
                     #[cfg(target_os = "macos")]
                     app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
                 });
+
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(state) = handle.try_state::<AppState>() else {
+                    return;
+                };
+                match reload_accounts(&state).await {
+                    Ok(()) => {
+                        let _ = handle.emit(RELOAD_EVENT, ());
+                    }
+                    Err(e) => error!("Failed to reload accounts: {}", e),
+                }
+            });
         }))
         .setup(|app| {
             // Initialize tracing
@@ -396,7 +705,13 @@ pub fn run() {
 
             // Get paths
             let db_path = config::get_db_path(app.handle())?;
-            let master_key = config::get_master_key(app.handle())?;
+            // Make sure a config file exists, then apply env overrides on top
+            config::load_config(app.handle())?;
+            let (config, _) = config::load_effective_config(app.handle())?;
+            let master_key = config::get_master_key(app.handle(), config.allow_default_master_key)?;
+            if config.proxy.api_key == models::DEFAULT_PROXY_API_KEY {
+                warn!("Proxy is using the built-in default API key - generate a new one in settings");
+            }
 
             // Initialize encrypted store
             let store = Arc::new(EncryptedStore::open(&db_path, &master_key)
@@ -414,9 +729,37 @@ pub fn run() {
             }
 
             // Initialize routing engine
-            let config = config::load_config(app.handle())?;
             let routing_engine = Arc::new(RoutingEngine::new(config.routing.strategy));
+
+            // Forward account availability changes so the UI needn't poll
+            let mut status_rx = routing_engine.subscribe_status_changes();
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+                loop {
+                    match status_rx.recv().await {
+                        Ok(change) => {
+                            let _ = handle.emit(routing::ACCOUNT_STATUS_EVENT, &change);
+                        }
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
             
+            // Warm up accounts as their circuits reset
+            if config.routing.warmup_on_enable {
+                let engine = routing_engine.clone();
+                let client = configured_client(app.handle())?;
+                tauri::async_runtime::spawn(async move {
+                    let mut ticks = tokio::time::interval(std::time::Duration::from_secs(CIRCUIT_WARMUP_SECS));
+                    loop {
+                        ticks.tick().await;
+                        proxy::warm_up_reset_circuits(&client, &engine).await;
+                    }
+                });
+            }
+
             // Use tokio runtime to update accounts
             let rt = tokio::runtime::Handle::current();
             rt.block_on(async {
@@ -426,11 +769,28 @@ pub fn run() {
             // Create usage poller
             let usage_poller = Arc::new(UsagePoller::new());
 
+            // Forward usage refresh progress to the frontend as Tauri events
+            let (usage_progress, mut progress_rx) = tokio::sync::broadcast::channel(64);
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+                loop {
+                    match progress_rx.recv().await {
+                        Ok(progress) => {
+                            let _ = handle.emit(usage::USAGE_PROGRESS_EVENT, &progress);
+                        }
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+
             // Create app state
             let app_state = AppState {
                 store,
                 routing_engine,
                 usage_poller,
+                usage_progress,
             };
 
             app.manage(app_state);
@@ -455,34 +815,52 @@ pub fn run() {
             add_account,
             update_account,
             remove_account,
+            merge_accounts,
             get_account,
             list_accounts,
             list_account_statuses,
             toggle_account_enabled,
+            set_account_locked,
+            update_account_key,
             
             // Usage data
             get_account_usage,
             refresh_all_usage,
             refresh_account_usage,
+            fetch_usage_range,
             
             // Routing
             get_routing_stats,
             set_routing_strategy,
             clear_routing_sessions,
+            list_routing_sessions,
+            revoke_routing_session,
+            reset_account_counters,
             
             // Proxy server
             start_proxy_server,
             stop_proxy_server,
             get_proxy_status,
+            rotate_proxy_key,
+            get_recent_requests,
             
             // Import/Export
             export_accounts,
+            export_accounts_encrypted,
             import_accounts,
+            import_accounts_encrypted,
             validate_api_key,
             
+            // Maintenance
+            vacuum_database,
+            factory_reset,
+            
             // Configuration
             load_app_config,
             save_app_config,
+            patch_app_config,
+            generate_proxy_api_key,
+            get_effective_config,
             update_proxy_configuration,
             update_routing_configuration,
             set_master_key,
@@ -505,3 +883,30 @@ pub fn run() {
             let _ = (app_handle, event);
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reload_picks_up_accounts_added_elsewhere() {
+        let store = Arc::new(EncryptedStore::open_in_memory("test_master_key").unwrap());
+        let (usage_progress, _) = tokio::sync::broadcast::channel(1);
+        let state = AppState {
+            store: store.clone(),
+            routing_engine: Arc::new(RoutingEngine::new(RoutingStrategy::LeastUtilized)),
+            usage_poller: Arc::new(UsagePoller::new()),
+            usage_progress,
+        };
+
+        // Saved straight to the database, as the CLI would
+        let account = Account::new("From CLI".to_string(), "sk-test".to_string());
+        store.save_account(&account).unwrap();
+        assert!(state.routing_engine.get_account_statuses().await.is_empty());
+
+        reload_accounts(&state).await.unwrap();
+        let statuses = state.routing_engine.get_account_statuses().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].account.id, account.id);
+    }
+}
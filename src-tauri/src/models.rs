@@ -20,6 +20,9 @@ pub struct Account {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_used: Option<DateTime<Utc>>,
+    /// Read-only: only `enabled` may change until the account is unlocked
+    #[serde(default)]
+    pub locked: bool,
 }
 
 impl Account {
@@ -38,6 +41,7 @@ impl Account {
             created_at: now,
             updated_at: now,
             last_used: None,
+            locked: false,
         }
     }
 
@@ -61,6 +65,153 @@ impl Account {
         self.priority = priority;
         self
     }
+
+    /// Check that the account may be removed: locked accounts have to be
+    /// unlocked first
+    pub fn validate_removal(&self) -> Result<(), Vec<FieldError>> {
+        if self.locked {
+            Err(vec![FieldError::new("account", "is locked; unlock it before removing it")])
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that `updated` only differs from this account where a lock
+    /// allows it; unlocked accounts accept any edit
+    pub fn validate_edit(&self, updated: &Account) -> Result<(), Vec<FieldError>> {
+        if !self.locked {
+            return Ok(());
+        }
+
+        let changed = [
+            ("label", self.label != updated.label),
+            ("api_key", self.api_key != updated.api_key),
+            ("org_id", self.org_id != updated.org_id),
+            ("model_scope", self.model_scope != updated.model_scope),
+            ("daily_limit", self.daily_limit != updated.daily_limit),
+            ("monthly_limit", self.monthly_limit != updated.monthly_limit),
+            ("priority", self.priority != updated.priority),
+        ];
+        let errors: Vec<FieldError> = changed
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| FieldError::new(field, "cannot change while the account is locked"))
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check the account for nonsensical values before it is persisted
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        if self.label.trim().is_empty() {
+            errors.push(FieldError::new("label", "must not be empty"));
+        }
+
+        if !self.api_key.starts_with("sk-")
+            || self.api_key.len() < 20
+            || self.api_key.contains(char::is_whitespace)
+        {
+            errors.push(FieldError::new(
+                "api_key",
+                "must start with sk-, be at least 20 characters and contain no whitespace",
+            ));
+        }
+
+        if matches!(&self.org_id, Some(org) if org.trim().is_empty()) {
+            errors.push(FieldError::new("org_id", "must not be empty when set"));
+        }
+
+        if self.model_scope.iter().any(|m| m.trim().is_empty()) {
+            errors.push(FieldError::new("model_scope", "must not contain empty model names"));
+        }
+
+        for (field, limit) in [("daily_limit", self.daily_limit), ("monthly_limit", self.monthly_limit)] {
+            if matches!(limit, Some(l) if !l.is_finite() || l <= 0.0) {
+                errors.push(FieldError::new(field, "must be a positive amount"));
+            }
+        }
+
+        if let (Some(daily), Some(monthly)) = (self.daily_limit, self.monthly_limit) {
+            if daily > monthly {
+                errors.push(FieldError::new("daily_limit", "must not exceed monthly_limit"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A validation failure tied to a single account field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Join errors into one human-readable line
+    pub fn join(errors: &[FieldError]) -> String {
+        errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+/// Error returned to the frontend by account commands. Validation failures
+/// keep their per-field messages so forms can highlight the inputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommandError {
+    Validation { fields: Vec<FieldError> },
+    Other { message: String },
+}
+
+impl From<Vec<FieldError>> for CommandError {
+    fn from(fields: Vec<FieldError>) -> Self {
+        CommandError::Validation { fields }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Other { message }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Validation { fields } => {
+                write!(f, "Invalid account: {}", FieldError::join(fields))
+            }
+            CommandError::Other { message } => f.write_str(message),
+        }
+    }
 }
 
 /// Account status combining account config with usage data
@@ -70,6 +221,99 @@ pub struct AccountStatus {
     pub usage: UsageSnapshot,
     pub is_available: bool,
     pub disable_reason: Option<String>,
+    /// Requests routed to the account since start or the last counter reset
+    #[serde(default)]
+    pub request_count: u64,
+    /// Upstream errors reported for the account over the same period
+    #[serde(default)]
+    pub error_count: u64,
+    /// Request body bytes sent upstream over the same period
+    #[serde(default)]
+    pub bytes_sent: u64,
+    /// Response body bytes received from upstream over the same period
+    #[serde(default)]
+    pub bytes_received: u64,
+    /// Latest rate-limit headers seen on an upstream response
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitSnapshot>,
+    /// Most recent upstream failure, to explain a tripped circuit
+    #[serde(default)]
+    pub last_error: Option<LastError>,
+    /// Smoothed upstream latency in milliseconds, once one has been observed
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+}
+
+/// Longest upstream error message kept for an account
+const LAST_ERROR_MESSAGE_LEN: usize = 200;
+
+/// Most recent upstream failure seen for an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastError {
+    /// HTTP status, `None` when the request never got a response
+    pub status: Option<u16>,
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+impl LastError {
+    pub fn new(status: Option<u16>, message: &str) -> Self {
+        Self {
+            status,
+            message: message.trim().chars().take(LAST_ERROR_MESSAGE_LEN).collect(),
+            at: Utc::now(),
+        }
+    }
+
+    /// Build from an upstream error response, keeping only `error.message`
+    /// so nothing else from the body is retained
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("Upstream returned {}", status));
+        Self::new(Some(status), &message)
+    }
+}
+
+/// How long an exhausted quota is assumed to last when the response carried
+/// no `x-ratelimit-reset-*` header for it
+const RATE_LIMIT_FALLBACK_HOLD_SECS: i64 = 60;
+
+/// Remaining upstream quota as reported by `x-ratelimit-*` headers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitSnapshot {
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// When the request quota refills, from `x-ratelimit-reset-requests`
+    #[serde(default)]
+    pub requests_reset_at: Option<DateTime<Utc>>,
+    /// When the token quota refills, from `x-ratelimit-reset-tokens`
+    #[serde(default)]
+    pub tokens_reset_at: Option<DateTime<Utc>>,
+    pub observed_at: DateTime<Utc>,
+}
+
+impl RateLimitSnapshot {
+    /// Whether either quota was reported as used up
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_requests == Some(0) || self.remaining_tokens == Some(0)
+    }
+
+    /// When every exhausted quota has refilled, or `None` if none is exhausted
+    pub fn held_until(&self) -> Option<DateTime<Utc>> {
+        let fallback = self.observed_at + chrono::Duration::seconds(RATE_LIMIT_FALLBACK_HOLD_SECS);
+        let requests = (self.remaining_requests == Some(0))
+            .then(|| self.requests_reset_at.unwrap_or(fallback));
+        let tokens = (self.remaining_tokens == Some(0))
+            .then(|| self.tokens_reset_at.unwrap_or(fallback));
+        requests.max(tokens)
+    }
+
+    /// Whether an exhausted quota is still waiting to refill at `now`
+    pub fn is_held_at(&self, now: DateTime<Utc>) -> bool {
+        self.held_until().is_some_and(|until| now < until)
+    }
 }
 
 /// Usage snapshot for an account at a point in time
@@ -178,6 +422,27 @@ pub struct CreateAccountRequest {
     pub priority: Option<i32>,
 }
 
+impl CreateAccountRequest {
+    /// Build the account this request describes, without validating it
+    pub fn into_account(self) -> Account {
+        let mut account = Account::new(self.label, self.api_key);
+
+        if let Some(org_id) = self.org_id {
+            account = account.with_org_id(org_id);
+        }
+        if let Some(models) = self.model_scope {
+            account = account.with_model_scope(models);
+        }
+        if let Some(daily) = self.daily_limit {
+            account = account.with_limits(Some(daily), self.monthly_limit);
+        }
+        if let Some(priority) = self.priority {
+            account = account.with_priority(priority);
+        }
+        account
+    }
+}
+
 /// Account update request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateAccountRequest {
@@ -212,6 +477,10 @@ impl Default for RoutingStrategy {
 pub struct RoutingConfig {
     pub strategy: RoutingStrategy,
     pub min_request_interval_ms: u64,
+    /// Send a small request to an account when it is enabled or its circuit
+    /// resets, before real traffic
+    #[serde(default)]
+    pub warmup_on_enable: bool,
 }
 
 impl Default for RoutingConfig {
@@ -219,10 +488,14 @@ impl Default for RoutingConfig {
         Self {
             strategy: RoutingStrategy::LeastUtilized,
             min_request_interval_ms: 100,
+            warmup_on_enable: false,
         }
     }
 }
 
+/// Built-in proxy client key, shared by every install
+pub const DEFAULT_PROXY_API_KEY: &str = "sk-codex-manager";
+
 /// Proxy server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyServerConfig {
@@ -235,7 +508,7 @@ impl Default for ProxyServerConfig {
     fn default() -> Self {
         Self {
             bind_addr: "127.0.0.1:8080".to_string(),
-            api_key: "sk-codex-manager".to_string(),
+            api_key: DEFAULT_PROXY_API_KEY.to_string(),
             openai_base_url: "https://api.openai.com".to_string(),
         }
     }
@@ -246,6 +519,14 @@ impl Default for ProxyServerConfig {
 pub struct AppConfig {
     pub proxy: ProxyServerConfig,
     pub routing: RoutingConfig,
+    /// Fall back to the built-in master key when none is stored instead of
+    /// refusing to start; only on by default in debug builds
+    #[serde(default = "default_allow_default_master_key")]
+    pub allow_default_master_key: bool,
+}
+
+fn default_allow_default_master_key() -> bool {
+    cfg!(debug_assertions)
 }
 
 impl Default for AppConfig {
@@ -253,6 +534,7 @@ impl Default for AppConfig {
         Self {
             proxy: ProxyServerConfig::default(),
             routing: RoutingConfig::default(),
+            allow_default_master_key: default_allow_default_master_key(),
         }
     }
 }
@@ -284,6 +566,40 @@ pub struct ProxyStatus {
     pub bind_addr: String,
     pub request_count: u64,
     pub uptime_seconds: u64,
+    /// Body bytes sent upstream, over all accounts
+    #[serde(default)]
+    pub bytes_sent: u64,
+    /// Body bytes received from upstream, over all accounts
+    #[serde(default)]
+    pub bytes_received: u64,
+}
+
+/// One proxied request, without any of its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestSummary {
+    pub at: DateTime<Utc>,
+    pub model: String,
+    pub account_label: String,
+    pub status: u16,
+    pub latency_ms: u64,
+}
+
+/// A sticky session pinned to an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// Shortest leading part of the session id that no other session shares,
+    /// so it always revokes exactly this session
+    pub session_prefix: String,
+    pub account_id: AccountId,
+    pub account_label: String,
+    pub age_seconds: u64,
+}
+
+/// Rows removed by a factory reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeSummary {
+    pub accounts: usize,
+    pub usage_snapshots: usize,
 }
 
 /// Import/Export data structure
@@ -294,6 +610,27 @@ pub struct AccountExport {
     pub accounts: Vec<Account>,
 }
 
+/// Progress of a bulk usage refresh, emitted once per finished account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRefreshProgress {
+    pub account_id: AccountId,
+    pub account_label: String,
+    /// Accounts finished so far, including this one
+    pub completed: usize,
+    pub total: usize,
+    pub error: Option<String>,
+}
+
+/// An account becoming available or unavailable for routing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountStatusChange {
+    pub account_id: AccountId,
+    pub account_label: String,
+    pub is_available: bool,
+    /// Why the account is now unavailable, if it is
+    pub disable_reason: Option<String>,
+}
+
 /// Validation result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
@@ -301,3 +638,61 @@ pub struct ValidationResult {
     pub org_id: Option<String>,
     pub error: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_create_request_serializes_field_errors() {
+        let request = CreateAccountRequest {
+            label: " ".to_string(),
+            api_key: "sk-0123456789abcdefghij".to_string(),
+            org_id: None,
+            model_scope: None,
+            daily_limit: Some(-5.0),
+            monthly_limit: None,
+            priority: None,
+        };
+
+        let error = CommandError::from(request.into_account().validate().unwrap_err());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "validation",
+                "fields": [
+                    { "field": "label", "message": "must not be empty" },
+                    { "field": "daily_limit", "message": "must be a positive amount" },
+                ],
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "Invalid account: label must not be empty; daily_limit must be a positive amount"
+        );
+
+        let other = CommandError::from("Account not found".to_string());
+        assert_eq!(
+            serde_json::to_value(&other).unwrap(),
+            serde_json::json!({ "kind": "other", "message": "Account not found" })
+        );
+    }
+
+    #[test]
+    fn test_locked_account_removal_is_a_validation_error() {
+        let mut account = Account::new("Primary".to_string(), "sk-0123456789abcdefghij".to_string());
+        assert!(account.validate_removal().is_ok());
+
+        account.locked = true;
+        let error = CommandError::from(account.validate_removal().unwrap_err());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "validation",
+                "fields": [
+                    { "field": "account", "message": "is locked; unlock it before removing it" },
+                ],
+            })
+        );
+    }
+}
@@ -12,14 +12,18 @@ use serde_json::Value;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::models::{ProxyServerConfig, ProxyStatus, RequestContext, RoutingDecision};
+use crate::models::{
+    LastError, ProxyServerConfig, ProxyStatus, RateLimitSnapshot, RequestContext,
+    RequestSummary, RoutingDecision,
+};
 use crate::routing::{RoutingEngine, RoutingReason};
+use crate::usage::OpenAIClient;
 
 /// Shared state for the proxy server
 #[derive(Clone)]
@@ -29,6 +33,40 @@ pub struct ProxyState {
     pub http_client: reqwest::Client,
     pub request_count: Arc<AtomicU64>,
     pub start_time: Arc<RwLock<Option<Instant>>>,
+    /// Previous client key, still accepted until the instant it expires
+    pub retired_key: Arc<RwLock<Option<(String, Instant)>>>,
+    /// Summaries of the latest upstream requests, for the recent requests table
+    pub recent_requests: Arc<RecentRequests>,
+}
+
+/// Requests kept for the recent requests table
+const RECENT_REQUESTS_CAPACITY: usize = 100;
+
+/// The last `capacity` request summaries, oldest first
+pub struct RecentRequests {
+    capacity: usize,
+    entries: std::sync::Mutex<std::collections::VecDeque<RequestSummary>>,
+}
+
+impl RecentRequests {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: std::sync::Mutex::new(std::collections::VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, summary: RequestSummary) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(summary);
+    }
+
+    pub fn snapshot(&self) -> Vec<RequestSummary> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
 }
 
 impl ProxyState {
@@ -39,6 +77,33 @@ impl ProxyState {
             http_client: reqwest::Client::new(),
             request_count: Arc::new(AtomicU64::new(0)),
             start_time: Arc::new(RwLock::new(None)),
+            retired_key: Arc::new(RwLock::new(None)),
+            recent_requests: Arc::new(RecentRequests::new(RECENT_REQUESTS_CAPACITY)),
+        }
+    }
+
+    /// Switch to `new_key`, accepting the current key for `grace` more
+    pub async fn rotate_key(&self, new_key: String, grace: Duration) {
+        let mut config = self.config.write().await;
+        let old_key = std::mem::replace(&mut config.api_key, new_key);
+        *self.retired_key.write().await = Some((old_key, Instant::now() + grace));
+    }
+
+    /// Check a client key against the current and, within its grace window, the retired key
+    async fn accepts_key(&self, provided_key: &str) -> bool {
+        if provided_key == self.config.read().await.api_key {
+            return true;
+        }
+
+        let mut retired = self.retired_key.write().await;
+        match retired.as_ref() {
+            Some((key, expires)) if Instant::now() < *expires => provided_key == key,
+            Some(_) => {
+                info!("Grace period ended, retiring previous proxy API key");
+                *retired = None;
+                false
+            }
+            None => false,
         }
     }
 }
@@ -136,12 +201,15 @@ impl ProxyServer {
         let uptime_seconds = start_time
             .map(|t| t.elapsed().as_secs())
             .unwrap_or(0);
+        let (bytes_sent, bytes_received) = self.state.routing_engine.total_traffic_bytes();
 
         ProxyStatus {
             running: self.shutdown_tx.is_some(),
             bind_addr: self.bind_addr.clone(),
             request_count: self.state.request_count.load(Ordering::Relaxed),
             uptime_seconds,
+            bytes_sent,
+            bytes_received,
         }
     }
 }
@@ -158,11 +226,8 @@ async fn auth_middleware(
         .and_then(|v| v.to_str().ok())
         .and_then(|v| v.strip_prefix("Bearer "));
 
-    let config = state.config.read().await;
-
     if let Some(provided_key) = auth_header {
-        if provided_key == config.api_key {
-            drop(config);
+        if state.accepts_key(provided_key).await {
             return Ok(next.run(request).await);
         }
     }
@@ -314,17 +379,37 @@ async fn handle_openai_request(
 
     let is_streaming = body.get("stream").and_then(|v| v.as_bool()).unwrap_or(false);
 
+    let payload = serde_json::to_vec(&body).map_err(|_| StatusCode::BAD_REQUEST)?;
+    state
+        .routing_engine
+        .record_bytes_sent(decision.account_id, payload.len() as u64);
+
+    let started = Instant::now();
     let upstream_resp = upstream_req
-        .json(&body)
+        .body(payload)
         .send()
         .await
         .map_err(|e| {
             error!("Upstream request failed: {}", e);
+            state
+                .routing_engine
+                .record_last_error(decision.account_id, LastError::new(None, &e.to_string()));
             state.routing_engine.report_error(decision.account_id, true);
             StatusCode::BAD_GATEWAY
         })?;
 
     let status = upstream_resp.status();
+    state.recent_requests.push(RequestSummary {
+        at: chrono::Utc::now(),
+        model: model.clone(),
+        account_label: decision.account_label.clone(),
+        status: status.as_u16(),
+        latency_ms: started.elapsed().as_millis() as u64,
+    });
+
+    if let Some(snapshot) = parse_rate_limit_headers(upstream_resp.headers()) {
+        state.routing_engine.record_rate_limits(decision.account_id, snapshot);
+    }
 
     if !status.is_success() {
         let error_body = upstream_resp
@@ -332,6 +417,13 @@ async fn handle_openai_request(
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
         warn!("Upstream error {}: {}", status, error_body);
+        state
+            .routing_engine
+            .record_bytes_received(decision.account_id, error_body.len() as u64);
+        state.routing_engine.record_last_error(
+            decision.account_id,
+            LastError::from_response(status.as_u16(), &error_body),
+        );
         state.routing_engine.report_error(decision.account_id, status.as_u16() >= 500);
 
         return Ok(Response::builder()
@@ -342,9 +434,15 @@ async fn handle_openai_request(
     }
 
     state.routing_engine.report_success(decision.account_id);
+    state.routing_engine.record_latency(decision.account_id, started.elapsed());
 
     if is_streaming {
+        let engine = state.routing_engine.clone();
+        let account_id = decision.account_id;
         let stream = upstream_resp.bytes_stream().map(move |result| {
+            if let Ok(chunk) = &result {
+                engine.record_bytes_received(account_id, chunk.len() as u64);
+            }
             result.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
         });
 
@@ -362,6 +460,9 @@ async fn handle_openai_request(
         .bytes()
         .await
         .map_err(|_| StatusCode::BAD_GATEWAY)?;
+    state
+        .routing_engine
+        .record_bytes_received(decision.account_id, response_body.len() as u64);
 
     Ok(Response::builder()
         .status(StatusCode::OK)
@@ -370,6 +471,58 @@ async fn handle_openai_request(
         .unwrap())
 }
 
+/// Read the `x-ratelimit-remaining-*` and `x-ratelimit-reset-*` headers from
+/// an upstream response
+fn parse_rate_limit_headers(headers: &reqwest::header::HeaderMap) -> Option<RateLimitSnapshot> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let remaining = |name: &str| header(name).and_then(|v| v.parse::<u64>().ok());
+
+    let remaining_requests = remaining("x-ratelimit-remaining-requests");
+    let remaining_tokens = remaining("x-ratelimit-remaining-tokens");
+    if remaining_requests.is_none() && remaining_tokens.is_none() {
+        return None;
+    }
+
+    let observed_at = chrono::Utc::now();
+    let reset_at = |name: &str| header(name).and_then(parse_reset_duration).map(|d| observed_at + d);
+
+    Some(RateLimitSnapshot {
+        remaining_requests,
+        remaining_tokens,
+        requests_reset_at: reset_at("x-ratelimit-reset-requests"),
+        tokens_reset_at: reset_at("x-ratelimit-reset-tokens"),
+        observed_at,
+    })
+}
+
+/// Parse a reset header such as `1s`, `6m0s` or `250ms`
+fn parse_reset_duration(value: &str) -> Option<chrono::Duration> {
+    let mut rest = value;
+    let mut total_ms = 0.0;
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (number, tail) = rest.split_at(number_end);
+        let unit_end = tail.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(tail.len());
+        let (unit, next) = tail.split_at(unit_end);
+
+        let unit_ms = match unit {
+            "ms" => 1.0,
+            "s" => 1_000.0,
+            "m" => 60_000.0,
+            "h" => 3_600_000.0,
+            _ => return None,
+        };
+        total_ms += number.parse::<f64>().ok()? * unit_ms;
+        rest = next;
+    }
+
+    Some(chrono::Duration::milliseconds(total_ms.ceil() as i64))
+}
+
 /// Extract session ID from request body
 fn extract_session_id(body: &Value) -> Option<String> {
     if let Some(messages) = body.get("messages").and_then(|v| v.as_array()) {
@@ -384,6 +537,41 @@ fn extract_session_id(body: &Value) -> Option<String> {
     None
 }
 
+/// Prime an account with a cheap request, closing its circuit and seeding
+/// its latency when it answers
+pub async fn warm_up_account(
+    client: &OpenAIClient,
+    engine: &RoutingEngine,
+    account: &crate::models::Account,
+) -> anyhow::Result<Duration> {
+    let latency = client.warm_up(account).await?;
+    engine.report_success(account.id);
+    engine.record_latency(account.id, latency);
+    Ok(latency)
+}
+
+/// Warm up accounts whose circuit has reset after its cooldown, so a dead
+/// account is caught by a cheap request rather than the next real one.
+/// A failed warmup counts as a fatal error against the account.
+///
+/// Returns how many accounts answered.
+pub async fn warm_up_reset_circuits(client: &OpenAIClient, engine: &RoutingEngine) -> usize {
+    let mut recovered = 0;
+    for account in engine.reset_circuit_accounts().await {
+        match warm_up_account(client, engine, &account).await {
+            Ok(_) => {
+                info!("Warmup succeeded for {}, closing circuit", account.label);
+                recovered += 1;
+            }
+            Err(e) => {
+                debug!("Warmup failed for {}: {}", account.label, e);
+                engine.report_error(account.id, true);
+            }
+        }
+    }
+    recovered
+}
+
 use axum::Json;
 
 /// Global proxy server instance (managed by Tauri state)
@@ -401,6 +589,7 @@ pub async fn start_proxy_server(
         return Err("Proxy server already running".to_string());
     }
 
+    let config = crate::config::proxy_with_env_overrides(config).map_err(|e| e.to_string())?;
     let mut new_server = ProxyServer::new(routing_engine.inner().clone(), config);
     new_server.start().await.map_err(|e| e.to_string())?;
     
@@ -421,6 +610,39 @@ pub async fn stop_proxy_server() -> Result<(), String> {
     }
 }
 
+/// Grace period during which the previous proxy key keeps working
+const DEFAULT_KEY_ROTATION_GRACE_SECS: u64 = 300;
+
+/// Tauri command: Rotate the client API key of the running proxy.
+///
+/// The old key keeps working for `grace_secs` (default 5 minutes) so
+/// clients can switch over without dropped requests.
+#[tauri::command]
+pub async fn rotate_proxy_key(new_key: String, grace_secs: Option<u64>) -> Result<(), String> {
+    if new_key.trim().is_empty() {
+        return Err("New key must not be empty".to_string());
+    }
+
+    let server = PROXY_SERVER.read().await;
+    let server = server.as_ref().ok_or_else(|| "Proxy server not running".to_string())?;
+
+    let grace = Duration::from_secs(grace_secs.unwrap_or(DEFAULT_KEY_ROTATION_GRACE_SECS));
+    server.state.rotate_key(new_key, grace).await;
+
+    info!("Rotated proxy API key; previous key accepted for {:?}", grace);
+    Ok(())
+}
+
+/// Tauri command: The latest requests that reached upstream, oldest first
+#[tauri::command]
+pub async fn get_recent_requests() -> Result<Vec<RequestSummary>, String> {
+    let server = PROXY_SERVER.read().await;
+    Ok(server
+        .as_ref()
+        .map(|s| s.state.recent_requests.snapshot())
+        .unwrap_or_default())
+}
+
 /// Tauri command: Get proxy status
 #[tauri::command]
 pub async fn get_proxy_status() -> Result<ProxyStatus, String> {
@@ -434,6 +656,185 @@ pub async fn get_proxy_status() -> Result<ProxyStatus, String> {
             bind_addr: "127.0.0.1:8080".to_string(),
             request_count: 0,
             uptime_seconds: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Account, RoutingStrategy};
+    use tower::ServiceExt;
+
+    /// Proxy state in front of a mock `upstream` served on a loopback port,
+    /// routing between `accounts`
+    async fn mock_proxy(upstream: Router, accounts: Vec<Account>) -> ProxyState {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, upstream).await.unwrap() });
+
+        let engine = Arc::new(RoutingEngine::new(RoutingStrategy::LeastUtilized));
+        engine.update_accounts(accounts, std::collections::HashMap::new()).await;
+        let config = ProxyServerConfig {
+            openai_base_url: format!("http://{}", upstream_addr),
+            ..ProxyServerConfig::default()
+        };
+        ProxyState::new(engine, config)
+    }
+
+    /// Authenticated JSON POST to the proxy, ready for a body
+    fn post_json(uri: &str) -> axum::http::request::Builder {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(header::AUTHORIZATION, "Bearer sk-codex-manager")
+            .header(header::CONTENT_TYPE, "application/json")
+    }
+
+    async fn health_status(app: &Router, key: &str) -> StatusCode {
+        let request = Request::builder()
+            .uri("/health")
+            .header(header::AUTHORIZATION, format!("Bearer {}", key))
+            .body(Body::empty())
+            .unwrap();
+
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_rotated_key_accepts_both_during_grace() {
+        let engine = Arc::new(RoutingEngine::new(RoutingStrategy::LeastUtilized));
+        let state = ProxyState::new(engine, ProxyServerConfig::default());
+        let app = ProxyServer::build_router(state.clone());
+
+        state.rotate_key("sk-new-key".to_string(), Duration::from_secs(60)).await;
+
+        assert_eq!(health_status(&app, "sk-codex-manager").await, StatusCode::OK);
+        assert_eq!(health_status(&app, "sk-new-key").await, StatusCode::OK);
+        assert_eq!(health_status(&app, "sk-other").await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_rotated_key_rejects_old_key_after_grace() {
+        let engine = Arc::new(RoutingEngine::new(RoutingStrategy::LeastUtilized));
+        let state = ProxyState::new(engine, ProxyServerConfig::default());
+        let app = ProxyServer::build_router(state.clone());
+
+        state.rotate_key("sk-new-key".to_string(), Duration::from_millis(50)).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(health_status(&app, "sk-codex-manager").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(health_status(&app, "sk-new-key").await, StatusCode::OK);
+        assert!(state.retired_key.read().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers_feed_routing() {
+        let upstream = Router::new().route(
+            "/v1/chat/completions",
+            post(|| async {
+                (
+                    [
+                        ("x-ratelimit-remaining-requests", "0"),
+                        ("x-ratelimit-remaining-tokens", "1500"),
+                        ("x-ratelimit-reset-requests", "6m0s"),
+                    ],
+                    Json(serde_json::json!({"id": "chatcmpl-1"})),
+                )
+            }),
+        );
+        let account = Account::new("Limited".to_string(), "sk-test".to_string());
+        let state = mock_proxy(upstream, vec![account]).await;
+        let engine = state.routing_engine.clone();
+        let app = ProxyServer::build_router(state);
+
+        let chat = || {
+            post_json("/v1/chat/completions")
+                .body(Body::from(r#"{"model":"gpt-4","messages":[]}"#))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(chat()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let statuses = engine.get_account_statuses().await;
+        let rate_limit = statuses[0].rate_limit.as_ref().unwrap();
+        assert_eq!(rate_limit.remaining_requests, Some(0));
+        assert_eq!(rate_limit.remaining_tokens, Some(1500));
+        assert_eq!(
+            rate_limit.requests_reset_at,
+            Some(rate_limit.observed_at + chrono::Duration::minutes(6))
+        );
+        assert_eq!(rate_limit.tokens_reset_at, None);
+
+        // No requests left upstream, so the account is skipped
+        let response = app.oneshot(chat()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_warmup_closes_circuit_and_records_latency() {
+        let upstream = Router::new().route("/v1/models", get(|| async { StatusCode::OK }));
+        let account = Account::new("Dormant".to_string(), "sk-test".to_string());
+        let state = mock_proxy(upstream, vec![account.clone()]).await;
+        let engine = state.routing_engine.clone();
+        for _ in 0..3 {
+            engine.report_error(account.id, true);
+        }
+        assert!(engine.resolve_account(&RequestContext::new("gpt-4".to_string())).await.is_err());
+
+        let client = OpenAIClient::new().with_base_url(state.config.read().await.openai_base_url.clone());
+        warm_up_account(&client, &engine, &account).await.unwrap();
+        assert!(engine.latency(account.id).is_some());
+        assert!(engine.resolve_account(&RequestContext::new("gpt-4".to_string())).await.is_ok());
+
+        // The UI sees the observed latency alongside the other counters
+        let statuses = engine.get_account_statuses().await;
+        assert!(statuses[0].latency_ms.is_some());
+    }
+
+    #[test]
+    fn test_parse_reset_duration() {
+        let ms = |v: &str| parse_reset_duration(v).map(|d| d.num_milliseconds());
+        assert_eq!(ms("1s"), Some(1_000));
+        assert_eq!(ms("6m0s"), Some(360_000));
+        assert_eq!(ms("1h2m3.5s"), Some(3_723_500));
+        assert_eq!(ms("250ms"), Some(250));
+        assert_eq!(ms(""), None);
+        assert_eq!(ms("12"), None);
+        assert_eq!(ms("5d"), None);
+    }
+
+    #[tokio::test]
+    async fn test_byte_counters_track_bodies() {
+        const RESPONSE: &str = r#"{"id":"chatcmpl-1"}"#;
+        const REQUEST: &str = r#"{"model":"gpt-4","messages":[]}"#;
+
+        let upstream = Router::new().route(
+            "/v1/chat/completions",
+            post(|| async { ([(header::CONTENT_TYPE, "application/json")], RESPONSE) }),
+        );
+        let account = Account::new("Counted".to_string(), "sk-test".to_string());
+        let state = mock_proxy(upstream, vec![account]).await;
+        let engine = state.routing_engine.clone();
+        let app = ProxyServer::build_router(state);
+
+        for _ in 0..2 {
+            let request = post_json("/v1/chat/completions")
+                .body(Body::from(REQUEST))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let statuses = engine.get_account_statuses().await;
+        assert_eq!(statuses[0].bytes_sent, 2 * REQUEST.len() as u64);
+        assert_eq!(statuses[0].bytes_received, 2 * RESPONSE.len() as u64);
+        assert_eq!(
+            engine.total_traffic_bytes(),
+            (2 * REQUEST.len() as u64, 2 * RESPONSE.len() as u64)
+        );
+    }
+}
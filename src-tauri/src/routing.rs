@@ -1,15 +1,50 @@
 use anyhow::Result;
 use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, instrument, trace, warn};
 
 use crate::models::{
-    Account, AccountFilter, AccountStatus, RequestContext, RoutingDecision, RoutingStats,
-    RoutingStrategy, UsageSnapshot,
+    Account, AccountFilter, AccountStatus, AccountStatusChange, LastError, RateLimitSnapshot,
+    RequestContext, RoutingDecision, RoutingStats, RoutingStrategy, SessionInfo, UsageSnapshot,
 };
 
+/// Event name the frontend listens on for account availability changes
+pub const ACCOUNT_STATUS_EVENT: &str = "account-status-changed";
+
+/// Status changes buffered for slow subscribers before they lag
+const STATUS_CHANGE_CAPACITY: usize = 64;
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Fewest characters of a session id shown when listing sessions
+const SESSION_PREFIX_LEN: usize = 8;
+
+/// Shortest prefix of `id`, at least `SESSION_PREFIX_LEN` characters, that
+/// no other id in `ids` starts with; the whole id when none is
+fn unique_prefix<'a>(id: &str, ids: impl Iterator<Item = &'a str> + Clone) -> String {
+    let mut end = 0;
+    for (count, (i, c)) in id.char_indices().enumerate() {
+        end = i + c.len_utf8();
+        let prefix = &id[..end];
+        if count + 1 >= SESSION_PREFIX_LEN
+            && !ids.clone().any(|other| other != id && other.starts_with(prefix))
+        {
+            break;
+        }
+    }
+    id[..end].to_string()
+}
+
+/// The account a sticky session is pinned to, and since when
+struct SessionPin {
+    account_id: uuid::Uuid,
+    pinned_at: Instant,
+}
+
 /// Routing reason for decision tracking
 #[derive(Debug, Clone)]
 pub enum RoutingReason {
@@ -63,14 +98,29 @@ struct AccountRouteState {
     last_used: Option<Instant>,
 }
 
+/// Per-account traffic counters, kept across account updates
+#[derive(Debug, Default)]
+struct AccountTraffic {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
 /// The routing engine - determines which account to use for requests
 pub struct RoutingEngine {
     strategy: RoutingStrategy,
     accounts: Arc<RwLock<Vec<AccountStatus>>>,
-    session_map: DashMap<String, uuid::Uuid>,
+    session_map: DashMap<String, SessionPin>,
     circuit_states: DashMap<uuid::Uuid, AccountRouteState>,
     round_robin_index: RwLock<usize>,
     min_request_interval: Duration,
+    traffic: DashMap<uuid::Uuid, AccountTraffic>,
+    rate_limits: DashMap<uuid::Uuid, RateLimitSnapshot>,
+    last_errors: DashMap<uuid::Uuid, LastError>,
+    /// Smoothed upstream latency per account
+    latencies: DashMap<uuid::Uuid, Duration>,
+    status_changes: tokio::sync::broadcast::Sender<AccountStatusChange>,
 }
 
 impl RoutingEngine {
@@ -83,6 +133,30 @@ impl RoutingEngine {
             circuit_states: DashMap::new(),
             round_robin_index: RwLock::new(0),
             min_request_interval: Duration::from_millis(100),
+            traffic: DashMap::new(),
+            rate_limits: DashMap::new(),
+            last_errors: DashMap::new(),
+            latencies: DashMap::new(),
+            status_changes: tokio::sync::broadcast::channel(STATUS_CHANGE_CAPACITY).0,
+        }
+    }
+
+    /// Receive a change whenever `update_accounts` or `update_single_account`
+    /// flips an existing account's availability
+    pub fn subscribe_status_changes(&self) -> tokio::sync::broadcast::Receiver<AccountStatusChange> {
+        self.status_changes.subscribe()
+    }
+
+    /// Announce `status` if its availability differs from `previous`
+    fn announce_if_changed(&self, previous: Option<&AccountStatus>, status: &AccountStatus) {
+        if previous.is_some_and(|p| p.is_available != status.is_available) {
+            // No subscribers is fine; nobody is watching yet
+            let _ = self.status_changes.send(AccountStatusChange {
+                account_id: status.account.id,
+                account_label: status.account.label.clone(),
+                is_available: status.is_available,
+                disable_reason: status.disable_reason.clone(),
+            });
         }
     }
 
@@ -110,33 +184,167 @@ impl RoutingEngine {
                 .cloned()
                 .unwrap_or_else(|| UsageSnapshot::new(account.id));
 
-            let is_available = account.enabled
-                && !usage.is_over_limit(&account)
-                && self.is_circuit_available(account.id).await;
-
-            let disable_reason = if !account.enabled {
-                Some("Account disabled".to_string())
-            } else if usage.is_over_limit(&account) {
-                Some("Over usage limit".to_string())
-            } else if !self.is_circuit_available(account.id).await {
-                Some("Circuit breaker open".to_string())
-            } else {
-                None
-            };
-
-            statuses.push(AccountStatus {
-                account,
-                usage,
-                is_available,
-                disable_reason,
-            });
+            statuses.push(self.build_status(account, usage).await);
         }
 
         let mut guard = self.accounts.write().await;
+        for status in &statuses {
+            let previous = guard.iter().find(|s| s.account.id == status.account.id);
+            self.announce_if_changed(previous, status);
+        }
         *guard = statuses;
         debug!("Updated {} accounts in routing engine", guard.len());
     }
 
+    /// Patch a single account in place without rebuilding the whole set.
+    ///
+    /// If `usage` is `None` the account keeps its current usage snapshot.
+    /// Accounts not yet known to the engine are appended.
+    pub async fn update_single_account(&self, account: Account, usage: Option<UsageSnapshot>) {
+        let mut guard = self.accounts.write().await;
+        let position = guard.iter().position(|s| s.account.id == account.id);
+
+        let usage = usage
+            .or_else(|| position.map(|i| guard[i].usage.clone()))
+            .unwrap_or_else(|| UsageSnapshot::new(account.id));
+
+        let account_id = account.id;
+        let status = self.build_status(account, usage).await;
+
+        self.announce_if_changed(position.map(|i| &guard[i]), &status);
+        match position {
+            Some(i) => guard[i] = status,
+            None => guard.push(status),
+        }
+        debug!("Updated account {} in routing engine", account_id);
+    }
+
+    /// Compute the routing status for an account
+    async fn build_status(&self, account: Account, usage: UsageSnapshot) -> AccountStatus {
+        let circuit_available = self.is_circuit_available(account.id).await;
+        let over_limit = usage.is_over_limit(&account);
+
+        let is_available = account.enabled && !over_limit && circuit_available;
+
+        let disable_reason = if !account.enabled {
+            Some("Account disabled".to_string())
+        } else if over_limit {
+            Some("Over usage limit".to_string())
+        } else if !circuit_available {
+            Some("Circuit breaker open".to_string())
+        } else {
+            None
+        };
+
+        let (request_count, error_count) = self.traffic_counts(account.id);
+        let (bytes_sent, bytes_received) = self.traffic_bytes(account.id);
+        let rate_limit = self.rate_limit(account.id);
+        let last_error = self.last_error(account.id);
+        let latency_ms = self.latency_ms(account.id);
+
+        AccountStatus {
+            account,
+            usage,
+            is_available,
+            disable_reason,
+            request_count,
+            error_count,
+            bytes_sent,
+            bytes_received,
+            rate_limit,
+            last_error,
+            latency_ms,
+        }
+    }
+
+    /// Requests and errors counted for an account
+    fn traffic_counts(&self, account_id: uuid::Uuid) -> (u64, u64) {
+        self.traffic
+            .get(&account_id)
+            .map(|t| (t.requests.load(Ordering::Relaxed), t.errors.load(Ordering::Relaxed)))
+            .unwrap_or((0, 0))
+    }
+
+    /// Body bytes sent to and received from upstream for an account
+    fn traffic_bytes(&self, account_id: uuid::Uuid) -> (u64, u64) {
+        self.traffic
+            .get(&account_id)
+            .map(|t| {
+                (
+                    t.bytes_sent.load(Ordering::Relaxed),
+                    t.bytes_received.load(Ordering::Relaxed),
+                )
+            })
+            .unwrap_or((0, 0))
+    }
+
+    /// Body bytes sent to and received from upstream over all accounts
+    pub fn total_traffic_bytes(&self) -> (u64, u64) {
+        self.traffic.iter().fold((0, 0), |(sent, received), t| {
+            (
+                sent + t.bytes_sent.load(Ordering::Relaxed),
+                received + t.bytes_received.load(Ordering::Relaxed),
+            )
+        })
+    }
+
+    /// Count a request body sent upstream for an account
+    pub fn record_bytes_sent(&self, account_id: uuid::Uuid, bytes: u64) {
+        self.traffic
+            .entry(account_id)
+            .or_default()
+            .bytes_sent
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Count response body bytes received from upstream for an account
+    pub fn record_bytes_received(&self, account_id: uuid::Uuid, bytes: u64) {
+        self.traffic
+            .entry(account_id)
+            .or_default()
+            .bytes_received
+            .fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Zero every account's request, error and byte counters
+    pub fn reset_traffic_counters(&self) {
+        self.traffic.clear();
+    }
+
+    /// Store the rate-limit headers from the latest upstream response
+    pub fn record_rate_limits(&self, account_id: uuid::Uuid, snapshot: RateLimitSnapshot) {
+        if snapshot.is_exhausted() {
+            debug!("Upstream reports account {} rate limit exhausted", account_id);
+        }
+        self.rate_limits.insert(account_id, snapshot);
+    }
+
+    /// Latest rate-limit snapshot for an account
+    pub fn rate_limit(&self, account_id: uuid::Uuid) -> Option<RateLimitSnapshot> {
+        self.rate_limits.get(&account_id).map(|s| s.clone())
+    }
+
+    /// Whether the account reported an exhausted quota that has not reset yet
+    fn is_rate_limited(&self, account_id: uuid::Uuid) -> bool {
+        self.rate_limits
+            .get(&account_id)
+            .is_some_and(|s| s.is_held_at(chrono::Utc::now()))
+    }
+
+    /// Whether the account can take a request for `model` right now; the
+    /// rate-limit hold and circuit are checked here rather than in
+    /// `is_available` because they change between account updates
+    fn eligible(&self, status: &AccountStatus, model: &str) -> bool {
+        status.is_available
+            && self.supports_model(&status.account, model)
+            && !self.is_rate_limited(status.account.id)
+            && self
+                .circuit_states
+                .get(&status.account.id)
+                .map(|state| state.can_attempt())
+                .unwrap_or(true)
+    }
+
     /// Check if account circuit is available
     async fn is_circuit_available(&self, account_id: uuid::Uuid) -> bool {
         self.circuit_states
@@ -153,15 +361,7 @@ impl RoutingEngine {
         // Filter to available accounts that support the model
         let candidates: Vec<&AccountStatus> = accounts
             .iter()
-            .filter(|s| {
-                s.is_available
-                    && self.supports_model(&s.account, &ctx.model)
-                    && self
-                        .circuit_states
-                        .get(&s.account.id)
-                        .map(|state| state.can_attempt())
-                        .unwrap_or(true)
-            })
+            .filter(|s| self.eligible(s, &ctx.model))
             .collect();
 
         if candidates.is_empty() {
@@ -182,6 +382,11 @@ impl RoutingEngine {
         if let Some(mut state) = self.circuit_states.get_mut(&selected.account.id) {
             state.last_used = Some(Instant::now());
         }
+        self.traffic
+            .entry(selected.account.id)
+            .or_default()
+            .requests
+            .fetch_add(1, Ordering::Relaxed);
 
         trace!(
             "Selected account {} ({}) for model {}",
@@ -259,15 +464,17 @@ impl RoutingEngine {
         session_id: Option<&str>,
     ) -> &'a AccountStatus {
         if let Some(session) = session_id {
-            if let Some(account_id) = self.session_map.get(session) {
-                if let Some(status) = candidates.iter().find(|s| s.account.id == *account_id) {
+            if let Some(pin) = self.session_map.get(session) {
+                if let Some(status) = candidates.iter().find(|s| s.account.id == pin.account_id) {
                     return status;
                 }
             }
 
             let selected = self.select_least_utilized(candidates).await;
-            self.session_map
-                .insert(session.to_string(), selected.account.id);
+            self.session_map.insert(
+                session.to_string(),
+                SessionPin { account_id: selected.account.id, pinned_at: Instant::now() },
+            );
             return selected;
         }
 
@@ -312,8 +519,44 @@ impl RoutingEngine {
         state.circuit = CircuitState::Closed;
     }
 
+    /// Fold an observed upstream latency into the account's moving average
+    pub fn record_latency(&self, account_id: uuid::Uuid, latency: Duration) {
+        self.latencies
+            .entry(account_id)
+            .and_modify(|avg| {
+                *avg = avg.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+            })
+            .or_insert(latency);
+    }
+
+    /// Smoothed upstream latency for an account, once one has been observed
+    pub fn latency(&self, account_id: uuid::Uuid) -> Option<Duration> {
+        self.latencies.get(&account_id).map(|l| *l)
+    }
+
+    /// Smoothed upstream latency in whole milliseconds, for status reports
+    fn latency_ms(&self, account_id: uuid::Uuid) -> Option<u64> {
+        self.latency(account_id).map(|l| l.as_millis() as u64)
+    }
+
+    /// Remember the latest upstream failure for an account
+    pub fn record_last_error(&self, account_id: uuid::Uuid, error: LastError) {
+        self.last_errors.insert(account_id, error);
+    }
+
+    /// Latest upstream failure recorded for an account
+    pub fn last_error(&self, account_id: uuid::Uuid) -> Option<LastError> {
+        self.last_errors.get(&account_id).map(|e| e.clone())
+    }
+
     /// Report error for an account (may open circuit breaker)
     pub fn report_error(&self, account_id: uuid::Uuid, is_fatal: bool) {
+        self.traffic
+            .entry(account_id)
+            .or_default()
+            .errors
+            .fetch_add(1, Ordering::Relaxed);
+
         let mut state = self
             .circuit_states
             .entry(account_id)
@@ -338,6 +581,23 @@ impl RoutingEngine {
         }
     }
 
+    /// Enabled accounts whose open circuit has sat out its cooldown, so the
+    /// next request routed would retry them
+    pub async fn reset_circuit_accounts(&self) -> Vec<Account> {
+        self.accounts
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.account.enabled)
+            .filter(|s| {
+                self.circuit_states.get(&s.account.id).is_some_and(|state| {
+                    matches!(state.circuit, CircuitState::Open { .. }) && state.circuit.can_attempt()
+                })
+            })
+            .map(|s| s.account.clone())
+            .collect()
+    }
+
     /// Get current routing statistics
     pub async fn get_stats(&self) -> RoutingStats {
         let accounts = self.accounts.read().await;
@@ -360,8 +620,277 @@ impl RoutingEngine {
         self.session_map.clear();
     }
 
-    /// Get all account statuses
+    /// Active sticky sessions, oldest pin first
+    pub async fn sessions(&self) -> Vec<SessionInfo> {
+        let accounts = self.accounts.read().await;
+        let mut pins: Vec<(String, uuid::Uuid, Instant)> = self
+            .session_map
+            .iter()
+            .map(|e| (e.key().clone(), e.account_id, e.pinned_at))
+            .collect();
+        pins.sort_by_key(|(_, _, pinned_at)| *pinned_at);
+
+        pins.iter()
+            .map(|(session, account_id, pinned_at)| SessionInfo {
+                session_prefix: unique_prefix(session, pins.iter().map(|(id, _, _)| id.as_str())),
+                account_id: *account_id,
+                account_label: accounts
+                    .iter()
+                    .find(|s| s.account.id == *account_id)
+                    .map(|s| s.account.label.clone())
+                    .unwrap_or_else(|| "(removed)".to_string()),
+                age_seconds: pinned_at.elapsed().as_secs(),
+            })
+            .collect()
+    }
+
+    /// Drop the pin of the session whose id is, or uniquely starts with,
+    /// `session`, returning the full session id
+    pub fn revoke_session(&self, session: &str) -> Result<String> {
+        if self.session_map.remove(session).is_some() {
+            return Ok(session.to_string());
+        }
+
+        let matches: Vec<String> = self
+            .session_map
+            .iter()
+            .map(|e| e.key().clone())
+            .filter(|id| id.starts_with(session))
+            .collect();
+        match matches.as_slice() {
+            [id] => {
+                self.session_map.remove(id);
+                Ok(id.clone())
+            }
+            [] => anyhow::bail!("No sticky session matches {}", session),
+            _ => anyhow::bail!("{} sticky sessions match {}; use a longer prefix", matches.len(), session),
+        }
+    }
+
+    /// Get all account statuses, with current traffic counts, rate limits and
+    /// last errors
     pub async fn get_account_statuses(&self) -> Vec<AccountStatus> {
-        self.accounts.read().await.clone()
+        let mut statuses = self.accounts.read().await.clone();
+        for status in &mut statuses {
+            (status.request_count, status.error_count) = self.traffic_counts(status.account.id);
+            (status.bytes_sent, status.bytes_received) = self.traffic_bytes(status.account.id);
+            status.rate_limit = self.rate_limit(status.account.id);
+            status.last_error = self.last_error(status.account.id);
+            status.latency_ms = self.latency_ms(status.account.id);
+        }
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_account(label: &str, priority: i32) -> Account {
+        Account::new(label.to_string(), "sk-test".to_string()).with_priority(priority)
+    }
+
+    #[tokio::test]
+    async fn test_status_change_announced_when_availability_flips() {
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+        let mut changes = engine.subscribe_status_changes();
+
+        let account = create_test_account("Watched", 1);
+        engine
+            .update_accounts(vec![account.clone()], std::collections::HashMap::new())
+            .await;
+        // Newly added accounts have no previous state to flip from
+        assert!(changes.try_recv().is_err());
+
+        let mut disabled = account.clone();
+        disabled.enabled = false;
+        engine
+            .update_accounts(vec![disabled.clone()], std::collections::HashMap::new())
+            .await;
+        let change = changes.try_recv().unwrap();
+        assert_eq!(change.account_id, account.id);
+        assert!(!change.is_available);
+        assert_eq!(change.disable_reason.as_deref(), Some("Account disabled"));
+
+        // Unchanged availability stays quiet
+        engine.update_single_account(disabled, None).await;
+        assert!(changes.try_recv().is_err());
+
+        engine.update_single_account(account, None).await;
+        assert!(changes.try_recv().unwrap().is_available);
+    }
+
+    #[tokio::test]
+    async fn test_update_single_account_patches_in_place() {
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+
+        let first = create_test_account("First", 1);
+        let second = create_test_account("Second", 2);
+
+        let mut second_usage = UsageSnapshot::new(second.id);
+        second_usage.monthly_usage = 42.0;
+
+        let mut usage_map = std::collections::HashMap::new();
+        usage_map.insert(second.id, second_usage);
+
+        engine
+            .update_accounts(vec![first.clone(), second.clone()], usage_map)
+            .await;
+
+        // Disable the first account; the second is never passed in again
+        let mut edited = first.clone();
+        edited.enabled = false;
+        edited.label = "First (edited)".to_string();
+        engine.update_single_account(edited, None).await;
+
+        let statuses = engine.get_account_statuses().await;
+        assert_eq!(statuses.len(), 2);
+
+        let first_status = statuses.iter().find(|s| s.account.id == first.id).unwrap();
+        assert_eq!(first_status.account.label, "First (edited)");
+        assert!(!first_status.is_available);
+        assert_eq!(first_status.disable_reason.as_deref(), Some("Account disabled"));
+
+        let second_status = statuses.iter().find(|s| s.account.id == second.id).unwrap();
+        assert_eq!(second_status.account, second);
+        assert_eq!(second_status.usage.monthly_usage, 42.0);
+        assert!(second_status.is_available);
+    }
+
+    #[tokio::test]
+    async fn test_traffic_counts_surface_in_statuses() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        let busy = create_test_account("Busy", 2);
+        let idle = create_test_account("Idle", 1);
+        engine
+            .update_accounts(vec![busy.clone(), idle.clone()], std::collections::HashMap::new())
+            .await;
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+        for _ in 0..3 {
+            let decision = engine.resolve_account(&ctx).await.unwrap();
+            assert_eq!(decision.account_id, busy.id);
+        }
+        engine.report_error(busy.id, false);
+
+        // Counts survive an account refresh
+        engine.update_single_account(busy.clone(), None).await;
+
+        let counts = |statuses: &[AccountStatus], id| {
+            let status = statuses.iter().find(|s| s.account.id == id).unwrap();
+            (status.request_count, status.error_count)
+        };
+        let statuses = engine.get_account_statuses().await;
+        assert_eq!(counts(&statuses, busy.id), (3, 1));
+        assert_eq!(counts(&statuses, idle.id), (0, 0));
+
+        engine.reset_traffic_counters();
+        let statuses = engine.get_account_statuses().await;
+        assert_eq!(counts(&statuses, busy.id), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_last_error_surfaces_in_statuses() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        let account = create_test_account("Flaky", 1);
+        engine
+            .update_accounts(vec![account.clone()], std::collections::HashMap::new())
+            .await;
+        assert!(engine.get_account_statuses().await[0].last_error.is_none());
+
+        let body = r#"{"error":{"message":"Rate limit reached for gpt-4"},"echo":"sk-secret"}"#;
+        engine.record_last_error(account.id, LastError::from_response(429, body));
+        engine.report_error(account.id, false);
+
+        let statuses = engine.get_account_statuses().await;
+        let last_error = statuses[0].last_error.as_ref().unwrap();
+        assert_eq!(last_error.status, Some(429));
+        assert_eq!(last_error.message, "Rate limit reached for gpt-4");
+
+        // Bodies without an OpenAI error message are not kept at all
+        engine.record_last_error(account.id, LastError::from_response(502, "<html>sk-secret</html>"));
+        let last_error = engine.last_error(account.id).unwrap();
+        assert_eq!(last_error.message, "Upstream returned 502");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_hold_lasts_until_reset() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        let account = create_test_account("Limited", 1);
+        engine
+            .update_accounts(vec![account.clone()], std::collections::HashMap::new())
+            .await;
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        let now = chrono::Utc::now();
+        let exhausted = |reset_in: i64| RateLimitSnapshot {
+            remaining_requests: Some(0),
+            remaining_tokens: Some(1500),
+            requests_reset_at: Some(now + chrono::Duration::seconds(reset_in)),
+            tokens_reset_at: None,
+            observed_at: now,
+        };
+
+        engine.record_rate_limits(account.id, exhausted(30));
+        assert!(engine.resolve_account(&ctx).await.is_err());
+        // The hold is checked per request, not folded into availability
+        assert!(engine.get_account_statuses().await[0].is_available);
+
+        // Once the reported reset time passes the account is routable again
+        engine.record_rate_limits(account.id, exhausted(-1));
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, account.id);
+    }
+
+    #[tokio::test]
+    async fn test_reset_circuit_accounts_wait_for_cooldown() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        let cooled = create_test_account("Cooled", 1);
+        let fresh = create_test_account("Fresh", 2);
+        engine
+            .update_accounts(vec![cooled.clone(), fresh.clone()], std::collections::HashMap::new())
+            .await;
+
+        for _ in 0..3 {
+            engine.report_error(cooled.id, true);
+            engine.report_error(fresh.id, true);
+        }
+        assert!(engine.reset_circuit_accounts().await.is_empty());
+
+        // Backdate one circuit past its cooldown
+        engine.circuit_states.get_mut(&cooled.id).unwrap().circuit = CircuitState::Open {
+            since: Instant::now() - Duration::from_secs(61),
+        };
+        let reset: Vec<_> = engine.reset_circuit_accounts().await.iter().map(|a| a.id).collect();
+        assert_eq!(reset, vec![cooled.id]);
+    }
+
+    #[tokio::test]
+    async fn test_list_and_revoke_sessions() {
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+        let account = create_test_account("Pinned", 1);
+        engine
+            .update_accounts(vec![account.clone()], std::collections::HashMap::new())
+            .await;
+
+        for session in ["session-alpha-1", "session-alpha-2", "other-session"] {
+            let ctx = RequestContext::new("gpt-4".to_string()).with_session(session.to_string());
+            engine.resolve_account(&ctx).await.unwrap();
+        }
+
+        let sessions = engine.sessions().await;
+        let mut prefixes: Vec<&str> = sessions.iter().map(|s| s.session_prefix.as_str()).collect();
+        prefixes.sort();
+        assert_eq!(prefixes, vec!["other-se", "session-alpha-1", "session-alpha-2"]);
+        assert!(sessions.iter().all(|s| s.account_id == account.id && s.account_label == "Pinned"));
+
+        // A shared prefix is ambiguous; a unique one or the full id revokes
+        assert!(engine.revoke_session("session-alpha").is_err());
+        assert_eq!(engine.revoke_session("other").unwrap(), "other-session");
+        assert_eq!(engine.revoke_session("session-alpha-2").unwrap(), "session-alpha-2");
+        assert!(engine.revoke_session("other").is_err());
+
+        // With its sibling gone the remaining session's prefix shortens again
+        let remaining: Vec<String> = engine.sessions().await.into_iter().map(|s| s.session_prefix).collect();
+        assert_eq!(remaining, vec!["session-"]);
     }
 }
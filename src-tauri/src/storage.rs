@@ -8,19 +8,31 @@ use argon2::{
     Argon2, PasswordHasher,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
 use rand::rngs::OsRng;
 use rusqlite::{params, Connection, OptionalExtension};
+use sha2::Sha256;
 use std::path::Path;
 use std::sync::Mutex;
 
-use crate::models::{Account, AccountId, UsageSnapshot};
+use crate::models::{Account, AccountId, PurgeSummary, UsageSnapshot};
+
+/// Salt length for passphrase-encrypted exports
+const EXPORT_SALT_LEN: usize = 16;
 
 /// Manages encrypted SQLite storage for accounts and usage data
 pub struct EncryptedStore {
     conn: Mutex<Connection>,
     cipher: Aes256Gcm,
+    /// HMAC secret for api_key hashes; random per database, kept in metadata
+    key_hash_secret: Vec<u8>,
 }
 
+type HmacSha256 = Hmac<Sha256>;
+
+/// Metadata key under which the api_key hash secret is stored
+const KEY_HASH_SECRET_KEY: &str = "key_hash_secret";
+
 impl EncryptedStore {
     /// Initialize or open the encrypted database
     pub fn open(db_path: &Path, master_key: &str) -> Result<Self> {
@@ -29,11 +41,13 @@ impl EncryptedStore {
         // Derive encryption key from master password
         let cipher = Self::derive_cipher(master_key)?;
 
-        let store = Self { 
+        let mut store = Self { 
             conn: Mutex::new(conn), 
-            cipher 
+            cipher,
+            key_hash_secret: Vec::new(),
         };
         store.init_schema()?;
+        store.init_key_hashes()?;
 
         Ok(store)
     }
@@ -43,11 +57,13 @@ impl EncryptedStore {
         let conn = Connection::open_in_memory().context("Failed to open in-memory database")?;
 
         let cipher = Self::derive_cipher(master_key)?;
-        let store = Self { 
+        let mut store = Self { 
             conn: Mutex::new(conn), 
-            cipher 
+            cipher,
+            key_hash_secret: Vec::new(),
         };
         store.init_schema()?;
+        store.init_key_hashes()?;
 
         Ok(store)
     }
@@ -116,6 +132,64 @@ impl EncryptedStore {
         String::from_utf8(plaintext).context("Invalid UTF-8 in decrypted data")
     }
 
+    /// Encrypt data with a key derived from `passphrase` instead of the master key.
+    ///
+    /// Used for portable exports: the output is base64 of
+    /// `salt (16) || nonce (12) || ciphertext`, so it can be decrypted on any
+    /// machine that knows the passphrase.
+    pub fn encrypt_with_passphrase(plaintext: &str, passphrase: &str) -> Result<String> {
+        let mut salt = [0u8; EXPORT_SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = Self::derive_export_cipher(passphrase, &salt)?;
+
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let ciphertext = cipher
+            .encrypt(nonce, plaintext.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Encryption failed: {:?}", e))?;
+
+        let mut combined = Vec::with_capacity(EXPORT_SALT_LEN + 12 + ciphertext.len());
+        combined.extend_from_slice(&salt);
+        combined.extend_from_slice(&nonce_bytes);
+        combined.extend_from_slice(&ciphertext);
+
+        Ok(BASE64.encode(&combined))
+    }
+
+    /// Decrypt data produced by `encrypt_with_passphrase`
+    pub fn decrypt_with_passphrase(ciphertext_b64: &str, passphrase: &str) -> Result<String> {
+        let combined = BASE64
+            .decode(ciphertext_b64.trim())
+            .context("Invalid base64 encoding")?;
+
+        if combined.len() < EXPORT_SALT_LEN + 12 {
+            anyhow::bail!("Ciphertext too short");
+        }
+
+        let (salt, rest) = combined.split_at(EXPORT_SALT_LEN);
+        let (nonce_bytes, ciphertext) = rest.split_at(12);
+        let cipher = Self::derive_export_cipher(passphrase, salt)?;
+
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Decryption failed: wrong passphrase or corrupted data"))?;
+
+        String::from_utf8(plaintext).context("Invalid UTF-8 in decrypted data")
+    }
+
+    /// Derive the export cipher from a passphrase and an explicit salt
+    fn derive_export_cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+        let mut key_bytes = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key_bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to derive export key: {}", e))?;
+
+        let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes);
+        Ok(Aes256Gcm::new(key))
+    }
+
     /// Initialize database schema
     fn init_schema(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -164,9 +238,84 @@ impl EncryptedStore {
         )
         .context("Failed to initialize database schema")?;
 
+        let has_hash_column = conn
+            .prepare("SELECT api_key_hash FROM accounts LIMIT 0")
+            .is_ok();
+        if !has_hash_column {
+            conn.execute_batch(
+                "ALTER TABLE accounts ADD COLUMN api_key_hash TEXT;
+                 CREATE INDEX IF NOT EXISTS idx_accounts_key_hash ON accounts(api_key_hash);",
+            )
+            .context("Failed to add api_key_hash column")?;
+        }
+
+        let has_locked_column = conn
+            .prepare("SELECT locked FROM accounts LIMIT 0")
+            .is_ok();
+        if !has_locked_column {
+            conn.execute_batch("ALTER TABLE accounts ADD COLUMN locked INTEGER DEFAULT 0;")
+                .context("Failed to add locked column")?;
+        }
+
         Ok(())
     }
 
+    /// Load (or create) the hash secret and hash any keys stored before hashing existed
+    fn init_key_hashes(&mut self) -> Result<()> {
+        self.key_hash_secret = match self.get_metadata(KEY_HASH_SECRET_KEY)? {
+            Some(encoded) => BASE64.decode(encoded).context("Invalid key hash secret")?,
+            None => {
+                let mut secret = vec![0u8; 32];
+                OsRng.fill_bytes(&mut secret);
+                self.set_metadata(KEY_HASH_SECRET_KEY, &BASE64.encode(&secret))?;
+                secret
+            }
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, api_key_encrypted FROM accounts WHERE api_key_hash IS NULL",
+        )?;
+        let unhashed: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        for (id, encrypted_key) in unhashed {
+            // Keys that can't be decrypted are left unhashed rather than failing open
+            if let Ok(api_key) = self.decrypt(&encrypted_key) {
+                conn.execute(
+                    "UPDATE accounts SET api_key_hash = ?1 WHERE id = ?2",
+                    [self.key_hash(&api_key), id],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keyed hash of an api_key, for duplicate detection without decrypting
+    fn key_hash(&self, api_key: &str) -> String {
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.key_hash_secret)
+            .expect("HMAC accepts keys of any length");
+        mac.update(api_key.as_bytes());
+        BASE64.encode(mac.finalize().into_bytes())
+    }
+
+    /// Find an account that stores the given api_key, using its keyed hash
+    pub fn find_by_key_hash(&self, api_key: &str) -> Result<Option<AccountId>> {
+        let conn = self.conn.lock().unwrap();
+        let id = conn
+            .query_row(
+                "SELECT id FROM accounts WHERE api_key_hash = ?1 LIMIT 1",
+                [self.key_hash(api_key)],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        id.map(|id| id.parse().context("Invalid account id"))
+            .transpose()
+    }
+
     /// Save or update an account
     pub fn save_account(&self, account: &Account) -> Result<()> {
         let encrypted_key = self.encrypt(&account.api_key)?;
@@ -178,11 +327,12 @@ impl EncryptedStore {
             INSERT INTO accounts (
                 id, label, api_key_encrypted, org_id, model_scope,
                 daily_limit, monthly_limit, priority, enabled,
-                created_at, updated_at, last_used
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                created_at, updated_at, last_used, api_key_hash, locked
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
             ON CONFLICT(id) DO UPDATE SET
                 label = excluded.label,
                 api_key_encrypted = excluded.api_key_encrypted,
+                api_key_hash = excluded.api_key_hash,
                 org_id = excluded.org_id,
                 model_scope = excluded.model_scope,
                 daily_limit = excluded.daily_limit,
@@ -190,7 +340,8 @@ impl EncryptedStore {
                 priority = excluded.priority,
                 enabled = excluded.enabled,
                 updated_at = excluded.updated_at,
-                last_used = excluded.last_used
+                last_used = excluded.last_used,
+                locked = excluded.locked
             "#,
             params![
                 account.id.to_string(),
@@ -205,6 +356,8 @@ impl EncryptedStore {
                 account.created_at.to_rfc3339(),
                 account.updated_at.to_rfc3339(),
                 account.last_used.map(|t| t.to_rfc3339()),
+                self.key_hash(&account.api_key),
+                account.locked as i32,
             ],
         )
         .context("Failed to save account")?;
@@ -253,6 +406,7 @@ impl EncryptedStore {
                 last_used: row
                     .get::<Option<String>, _>("last_used")?
                     .map(|s| s.parse().unwrap()),
+                locked: row.get::<_, Option<i32>>("locked")?.unwrap_or(0) != 0,
             })
         })?;
 
@@ -302,6 +456,7 @@ impl EncryptedStore {
                     last_used: row
                         .get::<Option<String>, _>("last_used")?
                         .map(|s| s.parse().unwrap()),
+                    locked: row.get::<_, Option<i32>>("locked")?.unwrap_or(0) != 0,
                 })
             })
             .optional()?;
@@ -309,20 +464,117 @@ impl EncryptedStore {
         Ok(account)
     }
 
+    /// Check whether an account with this ID is stored
+    pub fn account_exists(&self, id: AccountId) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let exists = conn
+            .query_row("SELECT 1 FROM accounts WHERE id = ?1", [id.to_string()], |_| Ok(()))
+            .optional()?
+            .is_some();
+
+        Ok(exists)
+    }
+
+    /// Save an imported account, returning it with the ID it was stored under.
+    ///
+    /// With `preserve_id` the original ID is kept unless another account
+    /// already uses it; otherwise (or on a collision) a fresh ID is generated.
+    pub fn import_account(&self, mut account: Account, preserve_id: bool) -> Result<Account> {
+        if !preserve_id || self.account_exists(account.id)? {
+            account.id = uuid::Uuid::new_v4();
+        }
+
+        self.save_account(&account)?;
+        Ok(account)
+    }
+
+    /// Replace an account's api_key, keeping its id, creation time and usage
+    /// history.
+    ///
+    /// Returns `false` if the account doesn't exist.
+    pub fn update_account_key(&self, id: AccountId, new_key: &str) -> Result<bool> {
+        let encrypted_key = self.encrypt(new_key)?;
+
+        let conn = self.conn.lock().unwrap();
+        let rows = conn
+            .execute(
+                "UPDATE accounts SET api_key_encrypted = ?2, api_key_hash = ?3, updated_at = ?4 WHERE id = ?1",
+                params![
+                    id.to_string(),
+                    encrypted_key,
+                    self.key_hash(new_key),
+                    chrono::Utc::now().to_rfc3339(),
+                ],
+            )
+            .context("Failed to update account key")?;
+
+        Ok(rows > 0)
+    }
+
     /// Delete an account
     pub fn delete_account(&self, id: AccountId) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
-        let rows = conn.execute("DELETE FROM accounts WHERE id = ?1", [id.to_string()])?;
 
-        // Also delete usage snapshots
+        // Delete usage snapshots first; they reference the account
         conn.execute(
             "DELETE FROM usage_snapshots WHERE account_id = ?1",
             [id.to_string()],
         )?;
 
+        let rows = conn.execute("DELETE FROM accounts WHERE id = ?1", [id.to_string()])?;
+
         Ok(rows > 0)
     }
 
+    /// Move all usage snapshots from one account to another.
+    ///
+    /// Returns the number of snapshots reassigned.
+    pub fn reassign_usage(&self, from_id: AccountId, to_id: AccountId) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let moved = Self::reassign_usage_in(&tx, from_id, to_id)?;
+        tx.commit()?;
+
+        Ok(moved)
+    }
+
+    /// Merge `from_id` into `to_id`: its usage history moves to the target
+    /// and the source account is deleted, atomically.
+    pub fn merge_accounts(&self, from_id: AccountId, to_id: AccountId) -> Result<usize> {
+        if from_id == to_id {
+            anyhow::bail!("Cannot merge an account into itself");
+        }
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let target_exists = tx
+            .query_row("SELECT 1 FROM accounts WHERE id = ?1", [to_id.to_string()], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !target_exists {
+            anyhow::bail!("Target account not found: {}", to_id);
+        }
+
+        let moved = Self::reassign_usage_in(&tx, from_id, to_id)?;
+        let deleted = tx.execute("DELETE FROM accounts WHERE id = ?1", [from_id.to_string()])?;
+        if deleted == 0 {
+            anyhow::bail!("Source account not found: {}", from_id);
+        }
+
+        tx.commit()?;
+
+        Ok(moved)
+    }
+
+    fn reassign_usage_in(conn: &Connection, from_id: AccountId, to_id: AccountId) -> Result<usize> {
+        conn.execute(
+            "UPDATE usage_snapshots SET account_id = ?2 WHERE account_id = ?1",
+            [from_id.to_string(), to_id.to_string()],
+        )
+        .context("Failed to reassign usage snapshots")
+    }
+
     /// Save a usage snapshot
     pub fn save_usage_snapshot(&self, snapshot: &UsageSnapshot) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -376,6 +628,38 @@ impl EncryptedStore {
         Ok(snapshot)
     }
 
+    /// Compact the database file, returning how many bytes were reclaimed.
+    ///
+    /// Pruned snapshots and deleted accounts leave free pages behind that
+    /// SQLite only returns to the filesystem on `VACUUM`.
+    pub fn vacuum(&self) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let before = database_size(&conn)?;
+
+        conn.execute_batch("VACUUM").context("Failed to vacuum database")?;
+        // Also empties the WAL file; a no-op for databases not in WAL mode
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .context("Failed to checkpoint database")?;
+
+        let after = database_size(&conn)?;
+        Ok(before.saturating_sub(after))
+    }
+
+    /// Delete every account and usage snapshot in one transaction.
+    ///
+    /// The schema and metadata, including the key hash secret, are kept.
+    pub fn purge_all(&self) -> Result<PurgeSummary> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let usage_snapshots = tx.execute("DELETE FROM usage_snapshots", [])?;
+        let accounts = tx.execute("DELETE FROM accounts", [])?;
+
+        tx.commit().context("Failed to purge database")?;
+
+        Ok(PurgeSummary { accounts, usage_snapshots })
+    }
+
     /// Get database metadata
     pub fn get_metadata(&self, key: &str) -> Result<Option<String>> {
         let conn = self.conn.lock().unwrap();
@@ -399,3 +683,208 @@ impl EncryptedStore {
         Ok(())
     }
 }
+
+
+/// Database size in bytes, from its page count and page size
+fn database_size(conn: &Connection) -> Result<u64> {
+    let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get::<_, i64>(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get::<_, i64>(0))?;
+    Ok((pages * page_size) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AccountExport;
+
+    #[test]
+    fn test_passphrase_export_round_trip() {
+        let account = Account::new("Export".to_string(), "sk-export-secret".to_string())
+            .with_priority(3);
+        let export = AccountExport {
+            version: "0.3.0".to_string(),
+            exported_at: chrono::Utc::now(),
+            accounts: vec![account.clone()],
+        };
+
+        let json = serde_json::to_string(&export).unwrap();
+        let blob = EncryptedStore::encrypt_with_passphrase(&json, "correct horse").unwrap();
+
+        // The api_key must not be readable in the encrypted blob
+        assert!(!blob.contains("sk-export-secret"));
+
+        let decrypted = EncryptedStore::decrypt_with_passphrase(&blob, "correct horse").unwrap();
+        let restored: AccountExport = serde_json::from_str(&decrypted).unwrap();
+        assert_eq!(restored.accounts, vec![account]);
+    }
+
+    #[test]
+    fn test_passphrase_export_rejects_wrong_passphrase() {
+        let blob = EncryptedStore::encrypt_with_passphrase("{}", "correct horse").unwrap();
+
+        assert!(EncryptedStore::decrypt_with_passphrase(&blob, "battery staple").is_err());
+        assert!(EncryptedStore::decrypt_with_passphrase("dG9vIHNob3J0", "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_update_account_key_keeps_history() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Rotated".to_string(), "sk-old-key".to_string());
+        store.save_account(&account).unwrap();
+        store.save_usage_snapshot(&UsageSnapshot::new(account.id)).unwrap();
+
+        assert!(store.update_account_key(account.id, "sk-new-key").unwrap());
+
+        let rotated = store.load_account(account.id).unwrap().unwrap();
+        assert_eq!(rotated.api_key, "sk-new-key");
+        assert_eq!(rotated.created_at, account.created_at);
+        assert!(store.load_latest_usage(account.id).unwrap().is_some());
+        assert_eq!(store.find_by_key_hash("sk-new-key").unwrap(), Some(account.id));
+        assert_eq!(store.find_by_key_hash("sk-old-key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_merge_accounts_moves_usage_history() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let source = Account::new("Source".to_string(), "sk-same".to_string());
+        let target = Account::new("Target".to_string(), "sk-same".to_string());
+        store.save_account(&source).unwrap();
+        store.save_account(&target).unwrap();
+
+        for account_id in [source.id, source.id, target.id] {
+            store.save_usage_snapshot(&UsageSnapshot::new(account_id)).unwrap();
+        }
+
+        assert_eq!(store.merge_accounts(source.id, target.id).unwrap(), 2);
+
+        let count_for = |id: AccountId| -> i64 {
+            store
+                .conn
+                .lock()
+                .unwrap()
+                .query_row(
+                    "SELECT COUNT(*) FROM usage_snapshots WHERE account_id = ?1",
+                    [id.to_string()],
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(count_for(target.id), 3);
+        assert_eq!(count_for(source.id), 0);
+        assert!(store.load_account(source.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_merge_accounts_rolls_back_on_missing_target() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let source = Account::new("Source".to_string(), "sk-same".to_string());
+        store.save_account(&source).unwrap();
+        store.save_usage_snapshot(&UsageSnapshot::new(source.id)).unwrap();
+
+        assert!(store.merge_accounts(source.id, uuid::Uuid::new_v4()).is_err());
+        assert!(store.load_account(source.id).unwrap().is_some());
+        assert!(store.load_latest_usage(source.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_purge_all_empties_store_but_keeps_schema() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let first = Account::new("First".to_string(), "sk-first".to_string());
+        let second = Account::new("Second".to_string(), "sk-second".to_string());
+        for account in [&first, &second] {
+            store.save_account(account).unwrap();
+            store.save_usage_snapshot(&UsageSnapshot::new(account.id)).unwrap();
+        }
+
+        let summary = store.purge_all().unwrap();
+        assert_eq!(summary, PurgeSummary { accounts: 2, usage_snapshots: 2 });
+        assert!(store.load_accounts().unwrap().is_empty());
+        assert!(store.load_latest_usage(first.id).unwrap().is_none());
+
+        // Key hashes still work, so re-adding the same key is not a duplicate
+        store.save_account(&first).unwrap();
+        assert_eq!(store.find_by_key_hash("sk-first").unwrap(), Some(first.id));
+        assert_eq!(store.load_accounts().unwrap(), vec![first]);
+    }
+
+    #[test]
+    fn test_key_hash_detects_duplicate_keys() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let first = Account::new("First".to_string(), "sk-shared-key".to_string());
+        let second = Account::new("Second".to_string(), "sk-shared-key".to_string());
+        let other = Account::new("Other".to_string(), "sk-other-key".to_string());
+
+        store.save_account(&first).unwrap();
+        assert_eq!(store.find_by_key_hash("sk-shared-key").unwrap(), Some(first.id));
+        assert_eq!(store.find_by_key_hash("sk-other-key").unwrap(), None);
+
+        store.save_account(&second).unwrap();
+        store.save_account(&other).unwrap();
+        assert_eq!(store.key_hash(&first.api_key), store.key_hash(&second.api_key));
+        assert_ne!(store.key_hash(&first.api_key), store.key_hash(&other.api_key));
+        assert_eq!(store.find_by_key_hash("sk-other-key").unwrap(), Some(other.id));
+
+        // The hash is keyed, not a bare digest of the api_key
+        let elsewhere = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        assert_ne!(store.key_hash("sk-shared-key"), elsewhere.key_hash("sk-shared-key"));
+    }
+
+    #[test]
+    fn test_import_preserves_ids_without_collision() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Imported".to_string(), "sk-import".to_string());
+        let original_id = account.id;
+
+        let imported = store.import_account(account.clone(), true).unwrap();
+        assert_eq!(imported.id, original_id);
+        assert!(store.load_account(original_id).unwrap().is_some());
+
+        let regenerated = EncryptedStore::open_in_memory("test_master_key")
+            .unwrap()
+            .import_account(account, false)
+            .unwrap();
+        assert_ne!(regenerated.id, original_id);
+    }
+
+    #[test]
+    fn test_import_preserve_ids_regenerates_on_collision() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let existing = Account::new("Existing".to_string(), "sk-existing".to_string());
+        store.save_account(&existing).unwrap();
+
+        let mut incoming = Account::new("Incoming".to_string(), "sk-incoming".to_string());
+        incoming.id = existing.id;
+
+        let imported = store.import_account(incoming, true).unwrap();
+        assert_ne!(imported.id, existing.id);
+        assert_eq!(store.load_account(existing.id).unwrap().unwrap().label, "Existing");
+        assert_eq!(store.load_account(imported.id).unwrap().unwrap().label, "Incoming");
+    }
+
+    #[test]
+    fn test_locked_account_rejects_key_edits_but_allows_toggling() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let mut account = Account::new("Shared".to_string(), "sk-shared-key-0123456789".to_string());
+        account.locked = true;
+        store.save_account(&account).unwrap();
+
+        let stored = store.load_account(account.id).unwrap().unwrap();
+        assert!(stored.locked);
+
+        let mut rekeyed = stored.clone();
+        rekeyed.api_key = "sk-replaced-key-0123456789".to_string();
+        let errors = stored.validate_edit(&rekeyed).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "api_key");
+
+        let mut toggled = stored.clone();
+        toggled.enabled = !stored.enabled;
+        toggled.updated_at = chrono::Utc::now();
+        assert!(stored.validate_edit(&toggled).is_ok());
+
+        // Unlocked accounts accept any edit
+        let mut unlocked = stored.clone();
+        unlocked.locked = false;
+        assert!(unlocked.validate_edit(&rekeyed).is_ok());
+    }
+}
@@ -1,15 +1,72 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{Datelike, NaiveDate, Utc};
 use reqwest::{Client, Method};
 use serde::Deserialize;
-use tracing::{debug, instrument, warn};
+use tracing::{debug, info, instrument, warn};
 
-use crate::models::{Account, UsageSnapshot, ValidationResult};
+use crate::models::{Account, AccountId, UsageRefreshProgress, UsageSnapshot, ValidationResult};
+use crate::storage::EncryptedStore;
+
+/// Event name the frontend listens on for bulk refresh progress
+pub const USAGE_PROGRESS_EVENT: &str = "usage-refresh-progress";
 
 /// OpenAI API client for fetching usage and billing information
 pub struct OpenAIClient {
     http: Client,
     base_url: String,
+    /// Date format that last worked per account, tried first next time
+    billing_formats: dashmap::DashMap<AccountId, BillingDateFormat>,
+}
+
+/// How a billing usage query encodes its date range. Some org endpoints
+/// reject one shape with a 400 and accept another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BillingDateFormat {
+    /// `start_date`/`end_date` as `YYYY-MM-DD`, end inclusive
+    Dates,
+    /// `start_time`/`end_time` as unix seconds, end exclusive
+    Epoch,
+    /// `Epoch` plus `bucket_width=1d`
+    DailyBuckets,
+}
+
+impl BillingDateFormat {
+    /// Every format, in the order tried
+    const ALL: [BillingDateFormat; 3] =
+        [BillingDateFormat::Dates, BillingDateFormat::Epoch, BillingDateFormat::DailyBuckets];
+
+    /// Query parameters for a range in this format
+    fn query(&self, range: UsageRange) -> Vec<(&'static str, String)> {
+        let epoch = |date: NaiveDate| date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp();
+        let end_exclusive = range.end.succ_opt().unwrap_or(range.end);
+
+        match self {
+            BillingDateFormat::Dates => vec![
+                ("start_date", range.start.format("%Y-%m-%d").to_string()),
+                ("end_date", range.end.format("%Y-%m-%d").to_string()),
+            ],
+            BillingDateFormat::Epoch => vec![
+                ("start_time", epoch(range.start).to_string()),
+                ("end_time", epoch(end_exclusive).to_string()),
+            ],
+            BillingDateFormat::DailyBuckets => {
+                let mut query = BillingDateFormat::Epoch.query(range);
+                query.push(("bucket_width", "1d".to_string()));
+                query
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BillingDateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BillingDateFormat::Dates => "dates",
+            BillingDateFormat::Epoch => "epoch",
+            BillingDateFormat::DailyBuckets => "daily_buckets",
+        };
+        f.write_str(name)
+    }
 }
 
 impl OpenAIClient {
@@ -19,6 +76,7 @@ impl OpenAIClient {
         Self {
             http: Client::new(),
             base_url: Self::DEFAULT_BASE_URL.to_string(),
+            billing_formats: dashmap::DashMap::new(),
         }
     }
 
@@ -51,7 +109,7 @@ impl OpenAIClient {
     pub async fn fetch_usage(&self, account: &Account) -> Result<UsageSnapshot> {
         let mut snapshot = UsageSnapshot::new(account.id);
 
-        match self.fetch_billing_usage(account).await {
+        match self.fetch_billing_usage(account, UsageRange::current_month()).await {
             Ok(usage) => {
                 snapshot.monthly_usage = usage.total_usage / 100.0;
                 debug!(
@@ -101,31 +159,66 @@ impl OpenAIClient {
         Ok(snapshot)
     }
 
-    /// Fetch billing usage
-    async fn fetch_billing_usage(&self, account: &Account) -> Result<BillingUsageResponse> {
-        let now = Utc::now();
-        let start_date = now.format("%Y-%m-01").to_string();
-        let end_date = now.format("%Y-%m-%d").to_string();
-
-        let resp = self
-            .build_request(account, Method::GET, "/v1/dashboard/billing/usage")
-            .query(&[("start_date", start_date), ("end_date", end_date)])
-            .send()
-            .await
-            .context("Failed to send billing usage request")?;
+    /// Total spend in dollars for an account over a date range
+    pub async fn fetch_usage_range(&self, account: &Account, range: UsageRange) -> Result<f64> {
+        let usage = self.fetch_billing_usage(account, range).await?;
+        Ok(usage.total_usage / 100.0)
+    }
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Billing usage API error: {} - {}", status, text);
+    /// Fetch billing usage.
+    ///
+    /// Starts with the date format that last worked for the account and
+    /// moves to the next one when the endpoint answers 400.
+    async fn fetch_billing_usage(
+        &self,
+        account: &Account,
+        range: UsageRange,
+    ) -> Result<BillingUsageResponse> {
+        let mut formats = BillingDateFormat::ALL.to_vec();
+        if let Some(known) = self.billing_formats.get(&account.id).map(|f| *f) {
+            if let Some(pos) = formats.iter().position(|f| *f == known) {
+                formats[..=pos].rotate_right(1);
+            }
         }
 
-        let usage: BillingUsageResponse = resp
-            .json()
-            .await
-            .context("Failed to parse billing usage response")?;
+        let mut rejected = None;
+        for format in formats {
+            let resp = self
+                .build_request(account, Method::GET, "/v1/dashboard/billing/usage")
+                .query(&format.query(range))
+                .send()
+                .await
+                .context("Failed to send billing usage request")?;
+
+            if resp.status() == reqwest::StatusCode::BAD_REQUEST {
+                let text = resp.text().await.unwrap_or_default();
+                debug!("Billing usage for {} rejected {} dates: {}", account.label, format, text);
+                rejected = Some(text);
+                continue;
+            }
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let text = resp.text().await.unwrap_or_default();
+                anyhow::bail!("Billing usage API error: {} - {}", status, text);
+            }
 
-        Ok(usage)
+            let usage: BillingUsageResponse = resp
+                .json()
+                .await
+                .context("Failed to parse billing usage response")?;
+
+            if self.billing_formats.insert(account.id, format) != Some(format) {
+                info!("Billing usage for {} accepted {} dates", account.label, format);
+            }
+
+            return Ok(usage);
+        }
+
+        anyhow::bail!(
+            "Billing usage API error: 400 Bad Request for every date format - {}",
+            rejected.unwrap_or_default()
+        )
     }
 
     /// Fetch subscription info
@@ -195,6 +288,24 @@ impl OpenAIClient {
         })
     }
 
+    /// Send a cheap authenticated request to confirm an account is live,
+    /// returning how long it took
+    #[instrument(skip(self, account), fields(account_id = %account.id))]
+    pub async fn warm_up(&self, account: &Account) -> Result<std::time::Duration> {
+        let started = std::time::Instant::now();
+        let resp = self
+            .build_request(account, Method::GET, "/v1/models")
+            .send()
+            .await
+            .context("Warmup request failed")?;
+
+        if !resp.status().is_success() {
+            anyhow::bail!("Warmup request returned {}", resp.status());
+        }
+
+        Ok(started.elapsed())
+    }
+
     /// Validate that an API key is working
     pub async fn validate_key(&self, api_key: &str, org_id: Option<&str>) -> Result<ValidationResult> {
         let mut req = self
@@ -298,12 +409,84 @@ impl UsagePoller {
     }
 }
 
+/// Refresh and store usage for each account, publishing progress as each one finishes.
+///
+/// Send errors are ignored: having no subscribers does not stop the refresh.
+pub async fn refresh_usage_with_progress(
+    client: &OpenAIClient,
+    store: &EncryptedStore,
+    accounts: Vec<Account>,
+    progress: &tokio::sync::broadcast::Sender<UsageRefreshProgress>,
+) -> Vec<(AccountId, Result<UsageSnapshot, String>)> {
+    let total = accounts.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, account) in accounts.into_iter().enumerate() {
+        let result = match client.fetch_usage(&account).await {
+            Ok(usage) => {
+                if let Err(e) = store.save_usage_snapshot(&usage) {
+                    Err(format!("Failed to save usage: {}", e))
+                } else {
+                    Ok(usage)
+                }
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        let _ = progress.send(UsageRefreshProgress {
+            account_id: account.id,
+            account_label: account.label.clone(),
+            completed: index + 1,
+            total,
+            error: result.as_ref().err().cloned(),
+        });
+
+        results.push((account.id, result));
+    }
+
+    results
+}
+
 impl Default for UsagePoller {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Longest span, in days, the billing usage endpoint is queried for at once
+pub const MAX_USAGE_RANGE_DAYS: i64 = 100;
+
+/// Date range for billing usage queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl UsageRange {
+    /// A validated range; `start` must not be after `end`, and the span is
+    /// capped at `MAX_USAGE_RANGE_DAYS`
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self> {
+        if start > end {
+            anyhow::bail!("Usage range start {} is after its end {}", start, end);
+        }
+        if (end - start).num_days() > MAX_USAGE_RANGE_DAYS {
+            anyhow::bail!(
+                "Usage range {} to {} spans more than {} days",
+                start, end, MAX_USAGE_RANGE_DAYS
+            );
+        }
+
+        Ok(Self { start, end })
+    }
+
+    /// The current month up to today (UTC)
+    pub fn current_month() -> Self {
+        let today = Utc::now().date_naive();
+        Self { start: today.with_day(1).unwrap_or(today), end: today }
+    }
+}
+
 /// Tauri command: Fetch usage for an account
 #[tauri::command]
 pub async fn fetch_account_usage(
@@ -332,3 +515,89 @@ pub async fn validate_api_key(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_refresh_emits_progress_per_account() {
+        // Every endpoint 404s, so each fetch completes quickly with empty usage
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, axum::Router::new()).await.unwrap();
+        });
+
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let accounts: Vec<Account> = ["One", "Two", "Three"]
+            .iter()
+            .map(|label| Account::new(label.to_string(), "sk-test".to_string()))
+            .collect();
+        for account in &accounts {
+            store.save_account(account).unwrap();
+        }
+
+        let (tx, mut rx) = tokio::sync::broadcast::channel(16);
+        let client = OpenAIClient::new().with_base_url(format!("http://{}", addr));
+
+        let results = refresh_usage_with_progress(&client, &store, accounts.clone(), &tx).await;
+        assert_eq!(results.len(), 3);
+
+        for (index, account) in accounts.iter().enumerate() {
+            let event = rx.try_recv().unwrap();
+            assert_eq!(event.account_id, account.id);
+            assert_eq!(event.completed, index + 1);
+            assert_eq!(event.total, 3);
+            assert!(event.error.is_none());
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_billing_usage_falls_back_to_epoch_dates() {
+        use axum::{extract::Query, http::StatusCode, routing::get, Json};
+        use std::collections::HashMap;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Rejects `start_date`, accepts `start_time`
+        let date_hits = Arc::new(AtomicUsize::new(0));
+        let counter = date_hits.clone();
+        let app = axum::Router::new().route(
+            "/v1/dashboard/billing/usage",
+            get(move |Query(query): Query<HashMap<String, String>>| {
+                let counter = counter.clone();
+                async move {
+                    if query.contains_key("start_date") {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        return Err(StatusCode::BAD_REQUEST);
+                    }
+                    assert_eq!(query["start_time"], "1705276800");
+                    assert_eq!(query["end_time"], "1707609600");
+                    Ok(Json(serde_json::json!({ "total_usage": 1250.0 })))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = OpenAIClient::new().with_base_url(format!("http://{}", addr));
+        let account = Account::new("Test".to_string(), "sk-test".to_string());
+        let range = UsageRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(client.fetch_usage_range(&account, range).await.unwrap(), 12.5);
+        assert_eq!(date_hits.load(Ordering::SeqCst), 1);
+
+        // The working format is remembered, so the rejected one isn't retried
+        assert_eq!(client.fetch_usage_range(&account, range).await.unwrap(), 12.5);
+        assert_eq!(date_hits.load(Ordering::SeqCst), 1);
+    }
+}
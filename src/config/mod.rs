@@ -1,10 +1,14 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::models::{Account, ModelInfo};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +21,38 @@ pub struct Config {
     pub polling: PollingConfig,
     /// UI configuration
     pub ui: UiConfig,
+    /// Catalog entries added to, or overriding, the built-in model catalog
+    #[serde(default)]
+    pub models: Vec<ModelInfo>,
+    /// Fall back to the built-in master key when none is provided instead of
+    /// refusing to start; only on by default in debug builds
+    #[serde(default = "default_allow_default_master_key")]
+    pub allow_default_master_key: bool,
+}
+
+/// Insecure master key used only when `allow_default_master_key` is set
+pub const DEFAULT_MASTER_KEY: &str = "codex-account-manager-default-key";
+
+/// Built-in proxy client key; anyone who knows the project knows it
+pub const DEFAULT_PROXY_API_KEY: &str = "sk-codex-account-manager";
+
+/// Prefix of generated proxy client keys
+pub const PROXY_API_KEY_PREFIX: &str = "sk-cam-";
+
+/// Length of the random part of a generated key: 43 base62 characters
+/// carry at least 256 bits
+const PROXY_API_KEY_RANDOM_LEN: usize = 43;
+
+/// A fresh proxy client key drawn from the OS random number generator
+pub fn generate_proxy_api_key() -> String {
+    use rand::distributions::{Alphanumeric, DistString};
+
+    let random = Alphanumeric.sample_string(&mut rand::rngs::OsRng, PROXY_API_KEY_RANDOM_LEN);
+    format!("{PROXY_API_KEY_PREFIX}{random}")
+}
+
+fn default_allow_default_master_key() -> bool {
+    cfg!(debug_assertions)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,12 +60,307 @@ pub struct ProxyConfig {
     pub bind_addr: SocketAddr,
     pub api_key: String,
     pub openai_base_url: String,
+    /// Model for the 1-token completion probe used when a key can't list models
+    #[serde(default = "default_probe_model")]
+    pub probe_model: String,
+    /// Upstream timeout for non-streaming requests
+    #[serde(default = "default_upstream_timeout_secs")]
+    pub upstream_timeout_secs: u64,
+    /// Upstream timeout for streaming requests, which can run for minutes
+    #[serde(default = "default_upstream_stream_timeout_secs")]
+    pub upstream_stream_timeout_secs: u64,
+    /// Reject requests whose estimated cost exceeds this many USD
+    #[serde(default)]
+    pub max_request_cost: Option<f64>,
+    /// Record the shape (message roles and lengths, never content) and routing
+    /// of 1 in this many requests for debugging; 0 disables sampling
+    #[serde(default)]
+    pub debug_sample_rate: u64,
+    /// Where sampled requests are appended as JSON lines; defaults to
+    /// `debug-samples.jsonl` in the data directory
+    #[serde(default)]
+    pub debug_sample_path: Option<PathBuf>,
+    /// Headers added to every upstream request, e.g. `OpenAI-Beta`
+    #[serde(default)]
+    pub forward_headers: HashMap<String, String>,
+    /// Client headers passed through to the upstream request; the proxy's own
+    /// authorization and content headers are never passed through
+    #[serde(default)]
+    pub passthrough_headers: Vec<String>,
+    /// Client headers never forwarded upstream, even if `passthrough_headers`
+    /// lists them, e.g. `user-agent` or tracking headers
+    #[serde(default)]
+    pub strip_client_headers: Vec<String>,
+    /// Gzip non-streaming responses for clients sending `Accept-Encoding: gzip`
+    #[serde(default)]
+    pub compression: bool,
+    /// Prefix the upstream serves under, e.g. `/openai` for
+    /// `{openai_base_url}/openai/v1/...`; for OpenAI-compatible backends
+    #[serde(default)]
+    pub base_path: Option<String>,
+    /// Most upstream attempts for one request across all accounts, the
+    /// first one included; unset keeps the built-in failover limit
+    #[serde(default)]
+    pub retry_budget: Option<u32>,
+    /// Longest random pause before failing over to the next account, so
+    /// concurrent requests don't retry in lockstep; 0 fails over at once
+    #[serde(default)]
+    pub failover_jitter_ms: u64,
+    /// Total time one request may spend across failover attempts; unset
+    /// only bounds each attempt by the upstream timeout
+    #[serde(default)]
+    pub failover_deadline_ms: Option<u64>,
+    /// Further addresses to listen on besides `bind_addr`: `host:port`, or
+    /// `unix:/path/to/socket` on unix
+    #[serde(default)]
+    pub additional_binds: Vec<String>,
+    /// Reject requests to model endpoints that don't name a model, instead
+    /// of routing them to a default one
+    #[serde(default)]
+    pub require_explicit_model: bool,
+    /// Log forwarded request bodies pretty-printed at debug level, to help
+    /// track down malformed client payloads
+    #[serde(default)]
+    pub reserialize_pretty: bool,
+    /// Send request bodies upstream re-serialized as compact JSON instead of
+    /// the client's original bytes
+    #[serde(default)]
+    pub normalize_outgoing_json: bool,
+    /// Upstream statuses retried on another account; they count towards
+    /// opening the account's circuit
+    #[serde(default = "default_retryable_statuses")]
+    pub retryable_statuses: Vec<u16>,
+    /// Upstream statuses that open the account's circuit at once, such as a
+    /// revoked key, before retrying on another account
+    #[serde(default = "default_fatal_statuses")]
+    pub fatal_statuses: Vec<u16>,
+    /// Upstream statuses retried on another account without counting
+    /// against the account's circuit. Any other status is passed through.
+    #[serde(default = "default_rate_limit_statuses")]
+    pub rate_limit_statuses: Vec<u16>,
+    /// Organization sent for accounts without an `org_id` of their own
+    #[serde(default)]
+    pub default_org_id: Option<String>,
+    /// Body answered with 200 instead of a 503 when every account is over
+    /// budget, e.g. a canned "at capacity" completion; unset keeps the 503.
+    /// Streaming and non-JSON requests always get the 503.
+    #[serde(default)]
+    pub over_budget_response: Option<serde_json::Value>,
+    /// Save the proxy's request total with the request counters and resume
+    /// from it on startup, instead of counting from zero every run
+    #[serde(default)]
+    pub persist_request_count: bool,
+    /// Available accounts `/readyz` needs before answering 200; below it
+    /// the proxy reports 503 so orchestrators hold traffic back
+    #[serde(default = "default_min_ready_accounts")]
+    pub min_ready_accounts: usize,
+    /// Requested model names rewritten to another model before routing,
+    /// e.g. `fast = "gpt-4o-mini"`
+    #[serde(default)]
+    pub model_aliases: HashMap<String, String>,
+    /// Top-level request parameters removed before forwarding
+    #[serde(default)]
+    pub strip_params: Vec<String>,
+    /// Top-level request parameters added when the client doesn't send them
+    #[serde(default)]
+    pub default_params: serde_json::Map<String, serde_json::Value>,
+    /// Parameters added for one model when the client doesn't send them,
+    /// keyed by the model name as requested (before `model_aliases`), e.g.
+    /// `deterministic = { temperature = 0 }`
+    #[serde(default)]
+    pub model_defaults: HashMap<String, serde_json::Map<String, serde_json::Value>>,
+    /// Highest `max_tokens`, `max_completion_tokens` or `max_output_tokens`
+    /// forwarded per upstream model (after `model_aliases`); larger client
+    /// values are lowered to it
+    #[serde(default)]
+    pub max_tokens_ceiling: HashMap<String, u64>,
+    /// Oldest TLS version upstream connections accept, `1.2` or `1.3`
+    #[serde(default)]
+    pub min_tls_version: Option<String>,
+    /// Accept invalid or self-signed upstream certificates. Only for local
+    /// OpenAI-compatible backends; never against a public API
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    /// Seconds to wait for an upstream connection before failing over
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+}
+
+fn default_probe_model() -> String {
+    crate::usage::OpenAIClient::DEFAULT_PROBE_MODEL.to_string()
+}
+
+fn default_retryable_statuses() -> Vec<u16> {
+    crate::proxy::StatusClasses::default().retryable
+}
+
+fn default_fatal_statuses() -> Vec<u16> {
+    crate::proxy::StatusClasses::default().fatal
+}
+
+fn default_rate_limit_statuses() -> Vec<u16> {
+    crate::proxy::StatusClasses::default().rate_limited
+}
+
+fn default_upstream_timeout_secs() -> u64 {
+    120
+}
+
+fn default_upstream_stream_timeout_secs() -> u64 {
+    600
+}
+
+fn default_billing_date_formats() -> Vec<crate::usage::BillingDateFormat> {
+    crate::usage::BillingDateFormat::ALL.to_vec()
+}
+
+fn default_min_ready_accounts() -> usize {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutingConfig {
     pub strategy: String,
     pub min_request_interval_ms: u64,
+    /// Seed for the tie-break RNG; unset uses entropy
+    #[serde(default)]
+    pub routing_seed: Option<u64>,
+    /// Usage snapshots older than this are treated as unknown
+    #[serde(default)]
+    pub usage_max_age_secs: Option<u64>,
+    /// What to do with accounts whose usage data is stale
+    #[serde(default)]
+    pub stale_usage_policy: StaleUsagePolicy,
+    /// Route requests to account subsets by estimated size; first match wins
+    #[serde(default)]
+    pub size_rules: Vec<SizeRule>,
+    /// Demote accounts as their utilization grows to spread spend evenly
+    #[serde(default)]
+    pub auto_balance: bool,
+    /// Strategy for picking the replacement account when a request fails over;
+    /// unset reuses `strategy`
+    #[serde(default)]
+    pub failover_strategy: Option<String>,
+    /// Whether sticky sessions pin per model family or globally
+    #[serde(default)]
+    pub sticky_scope: StickyScope,
+    /// Re-pin a sticky session when its account's utilization exceeds the least
+    /// utilized alternative by more than this ratio (e.g. 0.25); unset pins forever
+    #[serde(default)]
+    pub sticky_decay_margin: Option<f64>,
+    /// Drop sticky sessions left unused for this long; unset keeps them until
+    /// their account goes away
+    #[serde(default)]
+    pub session_ttl_secs: Option<u64>,
+    /// Send a small request to an account when it is enabled or its circuit
+    /// resets, before real traffic
+    #[serde(default)]
+    pub warmup_on_enable: bool,
+    /// What to do when every candidate is past its soft limit
+    #[serde(default)]
+    pub on_all_soft_limited: SoftLimitPolicy,
+    /// Regions preferred among accounts the strategy ranks equally, best first
+    #[serde(default)]
+    pub preferred_regions: Vec<String>,
+    /// Probe accounts with an open circuit this often so they recover without
+    /// client traffic; unset leaves recovery to real requests
+    #[serde(default)]
+    pub probe_interval_secs: Option<u64>,
+    /// Probe each account's key, organization and model list and reuse the
+    /// result for this long; unset skips capability probing
+    #[serde(default)]
+    pub capability_ttl_secs: Option<u64>,
+    /// Divert a share of traffic to one account regardless of strategy
+    #[serde(default)]
+    pub experiment: Option<ExperimentConfig>,
+    /// Briefly route around an account after it serves a very large response
+    #[serde(default)]
+    pub large_request_cooldown: Option<LargeRequestCooldown>,
+    /// Reuse the last decision for identical requests (same model, endpoint
+    /// and session pin) for this many milliseconds; unset routes every request
+    #[serde(default)]
+    pub decision_cache_ttl_ms: Option<u64>,
+    /// Save circuit breakers, sticky sessions and the round-robin cursor on
+    /// shutdown and restore them on the next start
+    #[serde(default)]
+    pub persist_engine_state: bool,
+}
+
+/// Sends a percentage of requests to a single account, e.g. to trial a new key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    pub account_id: uuid::Uuid,
+    /// Share of requests to divert, from 0 to 100
+    pub percent: f64,
+}
+
+/// Deprioritizes an account for a while after a response used many tokens,
+/// so the next large requests land elsewhere
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LargeRequestCooldown {
+    /// Total tokens a response must report to start the cooldown, and a
+    /// request's estimate must reach to be steered off a cooling account
+    pub min_tokens: u64,
+    /// How long the account is passed over while others are available
+    pub cooldown_secs: u64,
+}
+
+/// Restricts requests within a token range to a subset of accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeRule {
+    /// Inclusive lower bound on estimated tokens; unset means no lower bound
+    #[serde(default)]
+    pub min_tokens: Option<u64>,
+    /// Exclusive upper bound on estimated tokens; unset means no upper bound
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    pub account_ids: Vec<uuid::Uuid>,
+}
+
+impl SizeRule {
+    /// Whether a request of `tokens` estimated tokens falls in this rule
+    pub fn matches(&self, tokens: u64) -> bool {
+        self.min_tokens.is_none_or(|min| tokens >= min)
+            && self.max_tokens.is_none_or(|max| tokens < max)
+    }
+}
+
+/// Handling of accounts whose usage snapshot exceeds `usage_max_age_secs`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleUsagePolicy {
+    /// Keep the account, but only use it when no fresh account is available
+    #[default]
+    Deprioritize,
+    /// Mark the account unavailable until fresh usage arrives
+    Disable,
+}
+
+/// Handling of requests when every candidate account is past its soft limit.
+///
+/// Accounts under their soft limit are always preferred; this only decides
+/// what happens once none are left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoftLimitPolicy {
+    /// Fail the request as if no account were available
+    Reject,
+    /// Use the account least far past its soft limit
+    #[default]
+    UseLeastOverSoft,
+    /// Route among all of them with the normal strategy
+    Allow,
+}
+
+/// What a sticky session pins to an account
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickyScope {
+    /// One account per session, whatever model it asks for
+    #[default]
+    Global,
+    /// One account per session and model family (chat, embedding, image)
+    PerModel,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,12 +369,49 @@ pub struct PollingConfig {
     pub interval_seconds: u64,
     pub backoff_multiplier: f64,
     pub max_interval_seconds: u64,
+    /// Poll at `max_interval_seconds` once the proxy has seen no requests for
+    /// this long, until traffic resumes; unset always polls at the normal rate
+    #[serde(default)]
+    pub poll_idle_pause_secs: Option<u64>,
+    /// Date parameter shapes tried, in order, when billing usage answers 400
+    #[serde(default = "default_billing_date_formats")]
+    pub billing_date_formats: Vec<crate::usage::BillingDateFormat>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiConfig {
     pub theme: String,
     pub refresh_rate_ms: u64,
+    /// Warn in the TUI footer when an account's remaining budget drops below this
+    #[serde(default)]
+    pub low_budget_alert: Option<BudgetThreshold>,
+}
+
+/// Low remaining-budget threshold, e.g. `{ absolute = 5.0 }` or `{ percent = 10.0 }`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetThreshold {
+    /// Remaining budget in dollars
+    Absolute(f64),
+    /// Remaining budget as a percentage of the hard limit
+    Percent(f64),
+}
+
+impl BudgetThreshold {
+    /// Whether a snapshot's remaining budget is below the threshold
+    pub fn is_breached_by(&self, usage: &crate::models::UsageSnapshot) -> bool {
+        let Some(remaining) = usage.remaining_budget else {
+            return false;
+        };
+
+        match *self {
+            BudgetThreshold::Absolute(amount) => remaining < amount,
+            BudgetThreshold::Percent(percent) => match usage.hard_limit {
+                Some(limit) if limit > 0.0 => remaining / limit * 100.0 < percent,
+                _ => false,
+            },
+        }
+    }
 }
 
 impl Default for Config {
@@ -51,47 +419,163 @@ impl Default for Config {
         Self {
             proxy: ProxyConfig {
                 bind_addr: "127.0.0.1:8080".parse().unwrap(),
-                api_key: "sk-codex-account-manager".to_string(),
+                api_key: DEFAULT_PROXY_API_KEY.to_string(),
                 openai_base_url: "https://api.openai.com".to_string(),
+                probe_model: default_probe_model(),
+                upstream_timeout_secs: default_upstream_timeout_secs(),
+                upstream_stream_timeout_secs: default_upstream_stream_timeout_secs(),
+                max_request_cost: None,
+                debug_sample_rate: 0,
+                debug_sample_path: None,
+                forward_headers: HashMap::new(),
+                passthrough_headers: Vec::new(),
+                strip_client_headers: Vec::new(),
+                compression: false,
+                base_path: None,
+                retry_budget: None,
+                failover_jitter_ms: 0,
+                failover_deadline_ms: None,
+                additional_binds: Vec::new(),
+                require_explicit_model: false,
+                reserialize_pretty: false,
+                normalize_outgoing_json: false,
+                retryable_statuses: default_retryable_statuses(),
+                fatal_statuses: default_fatal_statuses(),
+                rate_limit_statuses: default_rate_limit_statuses(),
+                default_org_id: None,
+                over_budget_response: None,
+                persist_request_count: false,
+                min_ready_accounts: default_min_ready_accounts(),
+                model_aliases: HashMap::new(),
+                strip_params: Vec::new(),
+                default_params: serde_json::Map::new(),
+                model_defaults: HashMap::new(),
+                max_tokens_ceiling: HashMap::new(),
+                min_tls_version: None,
+                accept_invalid_certs: false,
+                connect_timeout_secs: None,
             },
             routing: RoutingConfig {
                 strategy: "least_utilized".to_string(),
                 min_request_interval_ms: 100,
+                routing_seed: None,
+                usage_max_age_secs: None,
+                stale_usage_policy: StaleUsagePolicy::Deprioritize,
+                size_rules: Vec::new(),
+                auto_balance: false,
+                failover_strategy: None,
+                sticky_scope: StickyScope::Global,
+                sticky_decay_margin: None,
+                session_ttl_secs: None,
+                warmup_on_enable: false,
+                on_all_soft_limited: SoftLimitPolicy::UseLeastOverSoft,
+                preferred_regions: Vec::new(),
+                probe_interval_secs: None,
+                capability_ttl_secs: None,
+                experiment: None,
+                large_request_cooldown: None,
+                decision_cache_ttl_ms: None,
+                persist_engine_state: false,
             },
             polling: PollingConfig {
                 enabled: true,
                 interval_seconds: 300,
                 backoff_multiplier: 2.0,
                 max_interval_seconds: 3600,
+                poll_idle_pause_secs: None,
+                billing_date_formats: default_billing_date_formats(),
             },
             ui: UiConfig {
                 theme: "dark".to_string(),
                 refresh_rate_ms: 1000,
+                low_budget_alert: None,
             },
+            models: Vec::new(),
+            allow_default_master_key: default_allow_default_master_key(),
         }
     }
 }
 
 impl Config {
-    /// Load configuration from file or create default
-    pub fn load() -> Result<(Self, PathBuf)> {
+    /// Client for usage polling, probes and key checks: the proxy's upstream,
+    /// organization and outbound TLS settings
+    pub fn openai_client(&self) -> Result<crate::usage::OpenAIClient> {
+        let min_tls_version = self
+            .proxy
+            .min_tls_version
+            .as_deref()
+            .map(crate::proxy::parse_tls_version)
+            .transpose()?;
+        let http = crate::proxy::outbound_http_client(
+            min_tls_version,
+            self.proxy.accept_invalid_certs,
+            self.proxy.connect_timeout_secs.map(std::time::Duration::from_secs),
+        )
+        .context("Failed to build the upstream HTTP client")?;
+
+        Ok(crate::usage::OpenAIClient::new()
+            .with_http_client(http)
+            .with_base_url(self.proxy.openai_base_url.clone())
+            .with_default_org_id(self.proxy.default_org_id.clone())
+            .with_probe_model(self.proxy.probe_model.clone())
+            .with_billing_date_formats(self.polling.billing_date_formats.clone()))
+    }
+
+    /// Master key for the database: the provided one, or the built-in
+    /// default if the configuration allows it
+    pub fn resolve_master_key(&self, provided: Option<String>) -> Result<String> {
+        match provided.filter(|key| !key.is_empty()) {
+            Some(key) => Ok(key),
+            None if self.allow_default_master_key => {
+                warn!("Using default master key - set CAM_MASTER_KEY environment variable!");
+                Ok(DEFAULT_MASTER_KEY.to_string())
+            }
+            None => anyhow::bail!(
+                "No master key provided. Pass --master-key or set CAM_MASTER_KEY \
+                 (set allow_default_master_key = true to use the insecure built-in key)"
+            ),
+        }
+    }
+
+    /// Load configuration from file (creating the default if missing), then
+    /// apply `CAM_*` environment variables and command-line overrides
+    pub fn load_effective(cli: &[(&str, toml::Value)]) -> Result<(EffectiveConfig, PathBuf)> {
         let config_path = Self::config_path()?;
 
-        if config_path.exists() {
+        let content = if config_path.exists() {
             info!("Loading configuration from {:?}", config_path);
-            let content = fs::read_to_string(&config_path)?;
-            let config: Config =
-                toml::from_str(&content).context("Failed to parse configuration file")?;
-            Ok((config, config_path))
+            Some(fs::read_to_string(&config_path)?)
         } else {
             info!(
                 "Configuration not found, creating default at {:?}",
                 config_path
             );
-            let config = Config::default();
-            config.save(&config_path)?;
-            Ok((config, config_path))
+            Config::default().save(&config_path)?;
+            None
+        };
+
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let effective = EffectiveConfig::resolve(content.as_deref(), &env, cli)?;
+        Ok((effective, config_path))
+    }
+
+    /// Set `proxy.api_key` in the configuration file, leaving everything
+    /// else in the file as written
+    pub fn store_proxy_api_key(path: &Path, key: &str) -> Result<()> {
+        let mut table: toml::Table = if path.exists() {
+            toml::from_str(&fs::read_to_string(path)?)
+                .context("Failed to parse configuration file")?
+        } else {
+            toml::Table::try_from(Config::default())
+                .context("Failed to serialize default configuration")?
+        };
+        set_path(&mut table, "proxy.api_key", toml::Value::String(key.to_string()));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::write(path, toml::to_string_pretty(&table)?)?;
+        Ok(())
     }
 
     /// Save configuration to file
@@ -122,13 +606,181 @@ impl Config {
 
         Ok(proj_dirs.data_dir().join("accounts.db"))
     }
+}
 
-    /// Get log directory
-    pub fn log_dir() -> Result<PathBuf> {
-        let proj_dirs = ProjectDirs::from("com", "codex", "account-manager")
-            .context("Failed to determine log directory")?;
+/// Prefix of environment variables overriding configuration values; the rest
+/// of the name is the dotted key in upper case with `_` for `.`, e.g.
+/// `CAM_PROXY_UPSTREAM_TIMEOUT_SECS` for `proxy.upstream_timeout_secs`
+pub const ENV_PREFIX: &str = "CAM_";
+
+/// Prefix of environment variables defining accounts, e.g.
+/// `CAM_ACCOUNT_TEAM_A=sk-...` for an account labelled `team-a`
+pub const ACCOUNT_ENV_PREFIX: &str = "CAM_ACCOUNT_";
+
+/// Accounts defined by `CAM_ACCOUNT_<LABEL>` variables, ordered by label.
+///
+/// Meant for stateless deployments that don't keep a database around.
+pub fn accounts_from_env(env: &HashMap<String, String>) -> Vec<Account> {
+    let mut accounts: Vec<Account> = env
+        .iter()
+        .filter_map(|(name, key)| {
+            let label = name.strip_prefix(ACCOUNT_ENV_PREFIX)?;
+            if label.is_empty() || key.is_empty() {
+                return None;
+            }
+            Some(Account::new(label.to_lowercase().replace('_', "-"), key.clone()))
+        })
+        .collect();
+    accounts.sort_by(|a, b| a.label.cmp(&b.label));
+    accounts
+}
+
+/// Where an effective configuration value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "file",
+            ConfigSource::Env => "env",
+            ConfigSource::Cli => "cli",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Fully resolved configuration and the source of each value, keyed by
+/// dotted path (e.g. `routing.strategy`)
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub config: Config,
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl EffectiveConfig {
+    /// Layer defaults, the file contents, environment variables and
+    /// command-line overrides, later layers winning.
+    ///
+    /// Environment variables can only override keys that the defaults or the
+    /// file define; unset optional values have to be set in the file first.
+    pub fn resolve(
+        file: Option<&str>,
+        env: &HashMap<String, String>,
+        cli: &[(&str, toml::Value)],
+    ) -> Result<Self> {
+        let mut merged = toml::Table::try_from(Config::default())
+            .context("Failed to serialize default configuration")?;
+        let mut sources = BTreeMap::new();
+        for (key, _) in leaves(&merged) {
+            sources.insert(key, ConfigSource::Default);
+        }
+
+        if let Some(content) = file {
+            let table: toml::Table =
+                toml::from_str(content).context("Failed to parse configuration file")?;
+            for (key, value) in leaves(&table) {
+                set_path(&mut merged, &key, value.clone());
+                sources.insert(key, ConfigSource::File);
+            }
+        }
 
-        Ok(proj_dirs.data_dir().join("logs"))
+        let keys: Vec<String> = sources.keys().cloned().collect();
+        for key in keys {
+            let Some(raw) = env.get(&env_var_name(&key)) else {
+                continue;
+            };
+            let is_string = matches!(get_path(&merged, &key), Some(toml::Value::String(_)));
+            set_path(&mut merged, &key, parse_env_value(raw, is_string));
+            sources.insert(key, ConfigSource::Env);
+        }
+
+        for (key, value) in cli {
+            set_path(&mut merged, key, value.clone());
+            sources.insert(key.to_string(), ConfigSource::Cli);
+        }
+
+        let config = Config::deserialize(merged).context("Invalid configuration override")?;
+        Ok(Self { config, sources })
+    }
+
+    /// Render every value as a TOML assignment annotated with its source
+    pub fn render(&self) -> Result<String> {
+        let table = toml::Table::try_from(&self.config)
+            .context("Failed to serialize configuration")?;
+
+        let mut out = String::new();
+        for (key, value) in leaves(&table) {
+            let source = self.sources.get(&key).copied().unwrap_or(ConfigSource::File);
+            out.push_str(&format!("{} = {}  # {}\n", key, value, source));
+        }
+        Ok(out)
+    }
+}
+
+/// Environment variable overriding a dotted configuration key
+fn env_var_name(key: &str) -> String {
+    format!("{}{}", ENV_PREFIX, key.replace('.', "_").to_uppercase())
+}
+
+/// Parse an environment value as TOML, falling back to a plain string
+fn parse_env_value(raw: &str, is_string: bool) -> toml::Value {
+    if is_string {
+        return toml::Value::String(raw.to_string());
+    }
+
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Non-table values of a table by dotted path; arrays count as one value
+fn leaves(table: &toml::Table) -> Vec<(String, &toml::Value)> {
+    let mut out = Vec::new();
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(inner) => {
+                for (inner_key, inner_value) in leaves(inner) {
+                    out.push((format!("{}.{}", key, inner_key), inner_value));
+                }
+            }
+            _ => out.push((key.clone(), value)),
+        }
+    }
+    out
+}
+
+fn get_path<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    match key.split_once('.') {
+        Some((head, rest)) => get_path(table.get(head)?.as_table()?, rest),
+        None => table.get(key),
+    }
+}
+
+fn set_path(table: &mut toml::Table, key: &str, value: toml::Value) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            let inner = table
+                .entry(head)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if !inner.is_table() {
+                *inner = toml::Value::Table(toml::Table::new());
+            }
+            if let toml::Value::Table(inner) = inner {
+                set_path(inner, rest, value);
+            }
+        }
+        None => {
+            table.insert(key.to_string(), value);
+        }
     }
 }
 
@@ -140,3 +792,97 @@ pub fn data_dir() -> Result<PathBuf> {
     fs::create_dir_all(proj_dirs.data_dir())?;
     Ok(proj_dirs.data_dir().to_path_buf())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_master_key_is_refused_unless_allowed() {
+        let mut config = Config {
+            allow_default_master_key: false,
+            ..Config::default()
+        };
+
+        let err = config.resolve_master_key(None).unwrap_err();
+        assert!(err.to_string().contains("No master key provided"));
+        assert!(config.resolve_master_key(Some(String::new())).is_err());
+        assert_eq!(config.resolve_master_key(Some("secret".to_string())).unwrap(), "secret");
+
+        config.allow_default_master_key = true;
+        assert_eq!(config.resolve_master_key(None).unwrap(), DEFAULT_MASTER_KEY);
+    }
+
+    #[test]
+    fn test_accounts_from_env() {
+        let env = HashMap::from([
+            ("CAM_ACCOUNT_TEAM_B".to_string(), "sk-team-b".to_string()),
+            ("CAM_ACCOUNT_TEAM_A".to_string(), "sk-team-a".to_string()),
+            ("CAM_ACCOUNT_EMPTY".to_string(), String::new()),
+            ("CAM_PROXY_API_KEY".to_string(), "sk-proxy".to_string()),
+        ]);
+
+        let accounts = accounts_from_env(&env);
+        let labels: Vec<&str> = accounts.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, vec!["team-a", "team-b"]);
+        assert_eq!(accounts[0].api_key, "sk-team-a");
+    }
+
+    #[test]
+    fn test_effective_config_tracks_sources() {
+        let file = r#"
+            [proxy]
+            api_key = "sk-from-file"
+            upstream_timeout_secs = 30
+
+            [routing]
+            strategy = "priority"
+        "#;
+        let env = HashMap::from([
+            ("CAM_PROXY_UPSTREAM_TIMEOUT_SECS".to_string(), "45".to_string()),
+            ("CAM_ROUTING_AUTO_BALANCE".to_string(), "true".to_string()),
+            ("CAM_UI_THEME".to_string(), "light".to_string()),
+            ("CAM_MASTER_KEY".to_string(), "not-a-config-value".to_string()),
+        ]);
+        let cli = [("proxy.api_key", toml::Value::String("sk-from-cli".to_string()))];
+
+        let effective = EffectiveConfig::resolve(Some(file), &env, &cli).unwrap();
+
+        assert_eq!(effective.config.proxy.upstream_timeout_secs, 45);
+        assert!(effective.config.routing.auto_balance);
+        assert_eq!(effective.config.ui.theme, "light");
+        assert_eq!(effective.config.proxy.api_key, "sk-from-cli");
+        assert_eq!(effective.config.routing.strategy, "priority");
+
+        let source = |key: &str| effective.sources.get(key).copied();
+        assert_eq!(source("proxy.upstream_timeout_secs"), Some(ConfigSource::Env));
+        assert_eq!(source("routing.auto_balance"), Some(ConfigSource::Env));
+        assert_eq!(source("ui.theme"), Some(ConfigSource::Env));
+        assert_eq!(source("proxy.api_key"), Some(ConfigSource::Cli));
+        assert_eq!(source("routing.strategy"), Some(ConfigSource::File));
+        assert_eq!(source("polling.interval_seconds"), Some(ConfigSource::Default));
+        assert_eq!(source("master_key"), None);
+
+        let rendered = effective.render().unwrap();
+        assert!(rendered.contains("proxy.upstream_timeout_secs = 45  # env\n"));
+        assert!(rendered.contains("routing.strategy = \"priority\"  # file\n"));
+
+        // Overrides that don't fit the field's type are rejected
+        let env = HashMap::from([("CAM_POLLING_ENABLED".to_string(), "sometimes".to_string())]);
+        assert!(EffectiveConfig::resolve(None, &env, &[]).is_err());
+    }
+
+    #[test]
+    fn test_generated_proxy_keys_are_unique_and_base62() {
+        let keys: std::collections::HashSet<String> =
+            (0..100).map(|_| generate_proxy_api_key()).collect();
+        assert_eq!(keys.len(), 100);
+
+        for key in &keys {
+            let random = key.strip_prefix(PROXY_API_KEY_PREFIX).unwrap();
+            assert_eq!(random.len(), PROXY_API_KEY_RANDOM_LEN);
+            assert!(random.chars().all(|c| c.is_ascii_alphanumeric()));
+        }
+        assert!(!keys.contains(DEFAULT_PROXY_API_KEY));
+    }
+}
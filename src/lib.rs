@@ -0,0 +1,7 @@
+pub mod config;
+pub mod models;
+pub mod proxy;
+pub mod routing;
+pub mod storage;
+pub mod ui;
+pub mod usage;
@@ -3,15 +3,9 @@ use clap::{Parser, Subcommand};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
-mod config;
-mod models;
-mod proxy;
-mod routing;
-mod storage;
-mod ui;
-mod usage;
+use codex_manager::{config, models, proxy, routing, storage, ui, usage};
 
 use config::Config;
 use routing::{RoutingEngine, RoutingStrategy};
@@ -34,13 +28,37 @@ struct Cli {
     #[arg(short, long)]
     proxy_only: bool,
 
-    /// Proxy bind address
-    #[arg(short, long, default_value = "127.0.0.1:8080")]
-    bind: SocketAddr,
+    /// Proxy bind address (overrides proxy.bind_addr)
+    #[arg(short, long)]
+    bind: Option<SocketAddr>,
 
-    /// API key for proxy authentication
-    #[arg(long, default_value = "sk-codex-account-manager")]
-    api_key: String,
+    /// API key for proxy authentication (overrides proxy.api_key)
+    #[arg(long)]
+    api_key: Option<String>,
+
+    /// Keep accounts and usage in memory only, loading accounts from
+    /// CAM_ACCOUNT_<LABEL> variables; nothing is written to the database
+    #[arg(long)]
+    in_memory: bool,
+}
+
+impl Cli {
+    /// Configuration values set on the command line
+    fn config_overrides(&self) -> Vec<(&'static str, toml::Value)> {
+        let bind = match &self.command {
+            Some(Commands::Proxy { bind: Some(bind), .. }) => Some(*bind),
+            _ => self.bind,
+        };
+
+        let mut overrides = Vec::new();
+        if let Some(bind) = bind {
+            overrides.push(("proxy.bind_addr", toml::Value::String(bind.to_string())));
+        }
+        if let Some(api_key) = &self.api_key {
+            overrides.push(("proxy.api_key", toml::Value::String(api_key.clone())));
+        }
+        overrides
+    }
 }
 
 #[derive(Subcommand)]
@@ -50,6 +68,8 @@ enum Commands {
         /// Bind address
         #[arg(short, long)]
         bind: Option<SocketAddr>,
+        #[command(subcommand)]
+        action: Option<ProxyCommands>,
     },
     /// Add a new account
     Add {
@@ -60,6 +80,35 @@ enum Commands {
         /// Organization ID (optional)
         #[arg(short, long)]
         org_id: Option<String>,
+        /// Tag for grouping; repeat for several, the first one groups the account
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Price factor for this account, e.g. 2.0 for a pricier fine-tune
+        #[arg(long, default_value_t = 1.0)]
+        cost_multiplier: f64,
+        /// Don't send the key upstream, for local backends without auth
+        #[arg(long)]
+        no_upstream_auth: bool,
+        /// Cron expression (UTC, seconds first) for when the account is
+        /// active, e.g. "0 * 9-17 * * Mon-Fri"
+        #[arg(long)]
+        schedule: Option<String>,
+        /// Keep as a health-probed standby, used only when no other account can serve
+        #[arg(long)]
+        standby: bool,
+        /// Tokens per minute to send this account at most
+        #[arg(long)]
+        tpm_limit: Option<u64>,
+        /// Region the account is served from, for `routing.preferred_regions`
+        #[arg(long)]
+        region: Option<String>,
+        /// Endpoint path prefix the key may call, e.g. /v1/embeddings; repeat
+        /// for several, none allows every endpoint
+        #[arg(long = "allowed-path")]
+        allowed_paths: Vec<String>,
+        /// Cost center the account's spend is charged back to
+        #[arg(long)]
+        cost_center: Option<String>,
     },
     /// List all accounts
     List,
@@ -68,26 +117,133 @@ enum Commands {
         /// Account ID or label
         identifier: String,
     },
+    /// Replace an account's API key, keeping its usage history; the new key
+    /// is read from `CAM_NEW_API_KEY` or stdin so it stays out of argv
+    RotateKey {
+        /// Account ID or label
+        identifier: String,
+        /// Check the new key against the upstream API before saving it
+        #[arg(long)]
+        validate: bool,
+    },
     /// Show account details
     Show {
         /// Account ID or label
         identifier: String,
     },
     /// Refresh usage data for all accounts
-    Refresh,
+    Refresh {
+        /// Report spend from this date (YYYY-MM-DD) instead of refreshing snapshots
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// End of the reported range (YYYY-MM-DD); defaults to today
+        #[arg(long, requires = "since")]
+        until: Option<chrono::NaiveDate>,
+    },
+    /// Print spend and requests per cost center from stored usage history,
+    /// as JSON
+    CostReport {
+        /// First day of the range (YYYY-MM-DD)
+        #[arg(long)]
+        since: chrono::NaiveDate,
+        /// Last day of the range (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Print CSV instead of JSON
+        #[arg(long)]
+        csv: bool,
+    },
     /// Configure settings
     Config {
         #[command(subcommand)]
         action: ConfigCommands,
     },
+    /// Check auth, routing and upstream forwarding through a loopback proxy
+    Selftest {
+        /// Forward to a local mock upstream instead of the configured one
+        #[arg(long)]
+        mock: bool,
+    },
+    /// Print which accounts are eligible for each catalog model, as JSON
+    RoutingMatrix,
+    /// List the running proxy's sticky sessions
+    Sessions {
+        #[command(subcommand)]
+        action: Option<SessionsCommands>,
+    },
+    /// Route simulated requests through the current accounts and usage and
+    /// print how they spread, without calling upstream
+    Simulate {
+        /// Number of requests to route
+        #[arg(long, default_value_t = 1000)]
+        requests: usize,
+        /// Model the requests ask for
+        #[arg(long, default_value = "gpt-4")]
+        model: String,
+    },
+    /// Compare two account exports, without showing any keys
+    ExportDiff {
+        /// Older export (JSON)
+        old: PathBuf,
+        /// Newer export (JSON)
+        new: PathBuf,
+    },
+    /// Add accounts in bulk from a CSV file with the columns label, api_key,
+    /// org_id, priority, daily_limit, monthly_limit, model_scope
+    ImportCsv {
+        /// CSV file; model_scope lists models separated by `;`
+        path: PathBuf,
+        /// Check each key against the upstream API and skip rejected ones
+        #[arg(long)]
+        validate_keys: bool,
+    },
+    /// Report whether the database opens, how many account keys decrypt
+    /// and which key derivation is in use
+    #[command(name = "keystatus")]
+    KeyStatus,
+    /// Time saving and loading generated accounts in a throwaway database,
+    /// to gauge key derivation and encryption overhead
+    BenchStorage {
+        /// Accounts to save and load
+        #[arg(long, default_value_t = 1000)]
+        accounts: usize,
+    },
+    /// Compact the database file
+    Vacuum,
+    /// Delete all accounts and usage data, and reset the configuration
+    Reset {
+        /// Confirm deleting everything; nothing happens without it
+        #[arg(long)]
+        yes: bool,
+        /// Leave the configuration file as it is
+        #[arg(long)]
+        keep_config: bool,
+    },
     /// Run interactive TUI
     Tui,
 }
 
+#[derive(Subcommand)]
+enum ProxyCommands {
+    /// Generate a random proxy API key and write it to the configuration
+    GenKey,
+}
+
+#[derive(Subcommand)]
+enum SessionsCommands {
+    /// Unpin a sticky session so its next request is routed afresh
+    Revoke {
+        /// Session hash, or a prefix only it starts with
+        session: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfigCommands {
     /// Show current configuration
     Show,
+    /// Show the resolved configuration and where each value came from
+    Effective,
     /// Set configuration value
     Set {
         /// Key (e.g., proxy.bind_addr)
@@ -111,39 +267,65 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Load or create configuration
-    let (config, config_path) = Config::load()?;
+    // Load or create configuration, applying env and command-line overrides
+    let (effective, config_path) = Config::load_effective(&cli.config_overrides())?;
+    let config = effective.config.clone();
 
-    // Get master key
-    let master_key = cli.master_key.unwrap_or_else(|| {
-        // Try to get from environment or prompt
-        std::env::var("CAM_MASTER_KEY").unwrap_or_else(|_| {
-            // In a real implementation, we'd use dialoguer here
-            // For now, use a default (NOT FOR PRODUCTION)
-            warn!("Using default master key - set CAM_MASTER_KEY environment variable!");
-            "codex-account-manager-default-key".to_string()
-        })
-    });
+    // Commands that don't touch the database
+    match &cli.command {
+        Some(Commands::Proxy {
+            action: Some(ProxyCommands::GenKey),
+            ..
+        }) => return gen_proxy_key(&effective, &config_path),
+        Some(Commands::ExportDiff { old, new }) => return export_diff(old, new),
+        Some(Commands::BenchStorage { accounts }) => return bench_storage(*accounts),
+        Some(Commands::Sessions { action }) => return sessions_command(&config, action.as_ref()).await,
+        _ => {}
+    }
 
-    // Initialize encrypted store
-    let db_path = Config::db_path()?;
-    std::fs::create_dir_all(db_path.parent().unwrap())?;
+    let opened = if cli.in_memory {
+        open_in_memory_store(cli.master_key)
+    } else {
+        open_database(&config, cli.master_key)
+    };
 
-    let store = EncryptedStore::open(&db_path, &master_key)
-        .context("Failed to open encrypted database. Check your master key.")?;
+    // Reports an unopenable database instead of failing on it
+    if let Some(Commands::KeyStatus) = cli.command {
+        return print_key_status(opened);
+    }
+    let store = opened?;
 
     // Execute command or start TUI
     match cli.command {
-        Some(Commands::Proxy { bind }) => {
-            let bind_addr = bind.unwrap_or(cli.bind);
-            run_proxy(bind_addr, cli.api_key, store).await?;
+        Some(Commands::Proxy { .. }) => {
+            let proxy = config.proxy.clone();
+            run_proxy(proxy.bind_addr, proxy.api_key, store, &config).await?;
         }
         Some(Commands::Add {
             label,
             api_key,
             org_id,
+            tags,
+            cost_multiplier,
+            no_upstream_auth,
+            schedule,
+            standby,
+            tpm_limit,
+            region,
+            allowed_paths,
+            cost_center,
         }) => {
-            add_account(store, label, api_key, org_id).await?;
+            let account = models::Account::new(label, api_key)
+                .with_tags(tags)
+                .with_cost_multiplier(cost_multiplier)
+                .with_no_upstream_auth(no_upstream_auth)
+                .with_schedule(schedule)
+                .with_standby(standby)
+                .with_tpm_limit(tpm_limit)
+                .with_region(region)
+                .with_allowed_paths(allowed_paths)
+                .with_cost_center(cost_center);
+            add_account(store, account, org_id).await?;
         }
         Some(Commands::List) => {
             list_accounts(store).await?;
@@ -151,11 +333,61 @@ async fn main() -> Result<()> {
         Some(Commands::Remove { identifier }) => {
             remove_account(store, identifier).await?;
         }
+        Some(Commands::RotateKey { identifier, validate }) => {
+            let api_key = read_new_api_key()?;
+            rotate_key(store, &config, identifier, api_key, validate).await?;
+        }
         Some(Commands::Show { identifier }) => {
             show_account(store, identifier).await?;
         }
-        Some(Commands::Refresh) => {
-            refresh_usage(store).await?;
+        Some(Commands::Refresh { since: Some(since), until }) => {
+            let until = until.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            let range = crate::usage::UsageRange::new(since, until)?;
+            report_usage_range(store, &config, range).await?;
+        }
+        Some(Commands::Refresh { .. }) => {
+            refresh_usage(store, &config).await?;
+        }
+        Some(Commands::CostReport { since, until, csv }) => {
+            let until = until.unwrap_or_else(|| chrono::Utc::now().date_naive());
+            if since > until {
+                anyhow::bail!("Report start {} is after its end {}", since, until);
+            }
+            print_cost_report(store, since, until, csv)?;
+        }
+        Some(Commands::Selftest { mock }) => {
+            run_selftest(store, &config, mock).await?;
+        }
+        Some(Commands::RoutingMatrix) => {
+            print_routing_matrix(store, &config).await?;
+        }
+        Some(Commands::Sessions { .. }) => unreachable!("handled before opening the store"),
+        Some(Commands::Simulate { requests, model }) => {
+            simulate_routing(store, &config, &model, requests).await?;
+        }
+        Some(Commands::ExportDiff { .. }) => unreachable!("handled before opening the store"),
+        Some(Commands::ImportCsv { path, validate_keys }) => {
+            import_csv(store, &config, &path, validate_keys).await?;
+        }
+        Some(Commands::KeyStatus) => unreachable!("handled when opening the store"),
+        Some(Commands::BenchStorage { .. }) => unreachable!("handled before opening the store"),
+        Some(Commands::Vacuum) => {
+            let reclaimed = store.vacuum()?;
+            println!("✓ Database compacted, reclaimed {} bytes", reclaimed);
+        }
+        Some(Commands::Reset { yes, keep_config }) => {
+            if !yes {
+                anyhow::bail!("This deletes all accounts and usage data; pass --yes to confirm");
+            }
+            let purged = store.purge_all()?;
+            println!(
+                "✓ Deleted {} accounts and {} usage snapshots",
+                purged.accounts, purged.usage_snapshots
+            );
+            if !keep_config {
+                Config::default().save(&config_path)?;
+                println!("✓ Configuration reset to defaults");
+            }
         }
         Some(Commands::Config { action }) => {
             match action {
@@ -163,6 +395,10 @@ async fn main() -> Result<()> {
                     println!("Configuration file: {:?}", config_path);
                     println!("{}", toml::to_string_pretty(&config)?);
                 }
+                ConfigCommands::Effective => {
+                    println!("Configuration file: {:?}", config_path);
+                    print!("{}", effective.render()?);
+                }
                 ConfigCommands::Set { key, value } => {
                     println!("Setting {} = {}", key, value);
                     // Implementation would update config and save
@@ -176,11 +412,11 @@ async fn main() -> Result<()> {
         }
         Some(Commands::Tui) | None => {
             // Start TUI
-            let strategy = parse_routing_strategy(&config.routing.strategy);
-            let routing_engine = Arc::new(RoutingEngine::new(strategy));
+            let routing_engine = Arc::new(build_routing_engine(&config));
 
             if cli.proxy_only {
-                run_proxy(cli.bind, cli.api_key, store).await?;
+                let proxy = config.proxy.clone();
+                run_proxy(proxy.bind_addr, proxy.api_key, store, &config).await?;
             } else {
                 run_tui(store, routing_engine, config).await?;
             }
@@ -190,55 +426,657 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Open the encrypted database in the data directory
+fn open_database(config: &Config, master_key: Option<String>) -> Result<EncryptedStore> {
+    // Get master key (clap also reads CAM_MASTER_KEY)
+    let master_key = config.resolve_master_key(master_key)?;
+
+    // Initialize encrypted store
+    let db_path = Config::db_path()?;
+    std::fs::create_dir_all(db_path.parent().unwrap())?;
+
+    EncryptedStore::open(&db_path, &master_key)
+        .context("Failed to open encrypted database. Check your master key.")
+}
+
+/// Benchmark a temporary database, leaving the real one untouched
+fn bench_storage(accounts: usize) -> Result<()> {
+    let path = std::env::temp_dir().join(format!("cam-bench-{}.db", uuid::Uuid::new_v4()));
+    let bench = storage::benchmark(&path, "cam-bench-storage", accounts);
+    let _ = std::fs::remove_file(&path);
+    let bench = bench?;
+
+    println!("Benchmarked {} accounts:", bench.accounts);
+    println!("  Open (key derivation): {:>10.1} ms", bench.open.as_secs_f64() * 1000.0);
+    println!(
+        "  Save:                  {:>10.1} ms  ({:.0} ops/sec)",
+        bench.save.as_secs_f64() * 1000.0,
+        bench.save_ops_per_sec()
+    );
+    println!(
+        "  Load:                  {:>10.1} ms  ({:.0} ops/sec)",
+        bench.load.as_secs_f64() * 1000.0,
+        bench.load_ops_per_sec()
+    );
+    Ok(())
+}
+
+/// Print whether the database opens and how many account keys decrypt
+fn print_key_status(opened: Result<EncryptedStore>) -> Result<()> {
+    let store = match opened {
+        Ok(store) => store,
+        Err(e) => {
+            println!("✗ Database does not open: {:#}", e);
+            return Err(e);
+        }
+    };
+    let status = store.key_status()?;
+
+    println!("✓ Database opens");
+    println!(
+        "Accounts:       {} of {} keys decrypt",
+        status.decryptable, status.accounts
+    );
+    for label in &status.undecryptable {
+        println!("  ✗ {}", label);
+    }
+    println!(
+        "Key derivation: {}",
+        status.kdf.as_deref().unwrap_or("none (external secret store)")
+    );
+
+    if !status.undecryptable.is_empty() {
+        println!("\nKeys that don't decrypt were saved with a different master key or salt.");
+    }
+
+    Ok(())
+}
+
+/// An in-memory store holding the accounts defined in the environment.
+///
+/// Nothing outlives the process, so without a master key a random one is used.
+fn open_in_memory_store(master_key: Option<String>) -> Result<EncryptedStore> {
+    warn!("Running with an in-memory store: accounts and usage data are lost on exit");
+
+    let master_key = master_key
+        .filter(|key| !key.is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let store = EncryptedStore::open_in_memory(&master_key)?;
+
+    let env: std::collections::HashMap<String, String> = std::env::vars().collect();
+    let accounts = config::accounts_from_env(&env);
+    for account in &accounts {
+        if let Err(errors) = account.validate() {
+            anyhow::bail!(
+                "Invalid account {}: {}",
+                account.label,
+                models::FieldError::join(&errors)
+            );
+        }
+        store.save_account(account)?;
+    }
+    info!("Loaded {} accounts from the environment", accounts.len());
+
+    Ok(store)
+}
+
 /// Parse routing strategy from string
 fn parse_routing_strategy(s: &str) -> RoutingStrategy {
     match s.to_lowercase().as_str() {
         "round_robin" | "round-robin" => RoutingStrategy::RoundRobin,
         "priority" => RoutingStrategy::Priority,
         "sticky" => RoutingStrategy::Sticky,
+        "cheapest_first" | "cheapest-first" => RoutingStrategy::CheapestFirst,
         _ => RoutingStrategy::LeastUtilized,
     }
 }
 
+/// Build a routing engine from the routing configuration
+fn build_routing_engine(config: &Config) -> RoutingEngine {
+    let routing = &config.routing;
+
+    RoutingEngine::new(parse_routing_strategy(&routing.strategy))
+        .with_seed(routing.routing_seed)
+        .with_usage_max_age(
+            routing.usage_max_age_secs.map(std::time::Duration::from_secs),
+            routing.stale_usage_policy,
+        )
+        .with_size_rules(routing.size_rules.clone())
+        .with_auto_balance(routing.auto_balance)
+        .with_failover_strategy(routing.failover_strategy.as_deref().map(parse_routing_strategy))
+        .with_catalog(models::ModelCatalog::with_overrides(&config.models))
+        .with_sticky_scope(routing.sticky_scope)
+        .with_sticky_decay(routing.sticky_decay_margin)
+        .with_session_ttl(routing.session_ttl_secs.map(std::time::Duration::from_secs))
+        .with_soft_limit_policy(routing.on_all_soft_limited)
+        .with_experiment(routing.experiment.clone())
+        .with_large_request_cooldown(routing.large_request_cooldown)
+        .with_decision_cache_ttl(routing.decision_cache_ttl_ms.map(std::time::Duration::from_millis))
+        .with_preferred_regions(routing.preferred_regions.clone())
+}
+
+/// Request body rewrites from the proxy config, in the order they run
+fn build_transforms(config: &Config) -> proxy::TransformPipeline {
+    let mut pipeline = proxy::TransformPipeline::new();
+    // Before aliasing, so defaults can target an alias
+    if !config.proxy.model_defaults.is_empty() {
+        pipeline = pipeline.with_transform(proxy::ModelDefaults::new(config.proxy.model_defaults.clone()));
+    }
+    if !config.proxy.model_aliases.is_empty() {
+        pipeline = pipeline.with_transform(proxy::ModelAliases::new(config.proxy.model_aliases.clone()));
+    }
+    if !config.proxy.strip_params.is_empty() {
+        pipeline = pipeline.with_transform(proxy::SanitizeParams::new(config.proxy.strip_params.clone()));
+    }
+    if !config.proxy.default_params.is_empty() {
+        pipeline = pipeline.with_transform(proxy::DefaultParams::new(config.proxy.default_params.clone()));
+    }
+    // Last, so no earlier transform raises `max_tokens` past the ceiling
+    if !config.proxy.max_tokens_ceiling.is_empty() {
+        pipeline = pipeline.with_transform(proxy::MaxTokensCeiling::new(config.proxy.max_tokens_ceiling.clone()));
+    }
+    pipeline
+}
+
+/// Proxy server settings from the configuration
+fn build_proxy_config(
+    config: &Config,
+    bind_addr: SocketAddr,
+    api_key: String,
+) -> Result<proxy::ProxyConfig> {
+    let proxy_config = proxy::ProxyConfig {
+        bind_addr,
+        api_key,
+        openai_base_url: config.proxy.openai_base_url.clone(),
+        upstream_timeout: std::time::Duration::from_secs(config.proxy.upstream_timeout_secs),
+        upstream_stream_timeout: std::time::Duration::from_secs(
+            config.proxy.upstream_stream_timeout_secs,
+        ),
+        max_request_cost: config.proxy.max_request_cost,
+        debug_sample_rate: config.proxy.debug_sample_rate,
+        debug_sample_path: match &config.proxy.debug_sample_path {
+            Some(path) => Some(path.clone()),
+            None => Some(config::data_dir()?.join("debug-samples.jsonl")),
+        },
+        forward_headers: config
+            .proxy
+            .forward_headers
+            .iter()
+            .map(|(name, value)| {
+                let name = reqwest::header::HeaderName::try_from(name.as_str())
+                    .with_context(|| format!("Invalid forward header name {:?}", name))?;
+                let value = reqwest::header::HeaderValue::try_from(value.as_str())
+                    .with_context(|| format!("Invalid value for forward header {}", name))?;
+                Ok((name, value))
+            })
+            .collect::<Result<_>>()?,
+        passthrough_headers: config
+            .proxy
+            .passthrough_headers
+            .iter()
+            .map(|name| {
+                reqwest::header::HeaderName::try_from(name.as_str())
+                    .with_context(|| format!("Invalid passthrough header name {:?}", name))
+            })
+            .collect::<Result<_>>()?,
+        strip_client_headers: config
+            .proxy
+            .strip_client_headers
+            .iter()
+            .map(|name| {
+                reqwest::header::HeaderName::try_from(name.as_str())
+                    .with_context(|| format!("Invalid stripped header name {:?}", name))
+            })
+            .collect::<Result<_>>()?,
+        compression: config.proxy.compression,
+        base_path: match &config.proxy.base_path {
+            Some(path) => proxy::normalize_base_path(path)?,
+            None => None,
+        },
+        retry_budget: config.proxy.retry_budget,
+        failover_jitter: std::time::Duration::from_millis(config.proxy.failover_jitter_ms),
+        failover_deadline: config.proxy.failover_deadline_ms.map(std::time::Duration::from_millis),
+        require_explicit_model: config.proxy.require_explicit_model,
+        reserialize_pretty: config.proxy.reserialize_pretty,
+        normalize_outgoing_json: config.proxy.normalize_outgoing_json,
+        status_classes: proxy::StatusClasses {
+            retryable: config.proxy.retryable_statuses.clone(),
+            fatal: config.proxy.fatal_statuses.clone(),
+            rate_limited: config.proxy.rate_limit_statuses.clone(),
+        },
+        default_org_id: config.proxy.default_org_id.clone(),
+        over_budget_response: config.proxy.over_budget_response.clone(),
+        min_ready_accounts: config.proxy.min_ready_accounts,
+        min_tls_version: config
+            .proxy
+            .min_tls_version
+            .as_deref()
+            .map(proxy::parse_tls_version)
+            .transpose()?,
+        accept_invalid_certs: config.proxy.accept_invalid_certs,
+        connect_timeout: config.proxy.connect_timeout_secs.map(std::time::Duration::from_secs),
+    };
+
+    // Fail at startup rather than on the first upstream request
+    proxy_config
+        .http_client()
+        .context("Failed to build the upstream HTTP client")?;
+    if proxy_config.accept_invalid_certs {
+        warn!("Upstream certificate checks are disabled (proxy.accept_invalid_certs)");
+    }
+
+    Ok(proxy_config)
+}
+
+/// Print the accounts added, removed and changed between two exports
+fn export_diff(old: &std::path::Path, new: &std::path::Path) -> Result<()> {
+    let load = |path: &std::path::Path| -> Result<models::AccountExport> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("{} is not an account export", path.display()))
+    };
+    let diff = load(old)?.diff(&load(new)?);
+
+    if diff.is_empty() {
+        println!("No changes");
+        return Ok(());
+    }
+    for (id, label) in &diff.added {
+        println!("+ {} ({})", label, id);
+    }
+    for (id, label) in &diff.removed {
+        println!("- {} ({})", label, id);
+    }
+    for change in &diff.changed {
+        let key_note = if change.key_changed() { "  [api key changed]" } else { "" };
+        println!("~ {} ({}): {}{}", change.label, change.id, change.fields.join(", "), key_note);
+    }
+    Ok(())
+}
+
+/// Write a fresh proxy API key to the configuration file and print it once
+fn gen_proxy_key(effective: &config::EffectiveConfig, config_path: &std::path::Path) -> Result<()> {
+    let key = config::generate_proxy_api_key();
+    Config::store_proxy_api_key(config_path, &key)?;
+
+    println!("{}", key);
+    eprintln!("Saved to {} - it will not be shown again.", config_path.display());
+    if let Some(source @ (config::ConfigSource::Env | config::ConfigSource::Cli)) =
+        effective.sources.get("proxy.api_key")
+    {
+        eprintln!(
+            "Note: proxy.api_key is currently overridden from {}; the new key only applies without that override.",
+            source
+        );
+    }
+    Ok(())
+}
+
 /// Run the proxy server
 async fn run_proxy(
     bind_addr: SocketAddr,
     api_key: String,
     store: EncryptedStore,
+    config: &Config,
 ) -> Result<()> {
     info!("Starting proxy server on http://{}", bind_addr);
 
+    if api_key == config::DEFAULT_PROXY_API_KEY {
+        warn!("Proxy is using the built-in default API key - generate one with: cam proxy gen-key");
+    }
+
     // Load accounts
     let accounts = store.load_accounts()?;
     info!("Loaded {} accounts", accounts.len());
 
+    if accounts.is_empty() {
+        warn!("==========================================================");
+        warn!("No accounts configured - every proxied request will fail.");
+        warn!("Add one with: cam add <label> <api_key>");
+        warn!("==========================================================");
+    }
+
     // Create routing engine
-    let strategy = RoutingStrategy::LeastUtilized;
-    let routing_engine = Arc::new(RoutingEngine::new(strategy));
+    let routing_engine = Arc::new(build_routing_engine(config));
+    let persist_engine_state = config.routing.persist_engine_state;
+    if persist_engine_state {
+        // A bad snapshot only costs the warm state, not the start
+        if let Some(saved) = store.engine_state()? {
+            match routing_engine.import_state(&saved).await {
+                Ok(()) => info!("Restored routing state from the last run"),
+                Err(e) => warn!("Ignoring saved routing state: {:#}", e),
+            }
+        }
+    }
 
     // Update with current accounts
     let usage_map = std::collections::HashMap::new();
     routing_engine.update_accounts(accounts, usage_map).await;
 
     // Start proxy
-    let proxy_config = proxy::ProxyConfig {
-        bind_addr,
-        api_key,
-        openai_base_url: "https://api.openai.com".to_string(),
-    };
+    let proxy_config = build_proxy_config(config, bind_addr, api_key)?;
 
-    let server = proxy::ProxyServer::new(routing_engine, proxy_config);
-    server.start().await?;
+    let mut targets = vec![proxy::BindTarget::Tcp(bind_addr)];
+    for raw in &config.proxy.additional_binds {
+        targets.push(proxy::BindTarget::parse(raw)?);
+    }
+
+    let mut server =
+        proxy::ProxyServer::new(routing_engine, proxy_config).with_transforms(build_transforms(config));
+    let state = server.state();
+    let persist_total = config.proxy.persist_request_count;
+    if persist_total {
+        let restored = store.total_request_count()?;
+        state.request_count.store(restored, std::sync::atomic::Ordering::Relaxed);
+        info!("Resuming from {} requests served in earlier runs", restored);
+    }
+    server.start(targets).await?;
 
     info!("Proxy server running. Press Ctrl+C to stop.");
 
-    // Wait for shutdown signal
-    tokio::signal::ctrl_c().await?;
+    let polling = &config.polling;
+    let poller = Arc::new(usage::UsagePoller::new()
+        .with_client(config.openai_client()?)
+        .with_intervals(
+            std::time::Duration::from_secs(polling.interval_seconds),
+            std::time::Duration::from_secs(polling.max_interval_seconds),
+        )
+        .with_idle_pause(polling.poll_idle_pause_secs.map(std::time::Duration::from_secs)));
+    let mut poll_errors = 0;
+    let mut last_poll = tokio::time::Instant::now();
+    let mut next_poll = last_poll + poller.effective_interval(0, state.idle_for());
+    // The poll in flight, off the loop so a slow poll never holds up Ctrl+C
+    let mut poll_task: Option<tokio::task::JoinHandle<Vec<PolledUsage>>> = None;
+
+    // Flush per-account request counters and poll usage periodically until shutdown
+    let mut flush = tokio::time::interval(std::time::Duration::from_secs(REQUEST_COUNT_FLUSH_SECS));
+    let mut schedules = tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_CHECK_SECS));
+    let mut standby_probes =
+        tokio::time::interval(std::time::Duration::from_secs(STANDBY_PROBE_SECS));
+    let probe_client = config.openai_client()?;
+    let circuit_probe_secs = config.routing.probe_interval_secs;
+    let mut circuit_probes = tokio::time::interval(std::time::Duration::from_secs(
+        circuit_probe_secs.unwrap_or(STANDBY_PROBE_SECS).max(1),
+    ));
+    let warmup_on_reset = config.routing.warmup_on_enable;
+    let mut circuit_warmups =
+        tokio::time::interval(std::time::Duration::from_secs(CIRCUIT_WARMUP_SECS));
+    let capabilities = config
+        .routing
+        .capability_ttl_secs
+        .map(|ttl| usage::CapabilityCache::new(std::time::Duration::from_secs(ttl)));
+    if let Some(cache) = &capabilities {
+        for probe in store.load_capabilities()? {
+            state.routing_engine.set_capabilities(probe.clone());
+            cache.insert(probe);
+        }
+    }
+    let session_ttl = config.routing.session_ttl_secs;
+    let mut session_evictions =
+        tokio::time::interval(std::time::Duration::from_secs(SESSION_EVICTION_SECS));
+    let mut capability_checks =
+        tokio::time::interval(std::time::Duration::from_secs(CAPABILITY_CHECK_SECS));
+    loop {
+        tokio::select! {
+            _ = flush.tick() => {
+                flush_request_counts(&store, &state, persist_total);
+
+                // Traffic resumed while polling was backed off for idleness
+                let due = last_poll + poller.effective_interval(poll_errors, state.idle_for());
+                if due < next_poll {
+                    let wait = due.saturating_duration_since(tokio::time::Instant::now());
+                    debug!("Traffic resumed, next usage poll in {:?}", wait);
+                    next_poll = due;
+                }
+            }
+            _ = schedules.tick() => apply_schedules(&store, &state.routing_engine).await,
+            _ = standby_probes.tick() => probe_standby_accounts(&probe_client, &state.routing_engine).await,
+            _ = session_evictions.tick(), if session_ttl.is_some() => {
+                let evicted = state.routing_engine.evict_stale_sessions();
+                if evicted > 0 {
+                    debug!("Evicted {} idle sticky sessions", evicted);
+                }
+            }
+            _ = capability_checks.tick(), if capabilities.is_some() => {
+                if let Some(cache) = &capabilities {
+                    refresh_capabilities(&store, cache, &probe_client, &state.routing_engine).await;
+                }
+            }
+            _ = circuit_probes.tick(), if circuit_probe_secs.is_some() => {
+                proxy::probe_open_circuits(&probe_client, &state.routing_engine).await;
+            }
+            _ = circuit_warmups.tick(), if warmup_on_reset => {
+                proxy::warm_up_reset_circuits(&probe_client, &state.routing_engine).await;
+            }
+            _ = tokio::time::sleep_until(next_poll), if polling.enabled && poll_task.is_none() => {
+                match store.load_accounts() {
+                    Ok(accounts) => poll_task = Some(tokio::spawn(fetch_usage(poller.clone(), accounts))),
+                    Err(e) => {
+                        error!("Failed to load accounts for usage polling: {}", e);
+                        poll_errors += 1;
+                        next_poll = tokio::time::Instant::now()
+                            + poller.effective_interval(poll_errors, state.idle_for());
+                    }
+                }
+            }
+            results = async { poll_task.as_mut().unwrap().await }, if poll_task.is_some() => {
+                poll_task = None;
+                poll_errors = match results {
+                    Ok(results) => apply_usage(&store, &state.routing_engine, results, poll_errors).await,
+                    Err(e) => {
+                        error!("Usage poll task failed: {}", e);
+                        poll_errors + 1
+                    }
+                };
+                last_poll = tokio::time::Instant::now();
+                let interval = poller.effective_interval(poll_errors, state.idle_for());
+                debug!("Next usage poll in {:?}", interval);
+                next_poll = last_poll + interval;
+            }
+            result = tokio::signal::ctrl_c() => {
+                result?;
+                break;
+            }
+        }
+    }
+
     info!("Shutting down...");
+    if let Some(task) = poll_task {
+        task.abort();
+    }
+    server.stop();
+    flush_request_counts(&store, &state, persist_total);
+    if persist_engine_state {
+        let saved = state.routing_engine.export_state().await;
+        if let Err(e) = saved.and_then(|json| store.save_engine_state(&json)) {
+            error!("Failed to save routing state: {}", e);
+        }
+    }
 
     Ok(())
 }
 
+/// How often the proxy persists its batched request counters
+const REQUEST_COUNT_FLUSH_SECS: u64 = 30;
+
+/// How often standby accounts are health-probed
+const STANDBY_PROBE_SECS: u64 = 60;
+
+/// How often circuits are checked for a reset that needs a warmup
+const CIRCUIT_WARMUP_SECS: u64 = 5;
+
+/// Probe every standby account so its circuit and latency stay current
+/// while it carries no traffic
+async fn probe_standby_accounts(client: &usage::OpenAIClient, engine: &RoutingEngine) {
+    for account in engine.standby_accounts().await {
+        match client.warm_up(&account).await {
+            Ok(latency) => {
+                engine.report_success(account.id);
+                engine.record_latency(account.id, latency);
+            }
+            Err(e) => {
+                warn!("Standby probe failed for {}: {}", account.label, e);
+                engine.report_error(account.id, true);
+            }
+        }
+    }
+}
+
+/// How often idle sticky sessions are evicted
+const SESSION_EVICTION_SECS: u64 = 60;
+
+/// How often capability probes are checked for expiry
+const CAPABILITY_CHECK_SECS: u64 = 60;
+
+/// Re-probe accounts whose cached capabilities have expired, persisting and
+/// routing with the new results
+async fn refresh_capabilities(
+    store: &EncryptedStore,
+    cache: &usage::CapabilityCache,
+    client: &usage::OpenAIClient,
+    engine: &RoutingEngine,
+) {
+    let accounts = match store.load_accounts() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            error!("Failed to load accounts for capability probes: {}", e);
+            return;
+        }
+    };
+
+    for account in accounts.iter().filter(|a| a.enabled) {
+        match cache.get_or_probe(client, account).await {
+            Ok((probe, true)) => {
+                if !probe.valid {
+                    warn!("Capability probe found the key for {} invalid", account.label);
+                }
+                if let Err(e) = store.save_capabilities(&probe) {
+                    error!("Failed to save capabilities for {}: {}", account.label, e);
+                }
+                engine.set_capabilities(probe);
+            }
+            Ok((_, false)) => {}
+            Err(e) => warn!("Capability probe failed for {}: {}", account.label, e),
+        }
+    }
+}
+
+/// How often account schedules are evaluated
+const SCHEDULE_CHECK_SECS: u64 = 60;
+
+/// Enable or disable accounts according to their schedules, pushing any
+/// change to storage and the routing engine
+async fn apply_schedules(store: &EncryptedStore, engine: &RoutingEngine) {
+    let mut accounts = match store.load_accounts() {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            error!("Failed to load accounts for schedules: {}", e);
+            return;
+        }
+    };
+
+    let now = chrono::Utc::now();
+    let mut changed = false;
+    for account in &mut accounts {
+        if !account.apply_schedule(now) {
+            continue;
+        }
+        info!(
+            "Schedule {} account {}",
+            if account.enabled { "enabled" } else { "disabled" },
+            account.label
+        );
+        if let Err(e) = store.save_account(account) {
+            error!("Failed to save scheduled state for {}: {}", account.label, e);
+        }
+        changed = true;
+    }
+
+    if changed {
+        let usage_map = accounts
+            .iter()
+            .filter_map(|a| store.load_latest_usage(a.id).ok().flatten().map(|u| (a.id, u)))
+            .collect();
+        engine.update_accounts(accounts, usage_map).await;
+    }
+}
+
+/// An account and the outcome of polling its usage
+type PolledUsage = (models::Account, Result<models::UsageSnapshot>);
+
+/// Poll every account's usage; runs as its own task, away from storage
+async fn fetch_usage(poller: Arc<usage::UsagePoller>, accounts: Vec<models::Account>) -> Vec<PolledUsage> {
+    let mut results = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let usage = poller.poll_account(&account, None).await;
+        results.push((account, usage));
+    }
+    results
+}
+
+/// Store polled usage and route with it; returns the new consecutive error count
+async fn apply_usage(
+    store: &EncryptedStore,
+    engine: &RoutingEngine,
+    results: Vec<PolledUsage>,
+    consecutive_errors: u32,
+) -> u32 {
+    let mut accounts = Vec::with_capacity(results.len());
+    let mut usage_map = std::collections::HashMap::new();
+    let mut failed = false;
+    for (account, usage) in results {
+        match usage {
+            Ok(usage) => {
+                if let Err(e) = store.save_usage_snapshot(&usage) {
+                    error!("Failed to save usage for {}: {}", account.label, e);
+                }
+                engine.clear_unpolled_usage(account.id);
+                usage_map.insert(account.id, usage);
+            }
+            Err(e) => {
+                warn!("Usage poll failed for {}: {}", account.label, e);
+                failed = true;
+                if let Ok(Some(usage)) = store.load_latest_usage(account.id) {
+                    usage_map.insert(account.id, usage);
+                }
+            }
+        }
+        accounts.push(account);
+    }
+    engine.update_accounts(accounts, usage_map).await;
+
+    if failed { consecutive_errors + 1 } else { 0 }
+}
+
+/// Persist the request counts and usage the proxy accumulated since the last
+/// flush, and with `persist_total` its running request total
+fn flush_request_counts(store: &EncryptedStore, state: &proxy::ProxyState, persist_total: bool) {
+    let today = chrono::Utc::now().date_naive();
+
+    if persist_total {
+        let total = state.request_count.load(std::sync::atomic::Ordering::Relaxed);
+        if let Err(e) = store.save_total_request_count(total) {
+            error!("Failed to save request total: {}", e);
+        }
+    }
+
+    for (account_id, count) in state.take_request_counts() {
+        if let Err(e) = store.add_request_count(account_id, today, count) {
+            error!("Failed to save request count for {}: {}", account_id, e);
+        }
+    }
+
+    for (account_id, delta) in state.routing_engine.take_unpolled_usage() {
+        match store.save_usage_delta(account_id, delta) {
+            // Never polled: hold on to it until the first poll covers it
+            Ok(None) => state.routing_engine.record_unpolled_usage(account_id, delta.tokens, delta.cost),
+            Ok(Some(_)) => {}
+            Err(e) => error!("Failed to save unpolled usage for {}: {}", account_id, e),
+        }
+    }
+}
+
 /// Run the TUI application
 async fn run_tui(
     store: EncryptedStore,
@@ -253,18 +1091,64 @@ async fn run_tui(
 /// Add a new account
 async fn add_account(
     store: EncryptedStore,
-    label: String,
-    api_key: String,
+    mut account: models::Account,
     org_id: Option<String>,
 ) -> Result<()> {
-    let mut account = models::Account::new(label.clone(), api_key);
 
     if let Some(org) = org_id {
         account = account.with_org_id(org);
     }
 
+    if let Err(errors) = account.validate() {
+        anyhow::bail!("Invalid account: {}", models::FieldError::join(&errors));
+    }
+
     store.save_account(&account)?;
-    println!("✓ Added account: {} ({})", label, account.id);
+    println!("✓ Added account: {} ({})", account.label, account.id);
+
+    Ok(())
+}
+
+/// Add the valid rows of an account CSV, reporting the rows that were skipped
+async fn import_csv(
+    store: EncryptedStore,
+    config: &Config,
+    path: &std::path::Path,
+    validate_keys: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let import = models::CsvImport::parse(&content);
+    let client = config.openai_client()?;
+
+    let mut imported = 0;
+    let mut rejected = 0;
+    for account in &import.accounts {
+        if validate_keys {
+            let valid = client
+                .validate_key(&account.api_key, account.org_id.as_deref())
+                .await
+                .is_ok_and(|info| info.is_valid);
+            if !valid {
+                println!("✗ {}: key was rejected upstream", account.label);
+                rejected += 1;
+                continue;
+            }
+        }
+
+        store.save_account(account)?;
+        println!("✓ Added account: {} ({})", account.label, account.id);
+        imported += 1;
+    }
+
+    for error in &import.errors {
+        println!("✗ Line {}: {}", error.line, error.message);
+    }
+    println!(
+        "\nImported {} accounts, skipped {} rows",
+        imported,
+        import.errors.len() + rejected
+    );
 
     Ok(())
 }
@@ -278,16 +1162,90 @@ async fn list_accounts(store: EncryptedStore) -> Result<()> {
         return Ok(());
     }
 
-    println!("\n{:<36} {:<20} {:<10} {:<10}", "ID", "Label", "Priority", "Enabled");
-    println!("{}", "-".repeat(80));
+    println!(
+        "\n{:<36} {:<20} {:<10} {:<10} {:<10} {:<10}",
+        "ID", "Label", "Priority", "Enabled", "Today", "Total"
+    );
+    println!("{}", "-".repeat(101));
 
     for account in accounts {
         println!(
-            "{:<36} {:<20} {:<10} {:<10}",
+            "{:<36} {:<20} {:<10} {:<10} {:<10} {:<10}",
             account.id,
             account.label,
             account.priority,
-            if account.enabled { "✓" } else { "✗" }
+            if account.enabled { "✓" } else { "✗" },
+            store.requests_today(account.id).unwrap_or(0),
+            store.lifetime_requests(account.id).unwrap_or(0)
+        );
+    }
+
+    println!();
+    Ok(())
+}
+
+/// List or revoke sticky sessions through the running proxy's admin API
+async fn sessions_command(config: &Config, action: Option<&SessionsCommands>) -> Result<()> {
+    let mut addr = config.proxy.bind_addr;
+    if addr.ip().is_unspecified() {
+        let loopback: std::net::IpAddr = if addr.is_ipv4() {
+            std::net::Ipv4Addr::LOCALHOST.into()
+        } else {
+            std::net::Ipv6Addr::LOCALHOST.into()
+        };
+        addr.set_ip(loopback);
+    }
+
+    let url = format!("http://{}/admin/sessions", addr);
+    let client = reqwest::Client::new();
+    let request = match action {
+        None => client.get(&url),
+        Some(SessionsCommands::Revoke { session }) => client
+            .post(format!("{}/revoke", url))
+            .json(&serde_json::json!({ "session": session })),
+    };
+    let response = request
+        .bearer_auth(&config.proxy.api_key)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach the proxy at {}", addr))?;
+
+    let status = response.status();
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .context("Proxy returned an unreadable response")?;
+    if !status.is_success() {
+        anyhow::bail!(
+            "{}",
+            body["error"]["message"].as_str().unwrap_or(status.as_str())
+        );
+    }
+
+    if let Some(SessionsCommands::Revoke { .. }) = action {
+        println!("✓ Revoked session: {}", body["revoked"].as_str().unwrap_or_default());
+        return Ok(());
+    }
+
+    let sessions: Vec<routing::SessionInfo> =
+        serde_json::from_value(body["sessions"].clone()).context("Unexpected sessions response")?;
+    if sessions.is_empty() {
+        println!("No sticky sessions.");
+        return Ok(());
+    }
+
+    println!(
+        "\n{:<20} {:<12} {:<20} {:<10}",
+        "Session", "Family", "Account", "Idle"
+    );
+    println!("{}", "-".repeat(65));
+    for session in sessions {
+        println!(
+            "{:<20} {:<12} {:<20} {:<10}",
+            session.session_prefix,
+            session.family.as_deref().unwrap_or("-"),
+            session.account_label,
+            format!("{}s", session.idle_seconds)
         );
     }
 
@@ -297,23 +1255,27 @@ async fn list_accounts(store: EncryptedStore) -> Result<()> {
 
 /// Remove an account
 async fn remove_account(store: EncryptedStore, identifier: String) -> Result<()> {
-    // Try to parse as UUID first
-    let id = if let Ok(uuid) = identifier.parse::<uuid::Uuid>() {
-        uuid
-    } else {
-        // Try to find by label
-        let accounts = store.load_accounts()?;
-        let found = accounts.iter().find(|a| a.label == identifier);
-
-        match found {
-            Some(account) => account.id,
+    // Try to parse as UUID first, then find by label
+    let accounts = store.load_accounts()?;
+    let account = match identifier.parse::<uuid::Uuid>() {
+        Ok(uuid) => match accounts.iter().find(|a| a.id == uuid) {
+            Some(account) => account,
             None => {
-                anyhow::bail!("Account not found: {}", identifier);
+                println!("✗ Account not found: {}", identifier);
+                return Ok(());
             }
-        }
+        },
+        Err(_) => accounts
+            .iter()
+            .find(|a| a.label == identifier)
+            .with_context(|| format!("Account not found: {}", identifier))?,
     };
 
-    if store.delete_account(id)? {
+    if let Err(errors) = account.validate_removal() {
+        anyhow::bail!("Cannot remove {}: {}", account.label, models::FieldError::join(&errors));
+    }
+
+    if store.delete_account(account.id)? {
         println!("✓ Removed account: {}", identifier);
     } else {
         println!("✗ Account not found: {}", identifier);
@@ -322,6 +1284,72 @@ async fn remove_account(store: EncryptedStore, identifier: String) -> Result<()>
     Ok(())
 }
 
+/// Read the key for `rotate-key` from `CAM_NEW_API_KEY`, prompting without
+/// echo on a terminal and reading one line from piped stdin otherwise
+fn read_new_api_key() -> Result<String> {
+    use std::io::{BufRead, IsTerminal};
+
+    if let Ok(key) = std::env::var("CAM_NEW_API_KEY") {
+        return Ok(key.trim().to_string());
+    }
+
+    let key = if std::io::stdin().is_terminal() {
+        dialoguer::Password::new()
+            .with_prompt("New API key")
+            .interact()
+            .context("Failed to read the new API key")?
+    } else {
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .context("Failed to read the new API key from stdin")?;
+        line
+    };
+
+    Ok(key.trim().to_string())
+}
+
+/// Replace an account's API key in place
+async fn rotate_key(
+    store: EncryptedStore,
+    config: &Config,
+    identifier: String,
+    api_key: String,
+    validate: bool,
+) -> Result<()> {
+    let account = if let Ok(uuid) = identifier.parse::<uuid::Uuid>() {
+        store.load_account(uuid)?
+    } else {
+        store.load_accounts()?.into_iter().find(|a| a.label == identifier)
+    };
+    let Some(original) = account else {
+        anyhow::bail!("Account not found: {}", identifier);
+    };
+
+    let mut account = original.clone();
+    account.api_key = api_key;
+    if let Err(errors) = original.validate_edit(&account) {
+        anyhow::bail!("Cannot rotate key: {}", models::FieldError::join(&errors));
+    }
+    if let Err(errors) = account.validate() {
+        anyhow::bail!("Invalid account: {}", models::FieldError::join(&errors));
+    }
+
+    if validate {
+        config
+            .openai_client()?
+            .validate_key(&account.api_key, account.org_id.as_deref())
+            .await
+            .context("New key was rejected upstream")?;
+    }
+
+    store.update_account_key(account.id, &account.api_key)?;
+    println!("✓ Rotated key for account: {} ({})", account.label, account.id);
+
+    Ok(())
+}
+
 /// Show account details
 async fn show_account(store: EncryptedStore, identifier: String) -> Result<()> {
     // Try to parse as UUID first
@@ -342,6 +1370,13 @@ async fn show_account(store: EncryptedStore, identifier: String) -> Result<()> {
             println!("Priority:  {}", acc.priority);
             println!("Enabled:   {}", acc.enabled);
 
+            if let Some(reason) = &acc.disabled_reason {
+                match acc.disabled_at {
+                    Some(at) => println!("Disabled:  {} (since {})", reason, at.to_rfc3339()),
+                    None => println!("Disabled:  {}", reason),
+                }
+            }
+
             if let Some(org) = acc.org_id {
                 println!("Org ID:    {}", org);
             }
@@ -354,6 +1389,18 @@ async fn show_account(store: EncryptedStore, identifier: String) -> Result<()> {
                 println!("Monthly:   ${:.2}", monthly);
             }
 
+            if let Some(schedule) = &acc.schedule {
+                println!("Schedule:  {}", schedule);
+            }
+
+            if acc.standby {
+                println!("Standby:   yes");
+            }
+
+            if let Some(tpm) = acc.tpm_limit {
+                println!("TPM limit: {}", tpm);
+            }
+
             // Show usage if available
             if let Ok(Some(usage)) = store.load_latest_usage(acc.id) {
                 println!("\nUsage Snapshot");
@@ -380,13 +1427,144 @@ async fn show_account(store: EncryptedStore, identifier: String) -> Result<()> {
     Ok(())
 }
 
+/// Latest usage snapshot per account
+type UsageMap = std::collections::HashMap<models::AccountId, models::UsageSnapshot>;
+
+/// Stored accounts and the latest usage snapshot of each one that has one
+fn load_accounts_with_usage(store: &EncryptedStore) -> Result<(Vec<models::Account>, UsageMap)> {
+    let accounts = store.load_accounts()?;
+    let mut usage_map = std::collections::HashMap::new();
+    for account in &accounts {
+        if let Some(usage) = store.load_latest_usage(account.id)? {
+            usage_map.insert(account.id, usage);
+        }
+    }
+    Ok((accounts, usage_map))
+}
+
+/// Run the proxy self-test with the configured accounts and api_key
+async fn run_selftest(store: EncryptedStore, config: &Config, mock: bool) -> Result<()> {
+    let (accounts, usage_map) = load_accounts_with_usage(&store)?;
+
+    let engine = Arc::new(build_routing_engine(config));
+    engine.update_accounts(accounts, usage_map).await;
+
+    let mut proxy_config =
+        build_proxy_config(config, config.proxy.bind_addr, config.proxy.api_key.clone())?;
+    // Samples from a self-test would only pollute the debug log
+    proxy_config.debug_sample_rate = 0;
+    if mock {
+        proxy_config.openai_base_url = proxy::spawn_mock_upstream().await?;
+    }
+    println!("Testing proxy against {}...", proxy_config.openai_base_url);
+
+    let report = proxy::selftest(engine, proxy_config, &config.proxy.probe_model).await?;
+    print!("{}", report.summary());
+
+    if !report.passed() {
+        anyhow::bail!("Proxy self-test failed");
+    }
+    Ok(())
+}
+
+/// Print the model x account eligibility matrix from stored accounts and usage
+async fn print_routing_matrix(store: EncryptedStore, config: &Config) -> Result<()> {
+    let (accounts, usage_map) = load_accounts_with_usage(&store)?;
+
+    let engine = build_routing_engine(config);
+    engine.update_accounts(accounts, usage_map).await;
+
+    println!("{}", serde_json::to_string_pretty(&engine.routing_matrix().await)?);
+    Ok(())
+}
+
+/// Print where `requests` simulated requests for `model` would be routed
+async fn simulate_routing(
+    store: EncryptedStore,
+    config: &Config,
+    model: &str,
+    requests: usize,
+) -> Result<()> {
+    let (accounts, usage_map) = load_accounts_with_usage(&store)?;
+
+    let engine = build_routing_engine(config);
+    engine.update_accounts(accounts, usage_map).await;
+    let report = engine.simulate(model, requests).await;
+
+    println!(
+        "Simulated {} {} requests with strategy {:?}:",
+        report.requests, report.model, report.strategy
+    );
+    for account in &report.accounts {
+        let share = if report.requests == 0 {
+            0.0
+        } else {
+            account.requests as f64 / report.requests as f64 * 100.0
+        };
+        println!("  {:<24} {:>8} {:>6.1}%", account.label, account.requests, share);
+    }
+    if report.unrouted > 0 {
+        println!("  {:<24} {:>8}", "No account available", report.unrouted);
+    }
+    println!("Variance: {:.2}", report.variance());
+    println!("Gini: {:.3}", report.gini());
+    Ok(())
+}
+
+/// Print each account's spend over a date range without saving snapshots
+async fn report_usage_range(
+    store: EncryptedStore,
+    config: &Config,
+    range: crate::usage::UsageRange,
+) -> Result<()> {
+    let accounts = store.load_accounts()?;
+    let client = config.openai_client()?;
+
+    println!(
+        "Usage from {} to {} for {} accounts:",
+        range.start,
+        range.end,
+        accounts.len()
+    );
+
+    let mut total = 0.0;
+    for account in accounts {
+        match client.fetch_usage_range(&account, range).await {
+            Ok(spend) => {
+                total += spend;
+                println!("  {:<24} ${:.2}", account.label, spend);
+            }
+            Err(e) => println!("  {:<24} ✗ ({})", account.label, e),
+        }
+    }
+    println!("  {:<24} ${:.2}", "Total", total);
+
+    Ok(())
+}
+
+/// Print usage grouped by cost center as JSON or CSV
+fn print_cost_report(
+    store: EncryptedStore,
+    since: chrono::NaiveDate,
+    until: chrono::NaiveDate,
+    csv: bool,
+) -> Result<()> {
+    let report = store.cost_center_report(since, until)?;
+    if csv {
+        print!("{}", report.to_csv());
+    } else {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+
+    Ok(())
+}
+
 /// Refresh usage data for all accounts
-async fn refresh_usage(store: EncryptedStore) -> Result<()> {
-    use crate::usage::{OpenAIClient, UsagePoller};
+async fn refresh_usage(store: EncryptedStore, config: &Config) -> Result<()> {
+    use crate::usage::{PollError, UsagePoller};
 
     let accounts = store.load_accounts()?;
-    let client = OpenAIClient::new();
-    let poller = UsagePoller::new();
+    let poller = UsagePoller::new().with_client(config.openai_client()?);
 
     println!("Refreshing usage for {} accounts...", accounts.len());
 
@@ -401,10 +1579,19 @@ async fn refresh_usage(store: EncryptedStore) -> Result<()> {
                     usage.monthly_usage,
                     usage.utilization_ratio() * 100.0
                 );
+
+                // Routing already skips exhausted accounts, and stops once
+                // the next month's usage comes in
+                if account.enabled && usage.remaining_budget.is_some_and(|r| r <= 0.0) {
+                    println!("    hard limit reached; skipped by routing until usage resets");
+                }
             }
-            Err(e) => {
-                println!("✗ ({})", e);
-            }
+            // Tell a broken key apart from a poll that can be retried
+            Err(e) => match e.downcast_ref::<PollError>() {
+                Some(PollError::KeyRejected(_)) => println!("✗ ({}; the key is invalid or revoked)", e),
+                Some(PollError::RateLimited(_)) => println!("✗ ({}; the key is valid)", e),
+                None => println!("✗ ({})", e),
+            },
         }
     }
 
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::routing::RoutingStrategy;
+
 /// Unique identifier for accounts
 pub type AccountId = Uuid;
 
@@ -20,6 +22,63 @@ pub struct Account {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_used: Option<DateTime<Utc>>,
+    /// Why the account was disabled automatically, if it was
+    #[serde(default)]
+    pub disabled_reason: Option<String>,
+    /// When the account was disabled automatically
+    #[serde(default)]
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Per-account routing hint, see `RoutingEngine` for precedence
+    #[serde(default)]
+    pub preferred_strategy: Option<RoutingStrategy>,
+    /// Free-form labels; the first one groups the account in the TUI
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Factor applied to list prices for this account, e.g. 2.0 for a
+    /// fine-tune billed at twice the base model's rate
+    #[serde(default = "default_cost_multiplier")]
+    pub cost_multiplier: f64,
+    /// Send upstream requests without an Authorization header, for local
+    /// OpenAI-compatible backends that reject or don't need a key
+    #[serde(default)]
+    pub no_upstream_auth: bool,
+    /// Cron expression (UTC, with a seconds field) matching the minutes the
+    /// account should be active; outside them the scheduler disables it
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Warm standby: health-probed but only routed to while no primary
+    /// (non-standby) account can serve a request
+    #[serde(default)]
+    pub standby: bool,
+    /// Tokens per minute the proxy sends this account at most, mirroring
+    /// OpenAI's TPM limit
+    #[serde(default)]
+    pub tpm_limit: Option<u64>,
+    /// Region the account's traffic is served from, e.g. `eu`; breaks ties
+    /// by the configured region preference
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Endpoint path prefixes a scoped key may call, e.g. `/v1/embeddings`;
+    /// empty allows every endpoint
+    #[serde(default)]
+    pub allowed_paths: Vec<String>,
+    /// Cost center the account's spend is charged back to
+    #[serde(default)]
+    pub cost_center: Option<String>,
+    /// Listed first in the TUI; no effect on routing
+    #[serde(default)]
+    pub pinned: bool,
+    /// Read-only: only `enabled` may change until the account is unlocked
+    #[serde(default)]
+    pub locked: bool,
+}
+
+/// `disabled_reason` the scheduler sets, so it only re-enables accounts it
+/// disabled itself
+pub const SCHEDULE_DISABLED_REASON: &str = "Outside scheduled window";
+
+fn default_cost_multiplier() -> f64 {
+    1.0
 }
 
 impl Account {
@@ -38,6 +97,20 @@ impl Account {
             created_at: now,
             updated_at: now,
             last_used: None,
+            disabled_reason: None,
+            disabled_at: None,
+            preferred_strategy: None,
+            tags: vec![],
+            cost_multiplier: default_cost_multiplier(),
+            no_upstream_auth: false,
+            schedule: None,
+            standby: false,
+            tpm_limit: None,
+            region: None,
+            allowed_paths: vec![],
+            cost_center: None,
+            pinned: false,
+            locked: false,
         }
     }
 
@@ -51,6 +124,57 @@ impl Account {
         self
     }
 
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_cost_multiplier(mut self, multiplier: f64) -> Self {
+        self.cost_multiplier = multiplier;
+        self
+    }
+
+    pub fn with_no_upstream_auth(mut self, no_upstream_auth: bool) -> Self {
+        self.no_upstream_auth = no_upstream_auth;
+        self
+    }
+
+    pub fn with_tpm_limit(mut self, tpm_limit: Option<u64>) -> Self {
+        self.tpm_limit = tpm_limit;
+        self
+    }
+
+    pub fn with_region(mut self, region: Option<String>) -> Self {
+        self.region = region;
+        self
+    }
+
+    pub fn with_allowed_paths(mut self, allowed_paths: Vec<String>) -> Self {
+        self.allowed_paths = allowed_paths;
+        self
+    }
+
+    pub fn with_cost_center(mut self, cost_center: Option<String>) -> Self {
+        self.cost_center = cost_center;
+        self
+    }
+
+    /// Whether the account may be sent a request for `path`
+    pub fn allows_path(&self, path: &str) -> bool {
+        self.allowed_paths.is_empty()
+            || self.allowed_paths.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+
+    pub fn with_standby(mut self, standby: bool) -> Self {
+        self.standby = standby;
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: Option<String>) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
     pub fn with_limits(mut self, daily: Option<f64>, monthly: Option<f64>) -> Self {
         self.daily_limit = daily;
         self.monthly_limit = monthly;
@@ -61,6 +185,204 @@ impl Account {
         self.priority = priority;
         self
     }
+
+    /// Check that the account may be removed: locked accounts have to be
+    /// unlocked first
+    pub fn validate_removal(&self) -> Result<(), Vec<FieldError>> {
+        if self.locked {
+            Err(vec![FieldError::new("account", "is locked; unlock it before removing it")])
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that `updated` only differs from this account where a lock
+    /// allows it; unlocked accounts accept any edit
+    pub fn validate_edit(&self, updated: &Account) -> Result<(), Vec<FieldError>> {
+        if !self.locked {
+            return Ok(());
+        }
+
+        let changed = [
+            ("label", self.label != updated.label),
+            ("api_key", self.api_key != updated.api_key),
+            ("org_id", self.org_id != updated.org_id),
+            ("model_scope", self.model_scope != updated.model_scope),
+            ("daily_limit", self.daily_limit != updated.daily_limit),
+            ("monthly_limit", self.monthly_limit != updated.monthly_limit),
+            ("priority", self.priority != updated.priority),
+            ("preferred_strategy", self.preferred_strategy != updated.preferred_strategy),
+            ("tags", self.tags != updated.tags),
+            ("cost_multiplier", self.cost_multiplier != updated.cost_multiplier),
+            ("no_upstream_auth", self.no_upstream_auth != updated.no_upstream_auth),
+            ("schedule", self.schedule != updated.schedule),
+            ("standby", self.standby != updated.standby),
+            ("tpm_limit", self.tpm_limit != updated.tpm_limit),
+            ("region", self.region != updated.region),
+            ("allowed_paths", self.allowed_paths != updated.allowed_paths),
+            ("cost_center", self.cost_center != updated.cost_center),
+            ("pinned", self.pinned != updated.pinned),
+        ];
+        let errors: Vec<FieldError> = changed
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| FieldError::new(field, "cannot change while the account is locked"))
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Disable the account automatically, recording why and when
+    pub fn auto_disable(&mut self, reason: impl Into<String>) {
+        let now = Utc::now();
+        self.enabled = false;
+        self.disabled_reason = Some(reason.into());
+        self.disabled_at = Some(now);
+        self.updated_at = now;
+    }
+
+    /// Whether `at` falls inside the account's schedule, or `None` without a
+    /// (valid) schedule
+    pub fn in_schedule(&self, at: DateTime<Utc>) -> Option<bool> {
+        let schedule: cron::Schedule = self.schedule.as_deref()?.parse().ok()?;
+        let minute = at.with_second(0)?.with_nanosecond(0)?;
+        Some(schedule.includes(minute))
+    }
+
+    /// Enable or disable the account according to its schedule at `at`.
+    ///
+    /// Accounts disabled by hand or for another reason stay disabled.
+    /// Returns whether anything changed.
+    pub fn apply_schedule(&mut self, at: DateTime<Utc>) -> bool {
+        match self.in_schedule(at) {
+            Some(false) if self.enabled => {
+                self.auto_disable(SCHEDULE_DISABLED_REASON);
+                true
+            }
+            Some(true)
+                if !self.enabled
+                    && self.disabled_reason.as_deref() == Some(SCHEDULE_DISABLED_REASON) =>
+            {
+                self.set_enabled(true);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Enable or disable the account by hand; clears any automatic reason
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.disabled_reason = None;
+        self.disabled_at = None;
+        self.updated_at = Utc::now();
+    }
+
+    /// Check the account for nonsensical values before it is persisted
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        if self.label.trim().is_empty() {
+            errors.push(FieldError::new("label", "must not be empty"));
+        }
+
+        // Keyless accounts never send their key, so any placeholder will do
+        if self.no_upstream_auth {
+            if self.api_key.trim().is_empty() {
+                errors.push(FieldError::new("api_key", "must not be empty"));
+            }
+        } else if !self.api_key.starts_with("sk-")
+            || self.api_key.len() < 20
+            || self.api_key.contains(char::is_whitespace)
+        {
+            errors.push(FieldError::new(
+                "api_key",
+                "must start with sk-, be at least 20 characters and contain no whitespace",
+            ));
+        }
+
+        if matches!(&self.org_id, Some(org) if org.trim().is_empty()) {
+            errors.push(FieldError::new("org_id", "must not be empty when set"));
+        }
+
+        if self.model_scope.iter().any(|m| m.trim().is_empty()) {
+            errors.push(FieldError::new("model_scope", "must not contain empty model names"));
+        }
+
+        if !(self.cost_multiplier.is_finite() && self.cost_multiplier > 0.0) {
+            errors.push(FieldError::new("cost_multiplier", "must be a positive number"));
+        }
+
+        if let Some(schedule) = &self.schedule {
+            if let Err(e) = schedule.parse::<cron::Schedule>() {
+                errors.push(FieldError::new(
+                    "schedule",
+                    &format!("is not a valid cron expression: {}", e),
+                ));
+            }
+        }
+
+        if self.tpm_limit == Some(0) {
+            errors.push(FieldError::new("tpm_limit", "must be at least 1 when set"));
+        }
+
+        if self.tags.iter().any(|t| t.trim().is_empty()) {
+            errors.push(FieldError::new("tags", "must not contain empty tags"));
+        }
+
+        for (field, limit) in [("daily_limit", self.daily_limit), ("monthly_limit", self.monthly_limit)] {
+            if matches!(limit, Some(l) if !l.is_finite() || l <= 0.0) {
+                errors.push(FieldError::new(field, "must be a positive amount"));
+            }
+        }
+
+        if let (Some(daily), Some(monthly)) = (self.daily_limit, self.monthly_limit) {
+            if daily > monthly {
+                errors.push(FieldError::new("daily_limit", "must not exceed monthly_limit"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A validation failure tied to a single account field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Join errors into one human-readable line
+    pub fn join(errors: &[FieldError]) -> String {
+        errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
 }
 
 /// Account status combining account config with usage data
@@ -70,6 +392,117 @@ pub struct AccountStatus {
     pub usage: UsageSnapshot,
     pub is_available: bool,
     pub disable_reason: Option<String>,
+    /// `usage` came from a stored poll rather than standing in for a missing one
+    pub usage_polled: bool,
+    /// Priority used for routing; equals `account.priority` unless auto-balance
+    /// lowers it for heavily used accounts
+    pub effective_priority: i32,
+    /// Latest `x-ratelimit-*` headers seen for the account
+    pub rate_limit: Option<RateLimitSnapshot>,
+}
+
+/// Longest upstream error message kept for an account
+const LAST_ERROR_MESSAGE_LEN: usize = 200;
+
+/// Most recent upstream failure seen for an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastError {
+    /// HTTP status, `None` when the request never got a response
+    pub status: Option<u16>,
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+impl LastError {
+    pub fn new(status: Option<u16>, message: &str) -> Self {
+        Self {
+            status,
+            message: message.trim().chars().take(LAST_ERROR_MESSAGE_LEN).collect(),
+            at: Utc::now(),
+        }
+    }
+
+    /// Build from an upstream error response, keeping only `error.message`
+    /// so nothing else from the body is retained
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("Upstream returned {}", status));
+        Self::new(Some(status), &message)
+    }
+}
+
+impl std::fmt::Display for LastError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(status) = self.status {
+            write!(f, "{} ", status)?;
+        }
+        write!(f, "{} ({})", self.message, self.at.format("%Y-%m-%d %H:%M:%S UTC"))
+    }
+}
+
+/// How long an exhausted quota is assumed to last when the response carried
+/// no `x-ratelimit-reset-*` header for it
+const RATE_LIMIT_FALLBACK_HOLD_SECS: i64 = 60;
+
+/// Remaining upstream quota as reported by `x-ratelimit-*` headers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitSnapshot {
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// When the request quota refills, from `x-ratelimit-reset-requests`
+    #[serde(default)]
+    pub requests_reset_at: Option<DateTime<Utc>>,
+    /// When the token quota refills, from `x-ratelimit-reset-tokens`
+    #[serde(default)]
+    pub tokens_reset_at: Option<DateTime<Utc>>,
+    pub observed_at: DateTime<Utc>,
+}
+
+impl RateLimitSnapshot {
+    /// Whether either quota was reported as used up
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_requests == Some(0) || self.remaining_tokens == Some(0)
+    }
+
+    /// When every exhausted quota has refilled, or `None` if none is exhausted
+    pub fn held_until(&self) -> Option<DateTime<Utc>> {
+        let fallback = self.observed_at + chrono::Duration::seconds(RATE_LIMIT_FALLBACK_HOLD_SECS);
+        let requests = (self.remaining_requests == Some(0))
+            .then(|| self.requests_reset_at.unwrap_or(fallback));
+        let tokens = (self.remaining_tokens == Some(0))
+            .then(|| self.tokens_reset_at.unwrap_or(fallback));
+        requests.max(tokens)
+    }
+
+    /// Whether an exhausted quota is still waiting to refill at `now`
+    pub fn is_held_at(&self, now: DateTime<Utc>) -> bool {
+        self.held_until().is_some_and(|until| now < until)
+    }
+}
+
+impl std::fmt::Display for RateLimitSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = |n: Option<u64>| n.map_or_else(|| "?".to_string(), |n| n.to_string());
+        write!(
+            f,
+            "{} requests, {} tokens left",
+            count(self.remaining_requests),
+            count(self.remaining_tokens)
+        )?;
+        if let Some(until) = self.held_until() {
+            write!(f, " (exhausted until {})", until.format("%H:%M:%S UTC"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Tokens and spend the proxy counted from responses since the last usage poll
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UsageDelta {
+    pub tokens: u64,
+    pub cost: f64,
 }
 
 /// Usage snapshot for an account at a point in time
@@ -83,7 +516,12 @@ pub struct UsageSnapshot {
     pub remaining_budget: Option<f64>,
     pub daily_usage: f64,
     pub monthly_usage: f64,
+    /// When the usage was polled from OpenAI
     pub timestamp: DateTime<Utc>,
+    /// When usage the proxy observed since the poll was last folded in;
+    /// unset for a snapshot straight from a poll
+    #[serde(default)]
+    pub observed_at: Option<DateTime<Utc>>,
 }
 
 impl UsageSnapshot {
@@ -98,6 +536,7 @@ impl UsageSnapshot {
             daily_usage: 0.0,
             monthly_usage: 0.0,
             timestamp: Utc::now(),
+            observed_at: None,
         }
     }
 
@@ -111,6 +550,45 @@ impl UsageSnapshot {
         0.0
     }
 
+    /// How far past its soft limit the account is, as `monthly_usage / soft_limit`;
+    /// `None` while under the soft limit or when there is none
+    pub fn soft_limit_overage(&self) -> Option<f64> {
+        let soft = self.soft_limit.filter(|s| *s > 0.0)?;
+        (self.monthly_usage >= soft).then(|| self.monthly_usage / soft)
+    }
+
+    /// When the figures were last current: the latest local observation, or the poll
+    pub fn as_of(&self) -> DateTime<Utc> {
+        self.observed_at.unwrap_or(self.timestamp)
+    }
+
+    /// This snapshot advanced by usage the proxy observed since it was taken.
+    /// The poll timestamp is kept, so staleness still reflects the last poll.
+    /// Daily and monthly spend restart when the day or month has changed in between.
+    pub fn with_delta(&self, delta: UsageDelta) -> Self {
+        use chrono::Datelike;
+
+        let now = Utc::now();
+        let as_of = self.as_of();
+        let same_day = as_of.date_naive() == now.date_naive();
+        let same_month = same_day || (as_of.year(), as_of.month()) == (now.year(), now.month());
+
+        Self {
+            tokens_used: self.tokens_used + delta.tokens,
+            cost_estimate: self.cost_estimate + delta.cost,
+            remaining_budget: self.remaining_budget.map(|r| r - delta.cost),
+            daily_usage: if same_day { self.daily_usage } else { 0.0 } + delta.cost,
+            monthly_usage: if same_month { self.monthly_usage } else { 0.0 } + delta.cost,
+            observed_at: Some(now),
+            ..self.clone()
+        }
+    }
+
+    /// Check if the snapshot is older than `max_age`
+    pub fn is_stale(&self, max_age: chrono::Duration) -> bool {
+        Utc::now() - self.timestamp > max_age
+    }
+
     /// Check if account is over its limits
     pub fn is_over_limit(&self, account: &Account) -> bool {
         if let Some(daily) = account.daily_limit {
@@ -132,13 +610,46 @@ impl UsageSnapshot {
     }
 }
 
+/// What an account's key was last found able to do, from probing the API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountCapabilities {
+    pub account_id: AccountId,
+    pub valid: bool,
+    /// Organization the API reported for the key
+    pub org_id: Option<String>,
+    /// Models the key can list; empty when listing isn't permitted
+    pub available_models: Vec<String>,
+    /// Whether the key can read organization billing usage
+    pub supports_usage_api: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl AccountCapabilities {
+    /// Check if the probe is recent enough to reuse
+    pub fn is_fresh(&self, ttl: chrono::Duration) -> bool {
+        Utc::now() - self.checked_at < ttl
+    }
+
+    /// Whether the probed model list rules `model` out. An unknown (empty)
+    /// list never does, since restricted keys can serve models they can't list.
+    pub fn excludes_model(&self, model: &str) -> bool {
+        !self.available_models.is_empty() && !self.available_models.iter().any(|m| m == model)
+    }
+}
+
 /// Request context passed to routing engine
 #[derive(Debug, Clone)]
 pub struct RequestContext {
     pub model: String,
     pub estimated_tokens: Option<u64>,
     pub session_id: Option<String>,
-    pub priority: Option<i32>,
+    /// Capability the target endpoint needs, if any
+    pub capability: Option<ModelCapability>,
+    /// Endpoint path the request is for, checked against `allowed_paths`
+    pub path: Option<String>,
+    /// Worst-case cost at base rates and the most the request may cost; accounts
+    /// whose `cost_multiplier` pushes it past the ceiling are skipped
+    pub cost_ceiling: Option<(f64, f64)>,
 }
 
 impl RequestContext {
@@ -147,29 +658,486 @@ impl RequestContext {
             model,
             estimated_tokens: None,
             session_id: None,
-            priority: None,
+            capability: None,
+            path: None,
+            cost_ceiling: None,
         }
     }
 
+    pub fn with_cost_ceiling(mut self, estimated_cost: f64, ceiling: f64) -> Self {
+        self.cost_ceiling = Some((estimated_cost, ceiling));
+        self
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn with_capability(mut self, capability: Option<ModelCapability>) -> Self {
+        self.capability = capability;
+        self
+    }
+
     pub fn with_session(mut self, session_id: String) -> Self {
         self.session_id = Some(session_id);
         self
     }
+
+    pub fn with_estimated_tokens(mut self, tokens: u64) -> Self {
+        self.estimated_tokens = Some(tokens);
+        self
+    }
+}
+
+/// What kind of endpoint a model serves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelCapability {
+    Chat,
+    Embedding,
+    Image,
+}
+
+impl ModelCapability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelCapability::Chat => "chat",
+            ModelCapability::Embedding => "embedding",
+            ModelCapability::Image => "image",
+        }
+    }
+
+    /// Capability required by an OpenAI API path, if it is model specific
+    pub fn for_path(path: &str) -> Option<Self> {
+        match path {
+            "/v1/chat/completions" | "/v1/completions" => Some(ModelCapability::Chat),
+            "/v1/embeddings" => Some(ModelCapability::Embedding),
+            p if p.starts_with("/v1/images/") => Some(ModelCapability::Image),
+            _ => None,
+        }
+    }
+}
+
+/// A model known to the proxy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub capability: ModelCapability,
+}
+
+/// Built-in model catalog; the first entry of each capability is its default
+pub const CATALOG: &[(&str, ModelCapability)] = &[
+    ("gpt-4o", ModelCapability::Chat),
+    ("gpt-4o-mini", ModelCapability::Chat),
+    ("gpt-4-turbo", ModelCapability::Chat),
+    ("gpt-4", ModelCapability::Chat),
+    ("gpt-3.5-turbo", ModelCapability::Chat),
+    ("text-embedding-3-small", ModelCapability::Embedding),
+    ("text-embedding-3-large", ModelCapability::Embedding),
+    ("text-embedding-ada-002", ModelCapability::Embedding),
+    ("dall-e-3", ModelCapability::Image),
+    ("dall-e-2", ModelCapability::Image),
+];
+
+/// Known models used to expand wildcard scopes and pick endpoint defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelCatalog {
+    models: Vec<ModelInfo>,
+}
+
+impl Default for ModelCatalog {
+    fn default() -> Self {
+        Self {
+            models: CATALOG
+                .iter()
+                .map(|(id, capability)| ModelInfo {
+                    id: id.to_string(),
+                    capability: *capability,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ModelCatalog {
+    /// Built-in catalog with `overrides` replacing entries of the same id
+    /// and appending new ones
+    pub fn with_overrides(overrides: &[ModelInfo]) -> Self {
+        let mut catalog = Self::default();
+        for model in overrides {
+            match catalog.models.iter_mut().find(|m| m.id == model.id) {
+                Some(existing) => existing.capability = model.capability,
+                None => catalog.models.push(model.clone()),
+            }
+        }
+        catalog
+    }
+
+    /// All models, in catalog order
+    pub fn models(&self) -> &[ModelInfo] {
+        &self.models
+    }
+
+    /// Capability of a known model
+    pub fn capability_of(&self, model: &str) -> Option<ModelCapability> {
+        self.models.iter().find(|m| m.id == model).map(|m| m.capability)
+    }
+
+    /// Models matched by a scope entry (`gpt-4*` wildcard or exact id)
+    pub fn expand(&self, pattern: &str) -> Vec<&ModelInfo> {
+        self.models
+            .iter()
+            .filter(|m| match pattern.strip_suffix('*') {
+                Some(prefix) => m.id.starts_with(prefix),
+                None => m.id == pattern,
+            })
+            .collect()
+    }
+
+    /// Default model for requests that don't name one
+    pub fn default_model(&self, capability: ModelCapability) -> Option<&str> {
+        self.models
+            .iter()
+            .find(|m| m.capability == capability)
+            .map(|m| m.id.as_str())
+    }
+}
+
+/// Accounts as exported by the desktop app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountExport {
+    pub version: String,
+    pub exported_at: DateTime<Utc>,
+    pub accounts: Vec<Account>,
+}
+
+/// Fields that change on every use and say nothing about configuration
+const VOLATILE_ACCOUNT_FIELDS: [&str; 3] = ["updated_at", "last_used", "disabled_at"];
+
+/// One account present in both exports with different settings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountChange {
+    pub id: AccountId,
+    pub label: String,
+    /// Names of the changed fields, `api_key` included when the key changed
+    pub fields: Vec<String>,
+}
+
+impl AccountChange {
+    pub fn key_changed(&self) -> bool {
+        self.fields.iter().any(|f| f == "api_key")
+    }
+}
+
+/// Differences between two exports; never carries api_keys
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExportDiff {
+    /// `(id, label)` of accounts only in the newer export
+    pub added: Vec<(AccountId, String)>,
+    /// `(id, label)` of accounts only in the older export
+    pub removed: Vec<(AccountId, String)>,
+    pub changed: Vec<AccountChange>,
+}
+
+impl ExportDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl AccountExport {
+    /// Compare against a newer export.
+    ///
+    /// Accounts are matched by ID, then by label, since importing assigns
+    /// fresh IDs.
+    pub fn diff(&self, newer: &AccountExport) -> ExportDiff {
+        let mut diff = ExportDiff::default();
+        let mut unmatched: Vec<&Account> = newer.accounts.iter().collect();
+
+        for old in &self.accounts {
+            let pos = unmatched
+                .iter()
+                .position(|new| new.id == old.id)
+                .or_else(|| unmatched.iter().position(|new| new.label == old.label));
+            let Some(pos) = pos else {
+                diff.removed.push((old.id, old.label.clone()));
+                continue;
+            };
+
+            let new = unmatched.remove(pos);
+            let fields = changed_fields(old, new);
+            if !fields.is_empty() {
+                diff.changed.push(AccountChange { id: new.id, label: new.label.clone(), fields });
+            }
+        }
+
+        diff.added = unmatched.iter().map(|a| (a.id, a.label.clone())).collect();
+        diff
+    }
+}
+
+/// Names of the settings that differ between two versions of an account
+fn changed_fields(old: &Account, new: &Account) -> Vec<String> {
+    let as_map = |account: &Account| match serde_json::to_value(account) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (old, new) = (as_map(old), as_map(new));
+
+    old.keys()
+        .chain(new.keys().filter(|k| !old.contains_key(*k)))
+        .filter(|k| !VOLATILE_ACCOUNT_FIELDS.contains(&k.as_str()) && k.as_str() != "id")
+        .filter(|k| old.get(*k) != new.get(*k))
+        .cloned()
+        .collect()
+}
+
+/// Columns of an account CSV, in order; only `label` and `api_key` are required
+pub const ACCOUNT_CSV_COLUMNS: [&str; 7] = [
+    "label",
+    "api_key",
+    "org_id",
+    "priority",
+    "daily_limit",
+    "monthly_limit",
+    "model_scope",
+];
+
+/// A CSV row that couldn't be turned into an account
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvRowError {
+    /// 1-based line number in the file
+    pub line: usize,
+    pub message: String,
+}
+
+/// Accounts parsed from a CSV file, and the rows that were skipped.
+///
+/// A header row naming the columns is optional. `model_scope` holds model
+/// names separated by `;`, and empty cells leave a setting unset.
+#[derive(Debug, Default)]
+pub struct CsvImport {
+    pub accounts: Vec<Account>,
+    pub errors: Vec<CsvRowError>,
+}
+
+impl CsvImport {
+    pub fn parse(input: &str) -> Self {
+        let mut import = CsvImport::default();
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let result = split_csv_line(line).and_then(|fields| {
+                if import.accounts.is_empty()
+                    && import.errors.is_empty()
+                    && fields[0].trim().eq_ignore_ascii_case(ACCOUNT_CSV_COLUMNS[0])
+                {
+                    return Ok(None);
+                }
+                account_from_csv_fields(&fields).map(Some)
+            });
+
+            match result {
+                Ok(Some(account)) => import.accounts.push(account),
+                Ok(None) => {}
+                Err(message) => import.errors.push(CsvRowError { line: line_number, message }),
+            }
+        }
+
+        import
+    }
+}
+
+/// Spend and requests of the accounts charged to one cost center
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostCenterTotal {
+    /// `None` collects accounts without a cost center
+    pub cost_center: Option<String>,
+    pub accounts: usize,
+    /// Dollars spent over the report's range
+    pub spend: f64,
+    pub requests: u64,
+}
+
+/// Usage attributed to cost centers over a range of UTC days, for chargeback
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostCenterReport {
+    pub start: chrono::NaiveDate,
+    pub end: chrono::NaiveDate,
+    /// Ordered by cost center, unassigned accounts first
+    pub centers: Vec<CostCenterTotal>,
+}
+
+impl CostCenterReport {
+    /// Group per-account `(account, spend, requests)` totals by cost center
+    pub fn from_accounts<'a>(
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+        totals: impl IntoIterator<Item = (&'a Account, f64, u64)>,
+    ) -> Self {
+        let mut centers = std::collections::BTreeMap::<Option<String>, CostCenterTotal>::new();
+        for (account, spend, requests) in totals {
+            let center = centers
+                .entry(account.cost_center.clone())
+                .or_insert_with(|| CostCenterTotal {
+                    cost_center: account.cost_center.clone(),
+                    accounts: 0,
+                    spend: 0.0,
+                    requests: 0,
+                });
+            center.accounts += 1;
+            center.spend += spend;
+            center.requests += requests;
+        }
+
+        Self { start, end, centers: centers.into_values().collect() }
+    }
+
+    /// The report as CSV with a header row; unassigned accounts have an empty
+    /// cost center
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("cost_center,accounts,spend,requests\n");
+        for center in &self.centers {
+            csv.push_str(&format!(
+                "{},{},{:.2},{}\n",
+                csv_field(center.cost_center.as_deref().unwrap_or("")),
+                center.accounts,
+                center.spend,
+                center.requests
+            ));
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field when it holds a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Split one CSV line into fields, honoring double-quoted fields with `""` escapes
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+
+    Ok(fields)
 }
 
-/// Account filtering criteria for routing
-#[derive(Debug, Clone, Default)]
-pub struct AccountFilter {
-    pub enabled_only: bool,
-    pub under_limit_only: bool,
-    pub supports_model: Option<String>,
-    pub min_priority: Option<i32>,
+/// Build and validate an account from the fields of one CSV row
+fn account_from_csv_fields(fields: &[String]) -> Result<Account, String> {
+    if fields.len() < 2 || fields.len() > ACCOUNT_CSV_COLUMNS.len() {
+        return Err(format!(
+            "expected 2 to {} columns, found {}",
+            ACCOUNT_CSV_COLUMNS.len(),
+            fields.len()
+        ));
+    }
+
+    let cell = |index: usize| {
+        fields
+            .get(index)
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+    };
+    let number = |index: usize| -> Result<Option<f64>, String> {
+        cell(index)
+            .map(|v| {
+                v.parse::<f64>()
+                    .map_err(|_| format!("{} is not a number: {}", ACCOUNT_CSV_COLUMNS[index], v))
+            })
+            .transpose()
+    };
+
+    let mut account = Account::new(
+        cell(0).unwrap_or_default().to_string(),
+        cell(1).unwrap_or_default().to_string(),
+    );
+    if let Some(org_id) = cell(2) {
+        account = account.with_org_id(org_id.to_string());
+    }
+    if let Some(priority) = cell(3) {
+        let priority = priority
+            .parse()
+            .map_err(|_| format!("priority is not an integer: {}", priority))?;
+        account = account.with_priority(priority);
+    }
+    account = account.with_limits(number(4)?, number(5)?);
+    if let Some(scope) = cell(6) {
+        account = account.with_model_scope(scope.split(';').map(|m| m.trim().to_string()).collect());
+    }
+
+    account.validate().map_err(|errors| FieldError::join(&errors))?;
+    Ok(account)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_schedule_toggles_account_in_and_out_of_window() {
+        use chrono::TimeZone;
+
+        let mut account = Account::new("Office".to_string(), "sk-office-0123456789abcdef".to_string())
+            .with_schedule(Some("0 * 9-16 * * Mon-Fri".to_string()));
+        assert!(account.validate().is_ok());
+
+        // Monday 2024-01-15
+        let in_window = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 45).unwrap();
+        let evening = Utc.with_ymd_and_hms(2024, 1, 15, 19, 0, 0).unwrap();
+        let saturday = Utc.with_ymd_and_hms(2024, 1, 20, 10, 30, 0).unwrap();
+        assert_eq!(account.in_schedule(in_window), Some(true));
+        assert_eq!(account.in_schedule(evening), Some(false));
+        assert_eq!(account.in_schedule(saturday), Some(false));
+
+        assert!(!account.apply_schedule(in_window));
+        assert!(account.enabled);
+
+        assert!(account.apply_schedule(evening));
+        assert!(!account.enabled);
+        assert_eq!(account.disabled_reason.as_deref(), Some(SCHEDULE_DISABLED_REASON));
+
+        assert!(account.apply_schedule(in_window));
+        assert!(account.enabled);
+        assert_eq!(account.disabled_reason, None);
+
+        // Accounts disabled by hand stay disabled inside the window
+        account.set_enabled(false);
+        assert!(!account.apply_schedule(in_window));
+        assert!(!account.enabled);
+
+        let invalid = account.with_schedule(Some("whenever".to_string()));
+        assert!(invalid.validate().unwrap_err().iter().any(|e| e.field == "schedule"));
+    }
+
     #[test]
     fn test_account_creation() {
         let account = Account::new("Test Account".to_string(), "sk-test123".to_string());
@@ -189,6 +1157,15 @@ mod tests {
         assert_eq!(usage.utilization_ratio(), 0.75);
     }
 
+    #[test]
+    fn test_usage_staleness() {
+        let mut usage = UsageSnapshot::new(Uuid::new_v4());
+        assert!(!usage.is_stale(chrono::Duration::hours(1)));
+
+        usage.timestamp = Utc::now() - chrono::Duration::days(2);
+        assert!(usage.is_stale(chrono::Duration::hours(1)));
+    }
+
     #[test]
     fn test_usage_over_limit() {
         let account = Account::new("Test".to_string(), "sk-test".to_string())
@@ -199,4 +1176,219 @@ mod tests {
 
         assert!(usage.is_over_limit(&account));
     }
+
+    fn valid_account() -> Account {
+        Account::new("Valid".to_string(), "sk-valid-key-0123456789".to_string())
+            .with_limits(Some(10.0), Some(100.0))
+    }
+
+    fn invalid_fields(account: &Account) -> Vec<String> {
+        account
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_account_passes_validation() {
+        assert_eq!(valid_account().validate(), Ok(()));
+        assert_eq!(
+            valid_account().with_limits(None, None).with_org_id("org-1".to_string()).validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_account_validation_reports_each_field() {
+        let mut account = valid_account();
+        account.label = "  ".to_string();
+        assert_eq!(invalid_fields(&account), vec!["label"]);
+
+        for key in ["", "pk-valid-key-0123456789", "sk-short", "sk-valid key-0123456789"] {
+            let mut account = valid_account();
+            account.api_key = key.to_string();
+            assert_eq!(invalid_fields(&account), vec!["api_key"], "key {:?}", key);
+        }
+
+        let account = valid_account().with_org_id(String::new());
+        assert_eq!(invalid_fields(&account), vec!["org_id"]);
+
+        let account = valid_account().with_model_scope(vec!["gpt-4".to_string(), "".to_string()]);
+        assert_eq!(invalid_fields(&account), vec!["model_scope"]);
+
+        let account = valid_account().with_limits(Some(-1.0), None);
+        assert_eq!(invalid_fields(&account), vec!["daily_limit"]);
+
+        let account = valid_account().with_limits(None, Some(f64::NAN));
+        assert_eq!(invalid_fields(&account), vec!["monthly_limit"]);
+
+        let account = valid_account().with_limits(Some(50.0), Some(20.0));
+        assert_eq!(invalid_fields(&account), vec!["daily_limit"]);
+    }
+
+    #[test]
+    fn test_field_errors_join_for_display() {
+        let mut account = valid_account();
+        account.label = String::new();
+        account.api_key = "bad".to_string();
+
+        let message = FieldError::join(&account.validate().unwrap_err());
+        assert!(message.starts_with("label must not be empty; api_key "));
+    }
+
+    #[test]
+    fn test_locked_account_rejects_key_changes() {
+        let mut original = valid_account();
+        let mut rotated = original.clone();
+        rotated.api_key = "sk-rotated-key-0123456789".to_string();
+        assert!(original.validate_edit(&rotated).is_ok());
+
+        original.locked = true;
+        let errors = original.validate_edit(&rotated).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "api_key");
+
+        let mut disabled = original.clone();
+        disabled.enabled = false;
+        assert!(original.validate_edit(&disabled).is_ok());
+
+        // Routing settings are locked along with the key
+        let mut retuned = original.clone();
+        retuned.tags = vec!["batch".to_string()];
+        retuned.standby = true;
+        retuned.tpm_limit = Some(1000);
+        retuned.allowed_paths = vec!["/v1/embeddings".to_string()];
+        let fields: Vec<String> = original
+            .validate_edit(&retuned)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["tags", "standby", "tpm_limit", "allowed_paths"]);
+    }
+
+    #[test]
+    fn test_locked_account_cannot_be_removed() {
+        let mut account = valid_account();
+        assert!(account.validate_removal().is_ok());
+
+        account.locked = true;
+        let errors = account.validate_removal().unwrap_err();
+        assert_eq!(errors[0].field, "account");
+    }
+
+    #[test]
+    fn test_catalog_wildcard_expansion() {
+        let catalog = ModelCatalog::default();
+        let ids = |pattern: &str| -> Vec<String> {
+            catalog.expand(pattern).iter().map(|m| m.id.clone()).collect()
+        };
+
+        assert_eq!(ids("gpt-4o*"), vec!["gpt-4o", "gpt-4o-mini"]);
+        assert_eq!(ids("text-embedding-3*"), vec!["text-embedding-3-small", "text-embedding-3-large"]);
+        assert_eq!(ids("dall-e-3"), vec!["dall-e-3"]);
+        assert!(ids("o1*").is_empty());
+
+        assert_eq!(catalog.default_model(ModelCapability::Chat), Some("gpt-4o"));
+        assert_eq!(catalog.default_model(ModelCapability::Embedding), Some("text-embedding-3-small"));
+        assert_eq!(ModelCapability::for_path("/v1/images/generations"), Some(ModelCapability::Image));
+        assert_eq!(ModelCapability::for_path("/v1/files"), None);
+    }
+
+    #[test]
+    fn test_catalog_overrides() {
+        let catalog = ModelCatalog::with_overrides(&[
+            ModelInfo { id: "o1-mini".to_string(), capability: ModelCapability::Chat },
+            ModelInfo { id: "gpt-4o".to_string(), capability: ModelCapability::Image },
+        ]);
+
+        assert_eq!(catalog.capability_of("o1-mini"), Some(ModelCapability::Chat));
+        assert_eq!(catalog.capability_of("gpt-4o"), Some(ModelCapability::Image));
+        assert_eq!(catalog.expand("o1*").len(), 1);
+        assert_eq!(catalog.models().len(), CATALOG.len() + 1);
+    }
+
+    #[test]
+    fn test_export_diff_reports_added_removed_and_changed() {
+        let export = |accounts: Vec<Account>| AccountExport {
+            version: "1".to_string(),
+            exported_at: Utc::now(),
+            accounts,
+        };
+
+        let kept = Account::new("Kept".to_string(), "sk-kept".to_string());
+        let rekeyed = Account::new("Rekeyed".to_string(), "sk-old".to_string());
+        let removed = Account::new("Removed".to_string(), "sk-removed".to_string());
+        let old = export(vec![kept.clone(), rekeyed.clone(), removed.clone()]);
+
+        let mut kept_touched = kept.clone();
+        kept_touched.last_used = Some(Utc::now());
+        let mut rekeyed_new = rekeyed.clone();
+        rekeyed_new.api_key = "sk-new".to_string();
+        rekeyed_new.priority = 5;
+        // Re-imported under a fresh ID, still matched by label
+        rekeyed_new.id = Uuid::new_v4();
+        let added = Account::new("Added".to_string(), "sk-added".to_string());
+        let new = export(vec![added.clone(), kept_touched, rekeyed_new.clone()]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![(added.id, "Added".to_string())]);
+        assert_eq!(diff.removed, vec![(removed.id, "Removed".to_string())]);
+        assert_eq!(diff.changed.len(), 1);
+        let change = &diff.changed[0];
+        assert_eq!(change.id, rekeyed_new.id);
+        assert!(change.key_changed());
+        assert!(change.fields.contains(&"priority".to_string()));
+
+        // Nothing in the report reveals a key
+        let rendered = serde_json::to_string(&diff).unwrap();
+        assert!(!rendered.contains("sk-new") && !rendered.contains("sk-old"));
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_csv_import_parses_well_formed_file() {
+        let csv = "\
+label,api_key,org_id,priority,daily_limit,monthly_limit,model_scope
+Team A,sk-team-a-0123456789abcdef,org-a,5,10,100,gpt-4;gpt-4o
+\"Team B, EU\",sk-team-b-0123456789abcdef,,,,,
+";
+        let import = CsvImport::parse(csv);
+        assert!(import.errors.is_empty(), "{:?}", import.errors);
+        assert_eq!(import.accounts.len(), 2);
+
+        let a = &import.accounts[0];
+        assert_eq!(a.label, "Team A");
+        assert_eq!(a.org_id.as_deref(), Some("org-a"));
+        assert_eq!(a.priority, 5);
+        assert_eq!((a.daily_limit, a.monthly_limit), (Some(10.0), Some(100.0)));
+        assert_eq!(a.model_scope, vec!["gpt-4".to_string(), "gpt-4o".to_string()]);
+
+        let b = &import.accounts[1];
+        assert_eq!(b.label, "Team B, EU");
+        assert_eq!(b.org_id, None);
+        assert!(b.model_scope.is_empty());
+    }
+
+    #[test]
+    fn test_csv_import_reports_bad_rows() {
+        let csv = "\
+Good,sk-good-0123456789abcdef
+Bad key,not-a-key
+Bad priority,sk-other-0123456789abcdef,,high
+
+Also good,sk-also-good-0123456789ab,org-x
+";
+        let import = CsvImport::parse(csv);
+        let labels: Vec<&str> = import.accounts.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, vec!["Good", "Also good"]);
+
+        let lines: Vec<usize> = import.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+        assert!(import.errors[0].message.starts_with("api_key must start with sk-"));
+        assert_eq!(import.errors[1].message, "priority is not an integer: high");
+    }
 }
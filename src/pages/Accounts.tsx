@@ -1,5 +1,6 @@
-import { useState, useEffect, useMemo } from 'react';
-import { Search, RefreshCw, Download, Trash2, LayoutGrid, List, ToggleLeft, ToggleRight, Eye, EyeOff } from 'lucide-react';
+import { useState, useEffect, useMemo, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Search, RefreshCw, Download, Upload, Trash2, LayoutGrid, List, ToggleLeft, ToggleRight, Eye, EyeOff } from 'lucide-react';
 import { useAccountStore } from '../stores/useAccountStore';
 import AccountCard from '../components/accounts/AccountCard';
 import AccountDetailsDialog from '../components/accounts/AccountDetailsDialog';
@@ -39,6 +40,8 @@ export default function Accounts() {
     const [currentPage, setCurrentPage] = useState(1);
     const [itemsPerPage, setItemsPerPage] = useState(12);
     const [switchingId, setSwitchingId] = useState<string | null>(null);
+    const [plaintextExport, setPlaintextExport] = useState<{ accountId?: string } | null>(null);
+    const importInput = useRef<HTMLInputElement>(null);
 
     useEffect(() => {
         fetchAccounts();
@@ -206,7 +209,71 @@ export default function Accounts() {
         }
     };
 
-    const handleExport = () => {
+    const downloadFile = (data: string, type: string, filename: string) => {
+        const blob = new Blob([data], { type });
+        const url = URL.createObjectURL(blob);
+        const link = document.createElement('a');
+        link.href = url;
+        link.download = filename;
+        link.click();
+        URL.revokeObjectURL(url);
+    };
+
+    // Encrypted unless the passphrase is left empty; `accountId` exports just that account,
+    // otherwise the selection (or every account when nothing is selected)
+    const promptExport = async (accountId?: string) => {
+        const passphrase = window.prompt('Passphrase to encrypt the export (leave empty for a plaintext export):');
+        if (passphrase === null) return;
+        if (passphrase === '') {
+            setPlaintextExport({ accountId });
+            return;
+        }
+
+        const date = new Date().toISOString().split('T')[0];
+        const account = accountId ? accounts.find(a => a.id === accountId) : undefined;
+        if (accountId && !account) return;
+        const ids = accountId ? [accountId] : selectedIds.size > 0 ? Array.from(selectedIds) : undefined;
+        const filename = account ? `account-${account.name}-${date}.cam` : `codex-accounts-${date}.cam`;
+
+        try {
+            const data = await invoke<string>('export_accounts_encrypted', { passphrase, ids });
+            downloadFile(data, 'application/octet-stream', filename);
+            showToast(account ? 'Account exported (encrypted)' : 'Accounts exported (encrypted)', 'success');
+        } catch (error) {
+            showToast(`Failed to export: ${error}`, 'error');
+        }
+    };
+
+    const handleExport = () => promptExport();
+
+    const handleImportFile = async (event: React.ChangeEvent<HTMLInputElement>) => {
+        const file = event.target.files?.[0];
+        event.target.value = '';
+        if (!file) return;
+
+        const passphrase = window.prompt(`Passphrase for ${file.name}:`);
+        if (!passphrase) return;
+
+        try {
+            const data = (await file.text()).trim();
+            const imported = await invoke<unknown[]>('import_accounts_encrypted', { data, passphrase });
+            showToast(`Imported ${imported.length} accounts`, 'success');
+            await fetchAccounts();
+        } catch (error) {
+            showToast(`Failed to import: ${error}`, 'error');
+        }
+    };
+
+    const executePlaintextExport = () => {
+        if (plaintextExport?.accountId) {
+            exportOnePlaintext(plaintextExport.accountId);
+        } else {
+            exportPlaintext();
+        }
+        setPlaintextExport(null);
+    };
+
+    const exportPlaintext = () => {
         const idsToExport = selectedIds.size > 0 ? Array.from(selectedIds) : accounts.map(a => a.id);
         const accountsToExport = accounts.filter(a => idsToExport.includes(a.id));
         const data = JSON.stringify(accountsToExport.map(a => ({
@@ -216,17 +283,13 @@ export default function Accounts() {
             organization: a.organization,
         })), null, 2);
         
-        const blob = new Blob([data], { type: 'application/json' });
-        const url = URL.createObjectURL(blob);
-        const link = document.createElement('a');
-        link.href = url;
-        link.download = `codex-accounts-${new Date().toISOString().split('T')[0]}.json`;
-        link.click();
-        URL.revokeObjectURL(url);
+        downloadFile(data, 'application/json', `codex-accounts-${new Date().toISOString().split('T')[0]}.json`);
         showToast('Accounts exported', 'success');
     };
 
-    const handleExportOne = (accountId: string) => {
+    const handleExportOne = (accountId: string) => promptExport(accountId);
+
+    const exportOnePlaintext = (accountId: string) => {
         const account = accounts.find(a => a.id === accountId);
         if (!account) return;
         const data = JSON.stringify([{
@@ -236,13 +299,7 @@ export default function Accounts() {
             organization: account.organization,
         }], null, 2);
         
-        const blob = new Blob([data], { type: 'application/json' });
-        const url = URL.createObjectURL(blob);
-        const link = document.createElement('a');
-        link.href = url;
-        link.download = `account-${account.name}-${new Date().toISOString().split('T')[0]}.json`;
-        link.click();
-        URL.revokeObjectURL(url);
+        downloadFile(data, 'application/json', `account-${account.name}-${new Date().toISOString().split('T')[0]}.json`);
     };
 
     return (
@@ -374,6 +431,21 @@ export default function Accounts() {
                         <Download className="w-3.5 h-3.5" />
                         Export
                     </button>
+
+                    <button
+                        className="px-3 py-2 border border-gray-200 dark:border-gray-800 text-gray-700 dark:text-gray-300 text-xs font-medium rounded-xl hover:bg-gray-50 dark:hover:bg-gray-800 transition-colors flex items-center gap-1.5"
+                        onClick={() => importInput.current?.click()}
+                    >
+                        <Upload className="w-3.5 h-3.5" />
+                        Import
+                    </button>
+                    <input
+                        ref={importInput}
+                        type="file"
+                        accept=".cam"
+                        className="hidden"
+                        onChange={handleImportFile}
+                    />
                 </div>
             </div>
 
@@ -524,6 +596,17 @@ export default function Accounts() {
                 onCancel={() => { setDeleteConfirmId(null); setIsBatchDelete(false); }}
             />
 
+            <ModalDialog
+                isOpen={!!plaintextExport}
+                title="Export Without Encryption"
+                message="The exported file will contain your API keys in plaintext. Anyone who can read the file can use these accounts. Continue?"
+                type="confirm"
+                confirmText="Export Plaintext"
+                isDestructive={true}
+                onConfirm={executePlaintextExport}
+                onCancel={() => setPlaintextExport(null)}
+            />
+
             {toggleConfirm && (
                 <ModalDialog
                     isOpen={!!toggleConfirm}
@@ -1,16 +1,30 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use dashmap::DashMap;
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{debug, instrument, trace, warn};
+use tracing::{debug, info, instrument, trace, warn};
 
-use crate::models::{Account, AccountFilter, AccountStatus, RequestContext, UsageSnapshot};
+use crate::config::{
+    ExperimentConfig, LargeRequestCooldown, SizeRule, SoftLimitPolicy, StaleUsagePolicy,
+    StickyScope,
+};
+use crate::models::{
+    Account, AccountCapabilities, AccountId, AccountStatus, LastError, ModelCapability, ModelCatalog,
+    ModelInfo, RateLimitSnapshot, RequestContext, UsageDelta, UsageSnapshot,
+};
 
 /// Routing strategy for selecting accounts
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RoutingStrategy {
     /// Prefer accounts with lowest utilization ratio
+    #[default]
     LeastUtilized,
     /// Round-robin through available accounts
     RoundRobin,
@@ -18,12 +32,8 @@ pub enum RoutingStrategy {
     Priority,
     /// Sticky sessions - route same content to same account
     Sticky,
-}
-
-impl Default for RoutingStrategy {
-    fn default() -> Self {
-        RoutingStrategy::LeastUtilized
-    }
+    /// Prefer accounts with the lowest cost multiplier, then lowest utilization
+    CheapestFirst,
 }
 
 /// Routing decision with metadata
@@ -36,16 +46,240 @@ pub struct RoutingDecision {
     pub reason: RoutingReason,
     pub utilization_ratio: f64,
     pub remaining_budget: Option<f64>,
+    /// The account's price factor, to apply to estimated and recorded costs
+    pub cost_multiplier: f64,
+    /// Omit the Authorization header upstream
+    pub no_upstream_auth: bool,
+    /// This request's estimate in the account's TPM window, if it has a limit
+    pub token_charge: Option<TokenCharge>,
 }
 
-#[derive(Debug, Clone)]
-pub enum RoutingReason {
+/// Why a request went to its account: the strategy in effect, how the
+/// account was picked, and the rules layered on top of that
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoutingReason {
+    /// Engine (or failover) strategy before any per-account override
+    pub strategy: RoutingStrategy,
+    pub selection: SelectionReason,
+    /// Rules that narrowed the candidates or overrode the strategy
+    pub layers: Vec<RoutingLayer>,
+}
+
+/// How the selected account was picked among the candidates
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SelectionReason {
     LeastUtilized,
-    RoundRobin { index: usize },
+    /// `index` is unset when the rotation was busy while the reason was built
+    RoundRobin { index: Option<usize> },
     Priority { priority: i32 },
     Sticky { session_id: String },
+    CheapestFirst { cost_multiplier: f64 },
     Fallback,
-    ErrorRecovery,
+    Experiment { percent: f64 },
+}
+
+/// A rule applied on top of the routing strategy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RoutingLayer {
+    /// Candidates were narrowed to a size rule's accounts
+    SizeRule { min_tokens: Option<u64>, max_tokens: Option<u64> },
+    /// No primary account was left, so a standby account served
+    Standby,
+    /// The session was pinned to an account preferring `Sticky`, overriding the strategy
+    StickyPreference,
+    /// A retry after `failed` accounts errored for this request
+    Failover { failed: usize },
+}
+
+impl std::fmt::Display for RoutingReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.selection {
+            SelectionReason::LeastUtilized => write!(f, "least utilized")?,
+            SelectionReason::RoundRobin { index: Some(index) } => {
+                write!(f, "round robin #{}", index)?
+            }
+            SelectionReason::RoundRobin { index: None } => write!(f, "round robin #unknown")?,
+            SelectionReason::Priority { priority } => write!(f, "priority {}", priority)?,
+            SelectionReason::Sticky { session_id } => write!(f, "sticky session {}", session_id)?,
+            SelectionReason::CheapestFirst { cost_multiplier } => {
+                write!(f, "cheapest (x{})", cost_multiplier)?
+            }
+            SelectionReason::Fallback => write!(f, "fallback")?,
+            SelectionReason::Experiment { percent } => write!(f, "experiment ({}%)", percent)?,
+        }
+
+        for layer in &self.layers {
+            match layer {
+                RoutingLayer::SizeRule { min_tokens, max_tokens } => write!(
+                    f,
+                    " + size rule {}..{}",
+                    min_tokens.map(|t| t.to_string()).unwrap_or_default(),
+                    max_tokens.map(|t| t.to_string()).unwrap_or_default()
+                )?,
+                RoutingLayer::Standby => write!(f, " + standby")?,
+                RoutingLayer::StickyPreference => write!(f, " + sticky preference")?,
+                RoutingLayer::Failover { failed } => write!(f, " + failover after {}", failed)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a request would be routed where it is, without routing it
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteTrace {
+    pub model: String,
+    pub strategy: RoutingStrategy,
+    /// Eligible accounts in the order they would be tried, with the
+    /// strategy-specific score from `rank_candidates`
+    pub candidates: Vec<TracedCandidate>,
+    pub excluded: Vec<TracedExclusion>,
+    pub chosen: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TracedCandidate {
+    pub account_id: AccountId,
+    pub label: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TracedExclusion {
+    pub account_id: AccountId,
+    pub label: String,
+    pub reason: String,
+}
+
+/// A sticky session pin, as listed by `/admin/sessions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// Shortest leading part of the session hash that no other session
+    /// shares, so it always revokes exactly this session
+    pub session_prefix: String,
+    /// Model family the pin applies to, under family-scoped stickiness
+    pub family: Option<String>,
+    pub account_id: AccountId,
+    pub account_label: String,
+    pub idle_seconds: u64,
+}
+
+/// Fewest characters of a session hash shown when listing sessions
+const SESSION_PREFIX_LEN: usize = 8;
+
+/// Shortest prefix of `id`, at least `SESSION_PREFIX_LEN` characters, that
+/// no other id in `ids` starts with; the whole id when none is
+fn unique_prefix<'a>(id: &str, ids: impl Iterator<Item = &'a str> + Clone) -> String {
+    let mut end = 0;
+    for (count, (i, c)) in id.char_indices().enumerate() {
+        end = i + c.len_utf8();
+        let prefix = &id[..end];
+        if count + 1 >= SESSION_PREFIX_LEN
+            && !ids.clone().any(|other| other != id && other.starts_with(prefix))
+        {
+            break;
+        }
+    }
+    id[..end].to_string()
+}
+
+/// Eligibility of every account for every catalog model, for capacity planning
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingMatrix {
+    pub strategy: RoutingStrategy,
+    pub rows: Vec<MatrixRow>,
+}
+
+/// One catalog model and how each account stands for it
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixRow {
+    pub model: String,
+    /// One cell per account, in account order
+    pub cells: Vec<MatrixCell>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixCell {
+    pub account_id: AccountId,
+    pub label: String,
+    pub eligible: bool,
+    /// Strategy-specific score (see `rank_candidates`), for eligible accounts
+    pub metric: Option<f64>,
+    /// Why the account is excluded, for ineligible accounts
+    pub reason: Option<String>,
+}
+
+/// How simulated requests spread over the accounts (see `simulate`)
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub model: String,
+    pub strategy: RoutingStrategy,
+    pub requests: usize,
+    /// One entry per account, in account order
+    pub accounts: Vec<SimulatedAccount>,
+    /// Requests no account could take
+    pub unrouted: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedAccount {
+    pub account_id: AccountId,
+    pub label: String,
+    pub requests: usize,
+}
+
+impl SimulationReport {
+    /// Population variance of the per-account request counts
+    pub fn variance(&self) -> f64 {
+        let n = self.accounts.len();
+        if n == 0 {
+            return 0.0;
+        }
+        let mean = self.accounts.iter().map(|a| a.requests as f64).sum::<f64>() / n as f64;
+        self.accounts
+            .iter()
+            .map(|a| (a.requests as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n as f64
+    }
+
+    /// Gini coefficient of the per-account request counts: 0 is a perfectly
+    /// even spread, values towards 1 mean one account takes everything
+    pub fn gini(&self) -> f64 {
+        let n = self.accounts.len() as f64;
+        let total: f64 = self.accounts.iter().map(|a| a.requests as f64).sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let pairwise: f64 = self
+            .accounts
+            .iter()
+            .flat_map(|a| {
+                self.accounts
+                    .iter()
+                    .map(move |b| (a.requests as f64 - b.requests as f64).abs())
+            })
+            .sum();
+        pairwise / (2.0 * n * total)
+    }
+}
+
+/// How an `update_accounts` call changed the account set
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountSetDiff {
+    pub added: Vec<AccountId>,
+    pub removed: Vec<AccountId>,
+    /// Accounts present before and after whose settings changed
+    pub changed: Vec<AccountId>,
+}
+
+impl AccountSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
 }
 
 /// Circuit breaker state for tracking account health
@@ -70,6 +304,68 @@ impl CircuitState {
     }
 }
 
+/// Routing engine state from `export_state`, with instants stored as ages
+/// so it survives a restart
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EngineState {
+    pub round_robin_index: usize,
+    pub circuits: Vec<CircuitSnapshot>,
+    pub sessions: Vec<SessionSnapshot>,
+    pub local_costs: Vec<LocalCostSnapshot>,
+}
+
+/// One account's circuit breaker and last use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitSnapshot {
+    pub account_id: AccountId,
+    pub circuit: CircuitPhase,
+    /// How long the circuit has been open
+    #[serde(default)]
+    pub open_for_ms: Option<u64>,
+    pub consecutive_errors: u32,
+    #[serde(default)]
+    pub last_used_ago_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitPhase {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// A sticky session pin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub session_id: String,
+    /// Model family under per-model stickiness
+    #[serde(default)]
+    pub family: Option<String>,
+    pub account_id: AccountId,
+    pub idle_ms: u64,
+}
+
+/// Locally accounted spend for one account and billing month
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalCostSnapshot {
+    pub account_id: AccountId,
+    pub year: i32,
+    pub month: u32,
+    pub cost: f64,
+}
+
+/// Milliseconds since `at`
+fn age_ms(at: Instant) -> u64 {
+    at.elapsed().as_millis() as u64
+}
+
+/// The instant `ms` milliseconds ago, or now if that predates the clock
+fn instant_ago(ms: u64) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(Duration::from_millis(ms)).unwrap_or(now)
+}
+
 /// Account routing state
 struct AccountRouteState {
     circuit: CircuitState,
@@ -78,66 +374,661 @@ struct AccountRouteState {
 }
 
 /// The routing engine - determines which account to use for requests
+///
+/// An account's `preferred_strategy` overrides the engine strategy for
+/// requests that carry a session id, in this order:
+///
+/// 1. A session pinned to an eligible account preferring `Sticky` stays on
+///    it, whatever the engine (or failover) strategy is.
+/// 2. Otherwise the engine strategy selects; when it picks an account
+///    preferring `Sticky`, the session is pinned to that account.
+/// 3. With the `Sticky` strategy, accounts preferring any other strategy are
+///    never pinned, so their sessions are re-routed on every request.
+///
+/// Requests without a session id ignore the hint.
 pub struct RoutingEngine {
     strategy: RoutingStrategy,
+    failover_strategy: Option<RoutingStrategy>, // used instead of `strategy` on retries
     accounts: Arc<RwLock<Vec<AccountStatus>>>,
-    session_map: DashMap<String, uuid::Uuid>, // session_id -> account_id
+    session_map: DashMap<SessionKey, SessionPin>, // (session_id, model family) -> pin
+    sticky_scope: StickyScope,
+    /// Idle time after which `evict_stale_sessions` drops a pin
+    session_ttl: Option<Duration>,
+    /// Utilization lead over the best alternative at which a pin is dropped
+    sticky_decay_margin: Option<f64>,
     circuit_states: DashMap<uuid::Uuid, AccountRouteState>,
     round_robin_index: RwLock<usize>,
-    min_request_interval: Duration,
+    rng: Mutex<StdRng>, // tie-breaks between equally ranked accounts
+    usage_max_age: Option<chrono::Duration>,
+    stale_usage_policy: StaleUsagePolicy,
+    soft_limit_policy: SoftLimitPolicy,
+    size_rules: Vec<SizeRule>,
+    auto_balance: bool,
+    catalog: ModelCatalog,
+    /// Cost accounted from proxied responses, per account, for the current UTC month
+    local_costs: DashMap<uuid::Uuid, LocalCost>,
+    /// Usage counted from responses and not yet written to storage
+    unpolled_usage: DashMap<uuid::Uuid, UsageDelta>,
+    /// Smoothed upstream latency per account
+    latencies: DashMap<uuid::Uuid, Duration>,
+    /// Whether the last routed request went to a standby account
+    standby_promoted: std::sync::atomic::AtomicBool,
+    /// Tokens sent in the last minute, for accounts with a TPM limit
+    token_windows: DashMap<uuid::Uuid, TokenWindow>,
+    /// Latest upstream failure per account, for diagnostics
+    last_errors: DashMap<uuid::Uuid, LastError>,
+    /// Latest `x-ratelimit-*` headers per account
+    rate_limits: DashMap<uuid::Uuid, RateLimitSnapshot>,
+    experiment: Option<ExperimentConfig>,
+    /// Regions preferred among equally ranked accounts, best first
+    preferred_regions: Vec<String>,
+    /// Latest capability probe per account, narrowing model support
+    capabilities: DashMap<uuid::Uuid, AccountCapabilities>,
+    large_request_cooldown: Option<LargeRequestCooldown>,
+    /// When each account's cooldown after a large response ends
+    cooling_until: DashMap<uuid::Uuid, Instant>,
+    /// How long a decision is reused for identical requests; `None` disables
+    decision_cache_ttl: Option<Duration>,
+    decision_cache: DashMap<DecisionKey, CachedDecision>,
+}
+
+/// Requests that share a cached decision: model and endpoint path
+type DecisionKey = (String, Option<String>);
+
+#[derive(Debug, Clone)]
+struct CachedDecision {
+    decision: RoutingDecision,
+    at: Instant,
+}
+
+/// Span of the tokens-per-minute window
+const TPM_WINDOW: Duration = Duration::from_secs(60);
+
+/// A request's entry in an account's TPM window, to correct in place once
+/// the response reports how many tokens it actually used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenCharge {
+    account_id: AccountId,
+    entry: u64,
+}
+
+/// Token charges within the last `TPM_WINDOW`
+#[derive(Debug, Default)]
+struct TokenWindow {
+    /// (charged at, entry id, tokens)
+    entries: std::collections::VecDeque<(Instant, u64, u64)>,
+    next_entry: u64,
+}
+
+impl TokenWindow {
+    fn push(&mut self, tokens: u64) -> u64 {
+        let now = Instant::now();
+        while matches!(self.entries.front(), Some((at, _, _)) if now.duration_since(*at) >= TPM_WINDOW) {
+            self.entries.pop_front();
+        }
+        let entry = self.next_entry;
+        self.next_entry += 1;
+        self.entries.push_back((now, entry, tokens));
+        entry
+    }
+
+    /// Replace an entry's tokens, keeping when it was charged; a no-op once it expired
+    fn adjust(&mut self, entry: u64, tokens: u64) {
+        if let Some((_, _, charged)) = self.entries.iter_mut().find(|(_, id, _)| *id == entry) {
+            *charged = tokens;
+        }
+    }
+
+    fn total(&self) -> u64 {
+        let now = Instant::now();
+        self.entries
+            .iter()
+            .filter(|(at, _, _)| now.duration_since(*at) < TPM_WINDOW)
+            .map(|(_, _, tokens)| tokens)
+            .sum()
+    }
 }
 
+/// Locally accumulated spend for one billing month
+#[derive(Debug, Clone, Copy)]
+struct LocalCost {
+    month: (i32, u32),
+    cost: f64,
+}
+
+/// Current UTC billing month as (year, month)
+fn billing_month() -> (i32, u32) {
+    use chrono::Datelike;
+    let now = chrono::Utc::now();
+    (now.year(), now.month())
+}
+
+/// Sticky session key; the model family is `None` for global stickiness
+type SessionKey = (String, Option<String>);
+
+/// The account a sticky session is pinned to, and when the pin was last used
+#[derive(Debug, Clone, Copy)]
+struct SessionPin {
+    account_id: AccountId,
+    last_touch: Instant,
+}
+
+impl SessionPin {
+    fn new(account_id: AccountId) -> Self {
+        Self { account_id, last_touch: Instant::now() }
+    }
+}
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Priority points removed per 100% utilization when auto-balancing
+const AUTO_BALANCE_PENALTY: f64 = 10.0;
+
 impl RoutingEngine {
     /// Create a new routing engine
     pub fn new(strategy: RoutingStrategy) -> Self {
         Self {
             strategy,
+            failover_strategy: None,
             accounts: Arc::new(RwLock::new(Vec::new())),
             session_map: DashMap::new(),
+            sticky_scope: StickyScope::default(),
+            session_ttl: None,
+            sticky_decay_margin: None,
             circuit_states: DashMap::new(),
             round_robin_index: RwLock::new(0),
-            min_request_interval: Duration::from_millis(100),
+            rng: Mutex::new(StdRng::from_entropy()),
+            usage_max_age: None,
+            stale_usage_policy: StaleUsagePolicy::default(),
+            soft_limit_policy: SoftLimitPolicy::default(),
+            size_rules: Vec::new(),
+            auto_balance: false,
+            catalog: ModelCatalog::default(),
+            local_costs: DashMap::new(),
+            unpolled_usage: DashMap::new(),
+            latencies: DashMap::new(),
+            standby_promoted: std::sync::atomic::AtomicBool::new(false),
+            token_windows: DashMap::new(),
+            last_errors: DashMap::new(),
+            rate_limits: DashMap::new(),
+            experiment: None,
+            preferred_regions: Vec::new(),
+            capabilities: DashMap::new(),
+            large_request_cooldown: None,
+            cooling_until: DashMap::new(),
+            decision_cache_ttl: None,
+            decision_cache: DashMap::new(),
+        }
+    }
+
+    /// Fold an observed upstream latency into the account's moving average
+    pub fn record_latency(&self, account_id: AccountId, latency: Duration) {
+        self.latencies
+            .entry(account_id)
+            .and_modify(|avg| {
+                *avg = avg.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+            })
+            .or_insert(latency);
+    }
+
+    /// Smoothed upstream latency for an account, once one has been observed
+    pub fn latency(&self, account_id: AccountId) -> Option<Duration> {
+        self.latencies.get(&account_id).map(|l| *l)
+    }
+
+    /// Add the cost of a proxied response to an account's local monthly spend
+    pub fn record_local_cost(&self, account_id: AccountId, cost: f64) {
+        let month = billing_month();
+        let mut entry = self
+            .local_costs
+            .entry(account_id)
+            .or_insert(LocalCost { month, cost: 0.0 });
+
+        if entry.month != month {
+            *entry = LocalCost { month, cost: 0.0 };
+        }
+        entry.cost += cost;
+    }
+
+    /// Count tokens and spend from a proxied response until the next flush to storage
+    pub fn record_unpolled_usage(&self, account_id: AccountId, tokens: u64, cost: f64) {
+        let mut delta = self.unpolled_usage.entry(account_id).or_default();
+        delta.tokens += tokens;
+        delta.cost += cost;
+    }
+
+    /// Drain the usage counted since the last call, per account
+    pub fn take_unpolled_usage(&self) -> Vec<(AccountId, UsageDelta)> {
+        let ids: Vec<AccountId> = self.unpolled_usage.iter().map(|e| *e.key()).collect();
+        ids.into_iter()
+            .filter_map(|id| self.unpolled_usage.remove(&id))
+            .collect()
+    }
+
+    /// Forget unflushed usage for an account whose fresh poll already covers it
+    pub fn clear_unpolled_usage(&self, account_id: AccountId) {
+        self.unpolled_usage.remove(&account_id);
+    }
+
+    /// Spend accounted locally for an account this billing month
+    pub fn local_cost(&self, account_id: AccountId) -> f64 {
+        self.local_costs
+            .get(&account_id)
+            .filter(|c| c.month == billing_month())
+            .map(|c| c.cost)
+            .unwrap_or(0.0)
+    }
+
+    /// Whether local spend has reached the account's own monthly limit.
+    ///
+    /// This holds the cap between usage polls, when OpenAI's reported
+    /// `monthly_usage` lags behind what the proxy has already sent.
+    fn over_local_cap(&self, account: &Account) -> bool {
+        account
+            .monthly_limit
+            .map(|limit| self.local_cost(account.id) >= limit)
+            .unwrap_or(false)
+    }
+
+    /// Whether every enabled account is past a spend limit, so a request
+    /// can't be served until the budget resets. False without enabled accounts.
+    pub async fn all_over_budget(&self) -> bool {
+        let accounts = self.accounts.read().await;
+        let mut enabled = accounts.iter().filter(|s| s.account.enabled).peekable();
+        enabled.peek().is_some()
+            && enabled.all(|s| s.usage.is_over_limit(&s.account) || self.over_local_cap(&s.account))
+    }
+
+    /// Lowest price factor among enabled accounts: what the cheapest account
+    /// would charge relative to base rates. 1.0 without enabled accounts.
+    pub async fn min_cost_multiplier(&self) -> f64 {
+        self.accounts
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.account.enabled)
+            .map(|s| s.account.cost_multiplier)
+            .reduce(f64::min)
+            .unwrap_or(1.0)
+    }
+
+    /// Tokens charged to an account within the last minute
+    pub fn tokens_last_minute(&self, account_id: AccountId) -> u64 {
+        self.token_windows
+            .get(&account_id)
+            .map(|window| window.total())
+            .unwrap_or(0)
+    }
+
+    /// Whether the request's estimated tokens would push the account past its
+    /// TPM limit
+    fn over_tpm_limit(&self, account: &Account, ctx: &RequestContext) -> bool {
+        let Some(limit) = account.tpm_limit else {
+            return false;
+        };
+        self.tokens_last_minute(account.id) + ctx.estimated_tokens.unwrap_or(0) > limit
+    }
+
+    /// Charge a routed request's estimated tokens to a TPM-limited account
+    fn charge_tokens(&self, account: &Account, tokens: u64) -> Option<TokenCharge> {
+        account.tpm_limit?;
+        let entry = self.token_windows.entry(account.id).or_default().push(tokens);
+        Some(TokenCharge { account_id: account.id, entry })
+    }
+
+    fn adjust_charge(&self, charge: TokenCharge, tokens: u64) {
+        if let Some(mut window) = self.token_windows.get_mut(&charge.account_id) {
+            window.adjust(charge.entry, tokens);
+        }
+    }
+
+    /// Drop a request's TPM charge after its attempt failed upstream
+    pub fn release_tokens(&self, charge: Option<TokenCharge>) {
+        if let Some(charge) = charge {
+            self.adjust_charge(charge, 0);
+        }
+    }
+
+    /// Replace the request's TPM estimate with the actual token count the
+    /// response reported, starting a cooldown if the response was large
+    pub fn reconcile_tokens(&self, account_id: AccountId, charge: Option<TokenCharge>, actual: u64) {
+        if let Some(charge) = charge {
+            self.adjust_charge(charge, actual);
+        }
+
+        if let Some(cooldown) = self.large_request_cooldown {
+            if actual >= cooldown.min_tokens {
+                debug!("Account {} served {} tokens, cooling down", account_id, actual);
+                self.cooling_until.insert(
+                    account_id,
+                    Instant::now() + Duration::from_secs(cooldown.cooldown_secs),
+                );
+            }
+        }
+    }
+
+    /// Reuse a decision for identical requests within `ttl` instead of
+    /// running the strategy again; `update_accounts` drops cached decisions
+    pub fn with_decision_cache_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.decision_cache_ttl = ttl;
+        self
+    }
+
+    fn decision_key(&self, ctx: &RequestContext) -> DecisionKey {
+        (ctx.model.clone(), ctx.path.clone())
+    }
+
+    /// A cached decision for `ctx` whose account is still among this
+    /// request's candidates
+    fn cached_decision<'a>(&self,
+        candidates: &[&'a AccountStatus],
+        ctx: &RequestContext,
+    ) -> Option<(RoutingDecision, &'a AccountStatus)> {
+        let ttl = self.decision_cache_ttl?;
+        let key = self.decision_key(ctx);
+        let cached = self.decision_cache.get(&key)?.clone();
+        if cached.at.elapsed() > ttl {
+            self.decision_cache.remove(&key);
+            return None;
+        }
+
+        let status = candidates
+            .iter()
+            .copied()
+            .find(|s| s.account.id == cached.decision.account_id)?;
+
+        let mut decision = cached.decision;
+        decision.reason.layers = self.applied_layers(ctx, status, false);
+        decision.utilization_ratio = status.usage.utilization_ratio();
+        decision.remaining_budget = status.usage.remaining_budget;
+        Some((decision, status))
+    }
+
+    /// Pass over accounts for a while after they serve a large response
+    pub fn with_large_request_cooldown(mut self, cooldown: Option<LargeRequestCooldown>) -> Self {
+        self.large_request_cooldown = cooldown;
+        self
+    }
+
+    /// Whether an account's usage is older than the maximum age as of now;
+    /// an account that was never polled has no fresh usage either
+    fn usage_stale(&self, status: &AccountStatus) -> bool {
+        self.usage_max_age
+            .is_some_and(|age| !status.usage_polled || status.usage.is_stale(age))
+    }
+
+    /// Whether an account is cooling down after a large response and should
+    /// be passed over for this request; only large requests are moved elsewhere
+    fn cooling_down(&self, account_id: AccountId, ctx: &RequestContext) -> bool {
+        let Some(cooldown) = self.large_request_cooldown else {
+            return false;
+        };
+        ctx.estimated_tokens.is_some_and(|tokens| tokens >= cooldown.min_tokens)
+            && self
+                .cooling_until
+                .get(&account_id)
+                .is_some_and(|until| Instant::now() < *until)
+    }
+
+    /// Pin sticky sessions per model family instead of globally
+    pub fn with_sticky_scope(mut self, scope: StickyScope) -> Self {
+        self.sticky_scope = scope;
+        self
+    }
+
+    /// Re-pin a sticky session once its account's utilization exceeds the
+    /// least utilized candidate's by more than `margin` (a 0.0-1.0 ratio)
+    pub fn with_sticky_decay(mut self, margin: Option<f64>) -> Self {
+        self.sticky_decay_margin = margin;
+        self
+    }
+
+    /// Evict sticky sessions unused for longer than `ttl`
+    pub fn with_session_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.session_ttl = ttl;
+        self
+    }
+
+    /// Whether a pinned account has become too loaded to keep its sessions
+    fn pin_decayed(&self, pinned: &AccountStatus, candidates: &[&AccountStatus]) -> bool {
+        let Some(margin) = self.sticky_decay_margin else {
+            return false;
+        };
+
+        let lowest = candidates
+            .iter()
+            .map(|s| s.usage.utilization_ratio())
+            .fold(f64::INFINITY, f64::min);
+        pinned.usage.utilization_ratio() - lowest > margin
+    }
+
+    /// Session map key for a request, if it carries a session id
+    fn session_key(&self, ctx: &RequestContext) -> Option<SessionKey> {
+        let session = ctx.session_id.clone()?;
+        let family = match self.sticky_scope {
+            StickyScope::Global => None,
+            StickyScope::PerModel => Some(
+                ctx.capability
+                    .or_else(|| self.catalog.capability_of(&ctx.model))
+                    .map(|c| c.as_str().to_string())
+                    // Models the catalog doesn't know are their own family
+                    .unwrap_or_else(|| ctx.model.clone()),
+            ),
+        };
+
+        Some((session, family))
+    }
+
+    /// Use a custom model catalog for wildcard expansion and capability checks
+    pub fn with_catalog(mut self, catalog: ModelCatalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Record an account's capability probe; its model list then limits
+    /// which models the account is routed
+    pub fn set_capabilities(&self, capabilities: AccountCapabilities) {
+        self.capabilities.insert(capabilities.account_id, capabilities);
+    }
+
+    /// Latest capability probe recorded for an account
+    pub fn capabilities(&self, account_id: AccountId) -> Option<AccountCapabilities> {
+        self.capabilities.get(&account_id).map(|c| c.clone())
+    }
+
+    /// Model catalog used by this engine
+    pub fn catalog(&self) -> &ModelCatalog {
+        &self.catalog
+    }
+
+    /// Models servable by at least one available account, in catalog order
+    pub async fn served_models(&self) -> Vec<ModelInfo> {
+        let accounts = self.accounts.read().await;
+
+        self.catalog
+            .models()
+            .iter()
+            .filter(|model| {
+                accounts
+                    .iter()
+                    .any(|s| s.is_available && self.supports_model(&s.account, &model.id))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Pick retry accounts with a different strategy than first attempts
+    pub fn with_failover_strategy(mut self, strategy: Option<RoutingStrategy>) -> Self {
+        self.failover_strategy = strategy;
+        self
+    }
+
+    /// Lower the effective priority of accounts in proportion to their utilization
+    pub fn with_auto_balance(mut self, enabled: bool) -> Self {
+        self.auto_balance = enabled;
+        self
+    }
+
+    /// Routing priority for an account given its current usage
+    fn effective_priority(&self,
+        account: &Account,
+        usage: &UsageSnapshot,
+    ) -> i32 {
+        if !self.auto_balance {
+            return account.priority;
+        }
+
+        let penalty = (usage.utilization_ratio().clamp(0.0, 1.0) * AUTO_BALANCE_PENALTY).round();
+        account.priority - penalty as i32
+    }
+
+    /// Restrict requests to account subsets by estimated token count
+    pub fn with_size_rules(mut self, rules: Vec<SizeRule>) -> Self {
+        self.size_rules = rules;
+        self
+    }
+
+    /// Prefer accounts in earlier-listed regions when the strategy ranks
+    /// several accounts equally
+    pub fn with_preferred_regions(mut self, regions: Vec<String>) -> Self {
+        self.preferred_regions = regions;
+        self
+    }
+
+    /// Divert a percentage of requests to one account
+    pub fn with_experiment(mut self, experiment: Option<ExperimentConfig>) -> Self {
+        self.experiment = experiment;
+        self
+    }
+
+    /// Treat usage snapshots older than `max_age` as unknown
+    pub fn with_usage_max_age(
+        mut self,
+        max_age: Option<Duration>,
+        policy: StaleUsagePolicy,
+    ) -> Self {
+        self.usage_max_age = max_age.and_then(|age| chrono::Duration::from_std(age).ok());
+        self.stale_usage_policy = policy;
+        self
+    }
+
+    /// Choose what happens when every candidate is past its soft limit
+    pub fn with_soft_limit_policy(mut self, policy: SoftLimitPolicy) -> Self {
+        self.soft_limit_policy = policy;
+        self
+    }
+
+    /// Seed the tie-break RNG for reproducible selection (entropy if `None`)
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        if let Some(seed) = seed {
+            self.rng = Mutex::new(StdRng::seed_from_u64(seed));
         }
+        self
     }
 
-    /// Update the accounts and usage data
+    /// Swap in a new account set and usage data.
+    ///
+    /// Circuit, latency and local spend state carry over for accounts that
+    /// remain; state for removed accounts, and sessions pinned to them, is
+    /// dropped.
     pub async fn update_accounts(&self,
         accounts: Vec<Account>,
         usage_map: std::collections::HashMap<uuid::Uuid, UsageSnapshot>,
-    ) {
+    ) -> AccountSetDiff {
         let mut statuses = Vec::new();
 
         for account in accounts {
-            let usage = usage_map
-                .get(&account.id)
-                .cloned()
-                .unwrap_or_else(|| UsageSnapshot::new(account.id));
+            let polled = usage_map.get(&account.id).cloned();
+            let usage_polled = polled.is_some();
+            let usage = polled.unwrap_or_else(|| UsageSnapshot::new(account.id));
+
+            let over_local_cap = self.over_local_cap(&account);
 
             let is_available = account.enabled
                 && !usage.is_over_limit(&account)
+                && !over_local_cap
                 && self.is_circuit_available(account.id).await;
 
             let disable_reason = if !account.enabled {
-                Some("Account disabled".to_string())
+                Some(
+                    account
+                        .disabled_reason
+                        .clone()
+                        .unwrap_or_else(|| "Account disabled".to_string()),
+                )
             } else if usage.is_over_limit(&account) {
                 Some("Over usage limit".to_string())
+            } else if over_local_cap {
+                Some("Local spend cap reached".to_string())
             } else if !self.is_circuit_available(account.id).await {
                 Some("Circuit breaker open".to_string())
             } else {
                 None
             };
 
+            let effective_priority = self.effective_priority(&account, &usage);
+            let rate_limit = self.rate_limit(account.id);
+
             statuses.push(AccountStatus {
                 account,
                 usage,
                 is_available,
                 disable_reason,
+                usage_polled,
+                effective_priority,
+                rate_limit,
             });
         }
 
         let mut guard = self.accounts.write().await;
+
+        let previous: std::collections::HashMap<AccountId, &Account> =
+            guard.iter().map(|s| (s.account.id, &s.account)).collect();
+        let mut diff = AccountSetDiff::default();
+        for status in &statuses {
+            match previous.get(&status.account.id) {
+                None => diff.added.push(status.account.id),
+                Some(old) if **old != status.account => diff.changed.push(status.account.id),
+                Some(_) => {}
+            }
+        }
+        let current: std::collections::HashSet<AccountId> =
+            statuses.iter().map(|s| s.account.id).collect();
+        diff.removed = guard
+            .iter()
+            .map(|s| s.account.id)
+            .filter(|id| !current.contains(id))
+            .collect();
+
+        self.decision_cache.clear();
+        for id in &diff.removed {
+            self.cooling_until.remove(id);
+            self.circuit_states.remove(id);
+            self.latencies.remove(id);
+            self.local_costs.remove(id);
+            self.token_windows.remove(id);
+            self.rate_limits.remove(id);
+        }
+        if !diff.removed.is_empty() {
+            self.session_map.retain(|_, pin| !diff.removed.contains(&pin.account_id));
+        }
+
         *guard = statuses;
-        debug!("Updated {} accounts in routing engine", guard.len());
+
+        if diff.is_empty() {
+            debug!("Updated {} accounts in routing engine", guard.len());
+        } else {
+            info!(
+                "Updated {} accounts in routing engine: {} added, {} removed, {} changed",
+                guard.len(),
+                diff.added.len(),
+                diff.removed.len(),
+                diff.changed.len()
+            );
+        }
+
+        diff
     }
 
     /// Check if account circuit is available
@@ -146,52 +1037,145 @@ impl RoutingEngine {
     ) -> bool {
         self.circuit_states
             .get(&account_id)
-            .map(|s| s.is_available())
+            .map(|s| s.circuit.is_available())
             .unwrap_or(true)
     }
 
     /// Resolve which account to use for a request
-    #[instrument(skip(self, ctx), fields(model = %ctx.model))]
     pub async fn resolve_account(&self,
         ctx: &RequestContext,
     ) -> Result<RoutingDecision> {
-        let accounts = self.accounts.read().await;
-
-        // Filter to available accounts that support the model
-        let candidates: Vec<&AccountStatus> = accounts
-            .iter()
-            .filter(|s| {
-                s.is_available
-                    && self.supports_model(&s.account, &ctx.model)
-                    && self.circuit_states
-                        .get(&s.account.id)
-                        .map(|state| state.can_attempt())
-                        .unwrap_or(true)
-            })
-            .collect();
+        self.resolve_with(ctx, self.strategy, &[]).await
+    }
 
-        if candidates.is_empty() {
-            anyhow::bail!("No available accounts for model {}", ctx.model);
-        }
+    /// Resolve a replacement account after `failed` accounts errored for this request.
+    ///
+    /// Uses the failover strategy when one is configured, otherwise the
+    /// primary strategy. Accounts in `failed` are never selected again.
+    pub async fn resolve_failover(&self,
+        ctx: &RequestContext,
+        failed: &[AccountId],
+    ) -> Result<RoutingDecision> {
+        let strategy = self.failover_strategy.unwrap_or(self.strategy);
+        let mut decision = self.resolve_with(ctx, strategy, failed).await?;
+        decision.reason.layers.push(RoutingLayer::Failover { failed: failed.len() });
+        Ok(decision)
+    }
 
-        // Apply routing strategy
-        let selected = match self.strategy {
-            RoutingStrategy::LeastUtilized => {
-                self.select_least_utilized(&candidates).await
-            }
-            RoutingStrategy::RoundRobin => {
-                self.select_round_robin(&candidates).await
-            }
-            RoutingStrategy::Priority => {
-                self.select_by_priority(&candidates).await
-            }
-            RoutingStrategy::Sticky = {
-                self.select_sticky(&candidates, ctx.session_id.as_deref()).await
+    #[instrument(
+        name = "resolve_account",
+        skip(self, ctx, exclude),
+        fields(model = %ctx.model, strategy = ?strategy, candidates = tracing::field::Empty)
+    )]
+    async fn resolve_with(&self,
+        ctx: &RequestContext,
+        strategy: RoutingStrategy,
+        exclude: &[AccountId],
+    ) -> Result<RoutingDecision> {
+        let accounts = self.accounts.read().await;
+        self.resolve_in(&accounts, ctx, strategy, exclude).await
+    }
+
+    /// Resolve a batch of requests under a single accounts read-lock.
+    ///
+    /// Requests are resolved in order, so round-robin and sticky state
+    /// advance exactly as if each had gone through `resolve_account`.
+    pub async fn resolve_many(&self,
+        ctxs: &[RequestContext],
+    ) -> Vec<Result<RoutingDecision>> {
+        let accounts = self.accounts.read().await;
+        let mut decisions = Vec::with_capacity(ctxs.len());
+        for ctx in ctxs {
+            decisions.push(self.resolve_in(&accounts, ctx, self.strategy, &[]).await);
+        }
+        decisions
+    }
+
+    /// Select an account among `accounts`, which the caller holds locked
+    async fn resolve_in(&self,
+        accounts: &[AccountStatus],
+        ctx: &RequestContext,
+        strategy: RoutingStrategy,
+        exclude: &[AccountId],
+    ) -> Result<RoutingDecision> {
+        // Filter to available accounts that support the model
+        let candidates: Vec<&AccountStatus> = self
+            .eligible_candidates(accounts, ctx)
+            .into_iter()
+            .filter(|s| !exclude.contains(&s.account.id))
+            .collect();
+        tracing::Span::current().record("candidates", candidates.len());
+
+        if candidates.is_empty() {
+            anyhow::bail!("No available accounts for model {}", ctx.model);
+        }
+
+        // A session pinned to an account that prefers stickiness stays there
+        let base_strategy = strategy;
+        let pinned = self.preferred_sticky_pin(&candidates, ctx);
+        let strategy = if pinned.is_some() { RoutingStrategy::Sticky } else { strategy };
+
+        // Failovers never divert, so a failing experiment account isn't retried
+        let diverted = if exclude.is_empty() {
+            self.divert_to_experiment(&candidates)
+        } else {
+            None
+        };
+
+        // Only plain strategy picks are reused: failovers re-route away from
+        // the account that just failed, and diversions and session pins are
+        // decided per request
+        let cacheable = self.decision_cache_ttl.is_some()
+            && exclude.is_empty()
+            && diverted.is_none()
+            && strategy != RoutingStrategy::Sticky;
+        if cacheable {
+            if let Some((mut decision, status)) = self.cached_decision(&candidates, ctx) {
+                self.note_standby_tier(status.account.standby);
+                decision.token_charge =
+                    self.charge_tokens(&status.account, ctx.estimated_tokens.unwrap_or(0));
+                if let Some(mut state) = self.circuit_states.get_mut(&status.account.id) {
+                    state.last_used = Some(Instant::now());
+                }
+                return Ok(decision);
             }
+        }
+
+        // Apply routing strategy
+        let selected = match diverted {
+            Some(selected) => selected,
+            None => match strategy {
+                RoutingStrategy::LeastUtilized => {
+                    self.select_least_utilized(&candidates).await
+                }
+                RoutingStrategy::RoundRobin => {
+                    self.select_round_robin(&candidates).await
+                }
+                RoutingStrategy::Priority => {
+                    self.select_by_priority(&candidates).await
+                }
+                RoutingStrategy::Sticky => {
+                    self.select_sticky(&candidates, self.session_key(ctx)).await
+                }
+                RoutingStrategy::CheapestFirst => {
+                    self.select_cheapest(&candidates).await
+                }
+            },
         };
 
+        if strategy != RoutingStrategy::Sticky
+            && selected.account.preferred_strategy == Some(RoutingStrategy::Sticky)
+        {
+            if let Some(key) = self.session_key(ctx) {
+                self.session_map.insert(key, SessionPin::new(selected.account.id));
+            }
+        }
+
+        self.note_standby_tier(selected.account.standby);
+        let token_charge = self.charge_tokens(&selected.account, ctx.estimated_tokens.unwrap_or(0));
+
         // Update last used time
-        if let Some(state) = self.circuit_states.get_mut(&selected.account.id) {
+        if let Some(mut state) = self.circuit_states.get_mut(&selected.account.id) {
             state.last_used = Some(Instant::now());
         }
 
@@ -202,15 +1186,443 @@ impl RoutingEngine {
             ctx.model
         );
 
-        Ok(RoutingDecision {
+        let decision = RoutingDecision {
             account_id: selected.account.id,
             account_label: selected.account.label.clone(),
             api_key: selected.account.api_key.clone(),
             org_id: selected.account.org_id.clone(),
-            reason: self.build_reason(ctx, &selected),
+            reason: RoutingReason {
+                strategy: base_strategy,
+                selection: match (diverted, &self.experiment) {
+                    (Some(_), Some(experiment)) => SelectionReason::Experiment { percent: experiment.percent },
+                    _ => self.build_reason(strategy, ctx, selected),
+                },
+                layers: self.applied_layers(ctx, selected, pinned.is_some()),
+            },
             utilization_ratio: selected.usage.utilization_ratio(),
             remaining_budget: selected.usage.remaining_budget,
-        })
+            cost_multiplier: selected.account.cost_multiplier,
+            no_upstream_auth: selected.account.no_upstream_auth,
+            token_charge,
+        };
+
+        // An account that pins sessions would hand its pin to every request
+        if cacheable && selected.account.preferred_strategy != Some(RoutingStrategy::Sticky) {
+            self.decision_cache.insert(
+                self.decision_key(ctx),
+                CachedDecision { decision: decision.clone(), at: Instant::now() },
+            );
+        }
+
+        Ok(decision)
+    }
+
+    /// The experiment account, if this request rolled a diversion and the
+    /// account is among the candidates; otherwise normal routing applies
+    fn divert_to_experiment<'a>(&self,
+        candidates: &[&'a AccountStatus],
+    ) -> Option<&'a AccountStatus> {
+        let experiment = self.experiment.as_ref()?;
+        if self.rng.lock().gen::<f64>() * 100.0 >= experiment.percent {
+            return None;
+        }
+        candidates.iter().copied().find(|s| s.account.id == experiment.account_id)
+    }
+
+    /// Rank all eligible accounts for a request without selecting one.
+    ///
+    /// Returns `(account_id, metric)` pairs in the order the current strategy
+    /// would try them. The metric is strategy-specific: utilization ratio for
+    /// least-utilized and sticky, priority for priority routing, cost
+    /// multiplier for cheapest-first, and the distance from the round-robin
+    /// cursor for round-robin. This never
+    /// advances the round-robin cursor or creates session mappings.
+    pub async fn rank_candidates(&self,
+        ctx: &RequestContext,
+    ) -> Vec<(AccountId, f64)> {
+        let accounts = self.accounts.read().await;
+        let mut candidates = self.eligible_candidates(&accounts, ctx);
+
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        let by_utilization = |a: &&AccountStatus, b: &&AccountStatus| {
+            let util_a = a.usage.utilization_ratio();
+            let util_b = b.usage.utilization_ratio();
+            util_a.partial_cmp(&util_b).unwrap_or(std::cmp::Ordering::Equal)
+        };
+
+        match self.strategy {
+            RoutingStrategy::LeastUtilized => {
+                candidates.sort_by(by_utilization);
+                candidates
+                    .iter()
+                    .map(|s| (s.account.id, s.usage.utilization_ratio()))
+                    .collect()
+            }
+            RoutingStrategy::Priority => {
+                candidates.sort_by_key(|s| std::cmp::Reverse(s.effective_priority));
+                candidates
+                    .iter()
+                    .map(|s| (s.account.id, s.effective_priority as f64))
+                    .collect()
+            }
+            RoutingStrategy::CheapestFirst => {
+                candidates.sort_by(|a, b| {
+                    a.account
+                        .cost_multiplier
+                        .partial_cmp(&b.account.cost_multiplier)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then_with(|| by_utilization(a, b))
+                });
+                candidates
+                    .iter()
+                    .map(|s| (s.account.id, s.account.cost_multiplier))
+                    .collect()
+            }
+            RoutingStrategy::RoundRobin => {
+                let len = candidates.len();
+                let start = *self.round_robin_index.read().await % len;
+                (0..len)
+                    .map(|offset| {
+                        let status = candidates[(start + offset) % len];
+                        (status.account.id, offset as f64)
+                    })
+                    .collect()
+            }
+            RoutingStrategy::Sticky => {
+                candidates.sort_by(by_utilization);
+
+                // A pinned session account is always tried first
+                let pinned = self
+                    .session_key(ctx)
+                    .and_then(|key| self.session_map.get(&key).map(|pin| pin.account_id));
+                if let Some(pos) = pinned
+                    .and_then(|id| candidates.iter().position(|s| s.account.id == id))
+                {
+                    let status = candidates.remove(pos);
+                    candidates.insert(0, status);
+                }
+
+                candidates
+                    .iter()
+                    .map(|s| (s.account.id, s.usage.utilization_ratio()))
+                    .collect()
+            }
+        }
+    }
+
+    /// Filter accounts to those eligible to serve the request.
+    ///
+    /// Standby accounts only take part once no primary account is left.
+    fn eligible_candidates<'a>(&self,
+        accounts: &'a [AccountStatus],
+        ctx: &RequestContext,
+    ) -> Vec<&'a AccountStatus> {
+        let (standby, primary): (Vec<&AccountStatus>, Vec<&AccountStatus>) = accounts
+            .iter()
+            .filter(|s| self.exclusion_reason(s, ctx).is_none())
+            .partition(|s| s.account.standby);
+
+        let primary = self.narrow_candidates(primary, ctx);
+        if !primary.is_empty() || standby.is_empty() {
+            return primary;
+        }
+        self.narrow_candidates(standby, ctx)
+    }
+
+    /// Apply size rules, the stale-usage and cooldown fallbacks and soft
+    /// limits to accounts that can each serve the request
+    fn narrow_candidates<'a>(&self,
+        candidates: Vec<&'a AccountStatus>,
+        ctx: &RequestContext,
+    ) -> Vec<&'a AccountStatus> {
+        let candidates = self.apply_size_rules(candidates, ctx);
+
+        // Accounts with stale usage are only used when nothing fresher is left
+        let candidates = if candidates.iter().any(|s| !self.usage_stale(s)) {
+            candidates.into_iter().filter(|s| !self.usage_stale(s)).collect()
+        } else {
+            candidates
+        };
+
+        // Likewise accounts cooling down after a large response
+        let candidates = if candidates.iter().any(|s| !self.cooling_down(s.account.id, ctx)) {
+            candidates.into_iter().filter(|s| !self.cooling_down(s.account.id, ctx)).collect()
+        } else {
+            candidates
+        };
+
+        self.apply_soft_limits(candidates)
+    }
+
+    /// Log when traffic moves onto the standby tier or back off it
+    fn note_standby_tier(&self, standby: bool) {
+        let was = self.standby_promoted.swap(standby, std::sync::atomic::Ordering::Relaxed);
+        match (was, standby) {
+            (false, true) => warn!("No primary account available, promoting standby accounts"),
+            (true, false) => info!("Primary accounts recovered, demoting standby accounts"),
+            _ => {}
+        }
+    }
+
+    /// Standby accounts that are enabled, for health probing
+    pub async fn standby_accounts(&self) -> Vec<Account> {
+        self.accounts
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.account.standby && s.account.enabled)
+            .map(|s| s.account.clone())
+            .collect()
+    }
+
+    /// Prefer accounts under their soft limit, falling back to the soft
+    /// limit policy when every candidate is past it
+    fn apply_soft_limits<'a>(&self,
+        candidates: Vec<&'a AccountStatus>,
+    ) -> Vec<&'a AccountStatus> {
+        if candidates.iter().any(|s| s.usage.soft_limit_overage().is_none()) {
+            return candidates
+                .into_iter()
+                .filter(|s| s.usage.soft_limit_overage().is_none())
+                .collect();
+        }
+
+        match self.soft_limit_policy {
+            SoftLimitPolicy::Reject => {
+                if !candidates.is_empty() {
+                    warn!("All {} candidates are past their soft limit, rejecting", candidates.len());
+                }
+                Vec::new()
+            }
+            SoftLimitPolicy::UseLeastOverSoft => {
+                let least = candidates
+                    .iter()
+                    .filter_map(|s| s.usage.soft_limit_overage())
+                    .fold(f64::INFINITY, f64::min);
+                candidates
+                    .into_iter()
+                    .filter(|s| s.usage.soft_limit_overage() == Some(least))
+                    .collect()
+            }
+            SoftLimitPolicy::Allow => candidates,
+        }
+    }
+
+    /// Why an account can't serve a request on its own merits, if it can't.
+    ///
+    /// Size rules and the stale-usage fallback depend on the other accounts,
+    /// so they are applied separately in `eligible_candidates`.
+    fn exclusion_reason(&self, status: &AccountStatus, ctx: &RequestContext) -> Option<String> {
+        if !status.is_available {
+            return Some(
+                status
+                    .disable_reason
+                    .clone()
+                    .unwrap_or_else(|| "Unavailable".to_string()),
+            );
+        }
+        if self.over_local_cap(&status.account) {
+            return Some("Local spend cap reached".to_string());
+        }
+        if self.over_tpm_limit(&status.account, ctx) {
+            return Some("Tokens-per-minute limit reached".to_string());
+        }
+        if self.is_rate_limited(status.account.id) {
+            return Some("Upstream rate limit exhausted".to_string());
+        }
+        if let Some((cost, ceiling)) = ctx.cost_ceiling {
+            if cost * status.account.cost_multiplier > ceiling {
+                return Some("Estimated cost over the request's ceiling".to_string());
+            }
+        }
+        if self.stale_usage_policy == StaleUsagePolicy::Disable && self.usage_stale(status) {
+            return Some("Usage data stale".to_string());
+        }
+        if !self.supports_model(&status.account, &ctx.model) {
+            return Some(format!("Model {} outside the account's scope", ctx.model));
+        }
+        if let Some(capability) = ctx.capability {
+            if !self.has_capability(&status.account, capability) {
+                return Some(format!("No {} models in scope", capability.as_str()));
+            }
+        }
+        if let Some(path) = ctx.path.as_deref() {
+            if !status.account.allows_path(path) {
+                return Some(format!("Endpoint {} not allowed for the account", path));
+            }
+        }
+        let circuit_open = self
+            .circuit_states
+            .get(&status.account.id)
+            .map(|state| !state.circuit.can_attempt())
+            .unwrap_or(false);
+        if circuit_open {
+            return Some("Circuit breaker open".to_string());
+        }
+
+        None
+    }
+
+    /// Trace how a request would be routed: every account, eligible or not,
+    /// and the one that would be chosen. Never mutates routing state.
+    pub async fn explain(&self, ctx: &RequestContext) -> RouteTrace {
+        let ranked = self.rank_candidates(ctx).await;
+        let accounts = self.accounts.read().await;
+
+        let label_of = |id: AccountId| {
+            accounts
+                .iter()
+                .find(|s| s.account.id == id)
+                .map(|s| s.account.label.clone())
+                .unwrap_or_default()
+        };
+        let candidates: Vec<TracedCandidate> = ranked
+            .iter()
+            .map(|&(account_id, score)| TracedCandidate {
+                account_id,
+                label: label_of(account_id),
+                score,
+            })
+            .collect();
+
+        let excluded = accounts
+            .iter()
+            .filter(|s| !ranked.iter().any(|(id, _)| *id == s.account.id))
+            .map(|s| {
+                let reason = self.exclusion_reason(s, ctx).unwrap_or_else(|| {
+                    if s.account.standby {
+                        "Standby, primary accounts available".to_string()
+                    } else if s.usage.soft_limit_overage().is_some() {
+                        "Past soft limit".to_string()
+                    } else if self.usage_stale(s) {
+                        "Usage data stale, fresher accounts available".to_string()
+                    } else {
+                        "Outside the size rule for this request".to_string()
+                    }
+                });
+                TracedExclusion {
+                    account_id: s.account.id,
+                    label: s.account.label.clone(),
+                    reason,
+                }
+            })
+            .collect();
+
+        RouteTrace {
+            model: ctx.model.clone(),
+            strategy: self.strategy,
+            chosen: candidates.first().map(|c| c.account_id),
+            candidates,
+            excluded,
+        }
+    }
+
+    /// Trace every catalog model against every account. Never mutates routing state.
+    pub async fn routing_matrix(&self) -> RoutingMatrix {
+        let mut rows = Vec::new();
+
+        for model in self.catalog.models() {
+            let ctx = RequestContext::new(model.id.clone()).with_capability(Some(model.capability));
+            let trace = self.explain(&ctx).await;
+
+            let accounts = self.accounts.read().await;
+            let cells = accounts
+                .iter()
+                .map(|s| {
+                    let id = s.account.id;
+                    let candidate = trace.candidates.iter().find(|c| c.account_id == id);
+                    let exclusion = trace.excluded.iter().find(|e| e.account_id == id);
+                    MatrixCell {
+                        account_id: id,
+                        label: s.account.label.clone(),
+                        eligible: candidate.is_some(),
+                        metric: candidate.map(|c| c.score),
+                        reason: exclusion.map(|e| e.reason.clone()),
+                    }
+                })
+                .collect();
+
+            rows.push(MatrixRow { model: model.id.clone(), cells });
+        }
+
+        RoutingMatrix { strategy: self.strategy, rows }
+    }
+
+    /// Resolve `requests` requests for `model` as one batch and count where
+    /// they go.
+    ///
+    /// Routes for real, so round-robin and sticky state advance as they would
+    /// under traffic; meant for a throwaway engine, never the proxy's.
+    pub async fn simulate(&self, model: &str, requests: usize) -> SimulationReport {
+        let mut counts: std::collections::HashMap<AccountId, usize> =
+            std::collections::HashMap::new();
+        let mut unrouted = 0;
+
+        let ctxs = vec![RequestContext::new(model.to_string()); requests];
+        for result in self.resolve_many(&ctxs).await {
+            match result {
+                Ok(decision) => *counts.entry(decision.account_id).or_insert(0) += 1,
+                Err(_) => unrouted += 1,
+            }
+        }
+
+        let accounts = self
+            .accounts
+            .read()
+            .await
+            .iter()
+            .map(|s| SimulatedAccount {
+                account_id: s.account.id,
+                label: s.account.label.clone(),
+                requests: counts.get(&s.account.id).copied().unwrap_or(0),
+            })
+            .collect();
+
+        SimulationReport {
+            model: model.to_string(),
+            strategy: self.strategy,
+            requests,
+            accounts,
+            unrouted,
+        }
+    }
+
+    /// First size rule matching the request's estimated tokens
+    fn size_rule_for(&self, ctx: &RequestContext) -> Option<&SizeRule> {
+        let tokens = ctx.estimated_tokens?;
+        self.size_rules.iter().find(|r| r.matches(tokens))
+    }
+
+    /// Narrow candidates to the subset of the first size rule matching the request
+    fn apply_size_rules<'a>(&self,
+        candidates: Vec<&'a AccountStatus>,
+        ctx: &RequestContext,
+    ) -> Vec<&'a AccountStatus> {
+        let Some(rule) = self.size_rule_for(ctx) else {
+            return candidates;
+        };
+        let tokens = ctx.estimated_tokens.unwrap_or_default();
+
+        let subset: Vec<&AccountStatus> = candidates
+            .iter()
+            .copied()
+            .filter(|s| rule.account_ids.contains(&s.account.id))
+            .collect();
+
+        if subset.is_empty() {
+            // Better to serve from the wrong tier than to fail the request
+            warn!(
+                "No available account in size rule for {} tokens, using all accounts",
+                tokens
+            );
+            candidates
+        } else {
+            trace!("Size rule matched {} tokens: {} candidates", tokens, subset.len());
+            subset
+        }
     }
 
     /// Check if account supports the requested model
@@ -218,6 +1630,13 @@ impl RoutingEngine {
         account: &Account,
         model: &str,
     ) -> bool {
+        let unlisted = self
+            .capabilities
+            .get(&account.id)
+            .is_some_and(|c| c.excludes_model(model));
+        if unlisted {
+            return false;
+        }
         if account.model_scope.is_empty() {
             return true; // Empty scope = all models
         }
@@ -231,25 +1650,64 @@ impl RoutingEngine {
         })
     }
 
+    /// Check if an account's scope can serve an endpoint capability.
+    ///
+    /// Scope entries the catalog doesn't know are given the benefit of the
+    /// doubt; an account is only excluded when its whole scope is known and
+    /// none of it has the capability (e.g. a chat-only key for embeddings).
+    fn has_capability(&self,
+        account: &Account,
+        capability: ModelCapability,
+    ) -> bool {
+        if account.model_scope.is_empty() {
+            return true;
+        }
+        account.model_scope.iter().any(|pattern| {
+            let models = self.catalog.expand(pattern);
+            models.is_empty() || models.iter().any(|m| m.capability == capability)
+        })
+    }
+
     /// Select account with lowest utilization ratio
-    async fn select_least_utilized(&self,
-        candidates: &[&AccountStatus],
-    ) -> &AccountStatus {
-        candidates
-            .iter()
-            .min_by(|a, b| {
-                let util_a = a.usage.utilization_ratio();
-                let util_b = b.usage.utilization_ratio();
-                util_a.partial_cmp(&util_b).unwrap_or(std::cmp::Ordering::Equal)
-            })
+    async fn select_least_utilized<'a>(&self,
+        candidates: &[&'a AccountStatus],
+    ) -> &'a AccountStatus {
+        let lowest = candidates
+            .iter()
+            .map(|s| s.usage.utilization_ratio())
+            .fold(f64::INFINITY, f64::min);
+
+        let tied: Vec<&AccountStatus> = candidates
+            .iter()
+            .filter(|s| s.usage.utilization_ratio() == lowest)
+            .copied()
+            .collect();
+
+        self.break_tie(&tied).unwrap_or(candidates[0])
+    }
+
+    /// Select the account with the lowest cost multiplier, least utilized among equals
+    async fn select_cheapest<'a>(&self,
+        candidates: &[&'a AccountStatus],
+    ) -> &'a AccountStatus {
+        let cheapest = candidates
+            .iter()
+            .map(|s| s.account.cost_multiplier)
+            .fold(f64::INFINITY, f64::min);
+
+        let tied: Vec<&AccountStatus> = candidates
+            .iter()
+            .filter(|s| s.account.cost_multiplier == cheapest)
             .copied()
-            .unwrap_or(candidates[0])
+            .collect();
+
+        self.select_least_utilized(&tied).await
     }
 
     /// Select account via round-robin
-    async fn select_round_robin(&self,
-        candidates: &[&AccountStatus],
-    ) -> &AccountStatus {
+    async fn select_round_robin<'a>(&self,
+        candidates: &[&'a AccountStatus],
+    ) -> &'a AccountStatus {
         let mut index = self.round_robin_index.write().await;
         let selected = candidates[*index % candidates.len()];
         *index = (*index + 1) % candidates.len();
@@ -257,33 +1715,104 @@ impl RoutingEngine {
     }
 
     /// Select account by priority (highest first)
-    async fn select_by_priority(&self,
-        candidates: &[&AccountStatus],
-    ) -> &AccountStatus {
-        candidates
+    async fn select_by_priority<'a>(&self,
+        candidates: &[&'a AccountStatus],
+    ) -> &'a AccountStatus {
+        let highest = candidates
+            .iter()
+            .map(|s| s.effective_priority)
+            .max()
+            .unwrap_or_default();
+
+        let tied: Vec<&AccountStatus> = candidates
+            .iter()
+            .filter(|s| s.effective_priority == highest)
+            .copied()
+            .collect();
+
+        self.break_tie(&tied).unwrap_or(candidates[0])
+    }
+
+    /// Pick one of several equally ranked accounts, preferring the best
+    /// placed region, then using the engine RNG
+    fn break_tie<'a>(&self,
+        tied: &[&'a AccountStatus],
+    ) -> Option<&'a AccountStatus> {
+        let best = tied.iter().map(|s| self.region_rank(&s.account)).min()?;
+        let tied: Vec<&AccountStatus> = tied
             .iter()
-            .max_by_key(|s| s.account.priority)
+            .filter(|s| self.region_rank(&s.account) == best)
             .copied()
-            .unwrap_or(candidates[0])
+            .collect();
+
+        match tied.as_slice() {
+            [] => None,
+            [only] => Some(*only),
+            _ => tied.choose(&mut *self.rng.lock()).copied(),
+        }
+    }
+
+    /// Position of an account's region in the preference list; accounts in
+    /// unlisted or no region rank after every listed one
+    fn region_rank(&self, account: &Account) -> usize {
+        account
+            .region
+            .as_ref()
+            .and_then(|region| self.preferred_regions.iter().position(|r| r == region))
+            .unwrap_or(self.preferred_regions.len())
+    }
+
+    /// Account a session is pinned to, if it is eligible and prefers sticky routing
+    fn preferred_sticky_pin<'a>(&self,
+        candidates: &[&'a AccountStatus],
+        ctx: &RequestContext,
+    ) -> Option<&'a AccountStatus> {
+        let key = self.session_key(ctx)?;
+        let account_id = self.session_map.get(&key)?.account_id;
+
+        let pinned = candidates.iter().copied().find(|s| {
+            s.account.id == account_id
+                && s.account.preferred_strategy == Some(RoutingStrategy::Sticky)
+                && !self.pin_decayed(s, candidates)
+        })?;
+        self.touch_session(&key);
+        Some(pinned)
+    }
+
+    /// Mark a session's pin as just used, keeping it from eviction
+    fn touch_session(&self, key: &SessionKey) {
+        if let Some(mut pin) = self.session_map.get_mut(key) {
+            pin.last_touch = Instant::now();
+        }
     }
 
     /// Select account with session stickiness
-    async fn select_sticky(
+    async fn select_sticky<'a>(
         &self,
-        candidates: &[&AccountStatus],
-        session_id: Option<&str>,
-    ) -> &AccountStatus {
+        candidates: &[&'a AccountStatus],
+        session_key: Option<SessionKey>,
+    ) -> &'a AccountStatus {
         // If we have a session ID, try to stick to the same account
-        if let Some(session) = session_id {
-            if let Some(account_id) = self.session_map.get(session) {
-                if let Some(status) = candidates.iter().find(|s| s.account.id == *account_id) {
+        if let Some(key) = session_key {
+            let pinned = self.session_map.get(&key).map(|pin| pin.account_id);
+            if let Some(account_id) = pinned {
+                if let Some(status) = candidates.iter().find(|s| {
+                    s.account.id == account_id
+                        && !Self::opts_out_of_sticky(&s.account)
+                        && !self.pin_decayed(s, candidates)
+                }) {
+                    self.touch_session(&key);
                     return status;
                 }
             }
 
             // No existing mapping or account unavailable - create new mapping
             let selected = self.select_least_utilized(candidates).await;
-            self.session_map.insert(session.to_string(), selected.account.id);
+            if Self::opts_out_of_sticky(&selected.account) {
+                self.session_map.remove(&key);
+            } else {
+                self.session_map.insert(key, SessionPin::new(selected.account.id));
+            }
             return selected;
         }
 
@@ -291,29 +1820,67 @@ impl RoutingEngine {
         self.select_least_utilized(candidates).await
     }
 
-    /// Build routing reason for decision
+    /// Whether an account prefers a strategy other than sticky routing
+    fn opts_out_of_sticky(account: &Account) -> bool {
+        matches!(account.preferred_strategy, Some(s) if s != RoutingStrategy::Sticky)
+    }
+
+    /// Build the strategy's part of the routing reason for decision
     fn build_reason(
         &self,
+        strategy: RoutingStrategy,
         ctx: &RequestContext,
         status: &AccountStatus,
-    ) -> RoutingReason {
-        match self.strategy {
-            RoutingStrategy::LeastUtilized => RoutingReason::LeastUtilized,
+    ) -> SelectionReason {
+        match strategy {
+            RoutingStrategy::LeastUtilized => SelectionReason::LeastUtilized,
             RoutingStrategy::RoundRobin => {
-                let index = *self.round_robin_index.blocking_read();
-                RoutingReason::RoundRobin { index }
+                // blocking_read panics inside the runtime; another request
+                // may hold the lock, and then the position isn't known
+                let index = self.round_robin_index.try_read().map(|i| *i).ok();
+                SelectionReason::RoundRobin { index }
             }
             RoutingStrategy::Priority => {
-                RoutingReason::Priority { priority: status.account.priority }
+                SelectionReason::Priority { priority: status.effective_priority }
             }
             RoutingStrategy::Sticky => {
                 if let Some(session) = &ctx.session_id {
-                    RoutingReason::Sticky { session_id: session.clone() }
+                    SelectionReason::Sticky { session_id: session.clone() }
                 } else {
-                    RoutingReason::Fallback
+                    SelectionReason::Fallback
                 }
             }
+            RoutingStrategy::CheapestFirst => SelectionReason::CheapestFirst {
+                cost_multiplier: status.account.cost_multiplier,
+            },
+        }
+    }
+
+    /// Rules that shaped routing to the selected account besides the strategy
+    fn applied_layers(&self,
+        ctx: &RequestContext,
+        selected: &AccountStatus,
+        sticky_preference: bool,
+    ) -> Vec<RoutingLayer> {
+        let mut layers = Vec::new();
+
+        let size_rule = self
+            .size_rule_for(ctx)
+            .filter(|rule| rule.account_ids.contains(&selected.account.id));
+        if let Some(rule) = size_rule {
+            layers.push(RoutingLayer::SizeRule {
+                min_tokens: rule.min_tokens,
+                max_tokens: rule.max_tokens,
+            });
+        }
+        if selected.account.standby {
+            layers.push(RoutingLayer::Standby);
         }
+        if sticky_preference {
+            layers.push(RoutingLayer::StickyPreference);
+        }
+
+        layers
     }
 
     /// Report success for an account (resets circuit breaker)
@@ -332,6 +1899,46 @@ impl RoutingEngine {
         state.circuit = CircuitState::Closed;
     }
 
+    /// Remember the latest upstream failure for an account
+    pub fn record_last_error(&self, account_id: AccountId, error: LastError) {
+        self.last_errors.insert(account_id, error);
+    }
+
+    /// Latest upstream failure recorded for an account
+    pub fn last_error(&self, account_id: AccountId) -> Option<LastError> {
+        self.last_errors.get(&account_id).map(|e| e.clone())
+    }
+
+    /// Store the rate-limit headers from the latest upstream response
+    pub fn record_rate_limits(&self, account_id: AccountId, snapshot: RateLimitSnapshot) {
+        if snapshot.is_exhausted() {
+            debug!("Upstream reports account {} rate limit exhausted", account_id);
+        }
+        self.rate_limits.insert(account_id, snapshot);
+    }
+
+    /// Latest rate-limit snapshot for an account
+    pub fn rate_limit(&self, account_id: AccountId) -> Option<RateLimitSnapshot> {
+        self.rate_limits.get(&account_id).map(|s| s.clone())
+    }
+
+    /// Whether the account reported an exhausted quota that has not reset
+    /// yet; checked per request since headers arrive between account updates
+    fn is_rate_limited(&self, account_id: AccountId) -> bool {
+        self.rate_limits
+            .get(&account_id)
+            .is_some_and(|s| s.is_held_at(chrono::Utc::now()))
+    }
+
+    /// Every account's status, with its latest rate-limit snapshot
+    pub async fn account_statuses(&self) -> Vec<AccountStatus> {
+        let mut statuses = self.accounts.read().await.clone();
+        for status in &mut statuses {
+            status.rate_limit = self.rate_limit(status.account.id);
+        }
+        statuses
+    }
+
     /// Report error for an account (may open circuit breaker)
     pub fn report_error(
         &self,
@@ -360,27 +1967,257 @@ impl RoutingEngine {
         }
     }
 
-    /// Get current routing statistics
-    pub async fn get_stats(&self,
-    ) -> RoutingStats {
-        let accounts = self.accounts.read().await;
+    /// Open an account's circuit right away, e.g. after its key was rejected
+    /// or a recovery probe failed
+    pub fn open_circuit(&self, account_id: AccountId) {
+        warn!("Opening circuit breaker for account {}", account_id);
+        let mut state = self.circuit_states
+            .entry(account_id)
+            .or_insert_with(|| AccountRouteState {
+                circuit: CircuitState::Closed,
+                consecutive_errors: 0,
+                last_used: None,
+            });
 
-        RoutingStats {
-            total_accounts: accounts.len(),
-            available_accounts: accounts.iter().filter(|s| s.is_available).count(),
-            strategy: self.strategy,
-            open_circuits: self.circuit_states
-                .iter()
-                .filter(|s| !s.is_available())
-                .count(),
-            active_sessions: self.session_map.len(),
-        }
+        state.consecutive_errors += 1;
+        state.circuit = CircuitState::Open { since: Instant::now() };
     }
 
-    /// Clear session mappings (e.g., on config reload)
+    /// Enabled accounts whose circuit is open, for recovery probing
+    pub async fn open_circuit_accounts(&self) -> Vec<Account> {
+        self.accounts
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.account.enabled)
+            .filter(|s| {
+                self.circuit_states
+                    .get(&s.account.id)
+                    .is_some_and(|state| matches!(state.circuit, CircuitState::Open { .. }))
+            })
+            .map(|s| s.account.clone())
+            .collect()
+    }
+
+    /// Enabled accounts whose open circuit has sat out its cooldown, so the
+    /// next request routed would retry them
+    pub async fn reset_circuit_accounts(&self) -> Vec<Account> {
+        self.accounts
+            .read()
+            .await
+            .iter()
+            .filter(|s| s.account.enabled)
+            .filter(|s| {
+                self.circuit_states.get(&s.account.id).is_some_and(|state| {
+                    matches!(state.circuit, CircuitState::Open { .. }) && state.circuit.can_attempt()
+                })
+            })
+            .map(|s| s.account.clone())
+            .collect()
+    }
+
+    /// Move an open circuit to half-open ahead of a recovery probe
+    pub fn half_open_circuit(&self, account_id: AccountId) {
+        if let Some(mut state) = self.circuit_states.get_mut(&account_id) {
+            if matches!(state.circuit, CircuitState::Open { .. }) {
+                state.circuit = CircuitState::HalfOpen;
+            }
+        }
+    }
+
+    /// Get current routing statistics
+    pub async fn get_stats(&self,
+    ) -> RoutingStats {
+        let accounts = self.accounts.read().await;
+
+        RoutingStats {
+            total_accounts: accounts.len(),
+            available_accounts: accounts.iter().filter(|s| s.is_available).count(),
+            open_circuits: self.circuit_states
+                .iter()
+                .filter(|s| !s.circuit.is_available())
+                .count(),
+        }
+    }
+
+    /// Number of sticky sessions currently pinned to an account
+    pub fn active_sessions(&self) -> usize {
+        self.session_map.len()
+    }
+
+    /// Drop sticky sessions idle for longer than the session TTL, returning
+    /// how many were evicted; a no-op without a TTL
+    pub fn evict_stale_sessions(&self) -> usize {
+        self.evict_sessions_idle_at(Instant::now())
+    }
+
+    /// `evict_stale_sessions` as of `now`
+    fn evict_sessions_idle_at(&self, now: Instant) -> usize {
+        let Some(ttl) = self.session_ttl else {
+            return 0;
+        };
+
+        let before = self.session_map.len();
+        self.session_map
+            .retain(|_, pin| now.saturating_duration_since(pin.last_touch) <= ttl);
+        before - self.session_map.len()
+    }
+
+    /// Circuit states, last use, the round-robin cursor, sticky sessions and
+    /// local spend as JSON, e.g. to keep in store metadata across restarts
+    pub async fn export_state(&self) -> Result<String> {
+        let circuits = self
+            .circuit_states
+            .iter()
+            .map(|entry| {
+                let (circuit, open_for_ms) = match entry.circuit {
+                    CircuitState::Closed => (CircuitPhase::Closed, None),
+                    CircuitState::Open { since } => (CircuitPhase::Open, Some(age_ms(since))),
+                    CircuitState::HalfOpen => (CircuitPhase::HalfOpen, None),
+                };
+                CircuitSnapshot {
+                    account_id: *entry.key(),
+                    circuit,
+                    open_for_ms,
+                    consecutive_errors: entry.consecutive_errors,
+                    last_used_ago_ms: entry.last_used.map(age_ms),
+                }
+            })
+            .collect();
+        let sessions = self
+            .session_map
+            .iter()
+            .map(|entry| SessionSnapshot {
+                session_id: entry.key().0.clone(),
+                family: entry.key().1.clone(),
+                account_id: entry.account_id,
+                idle_ms: age_ms(entry.last_touch),
+            })
+            .collect();
+        let local_costs = self
+            .local_costs
+            .iter()
+            .map(|entry| LocalCostSnapshot {
+                account_id: *entry.key(),
+                year: entry.month.0,
+                month: entry.month.1,
+                cost: entry.cost,
+            })
+            .collect();
+
+        let state = EngineState {
+            round_robin_index: *self.round_robin_index.read().await,
+            circuits,
+            sessions,
+            local_costs,
+        };
+        serde_json::to_string(&state).context("Failed to serialize routing state")
+    }
+
+    /// Restore state saved by `export_state`, replacing the engine's own.
+    ///
+    /// Import before `update_accounts` so account availability reflects the
+    /// restored circuits.
+    pub async fn import_state(&self, json: &str) -> Result<()> {
+        let state: EngineState = serde_json::from_str(json).context("Invalid routing state")?;
+
+        self.circuit_states.clear();
+        for circuit in state.circuits {
+            let phase = match circuit.circuit {
+                CircuitPhase::Closed => CircuitState::Closed,
+                CircuitPhase::Open => CircuitState::Open {
+                    since: instant_ago(circuit.open_for_ms.unwrap_or(0)),
+                },
+                CircuitPhase::HalfOpen => CircuitState::HalfOpen,
+            };
+            self.circuit_states.insert(
+                circuit.account_id,
+                AccountRouteState {
+                    circuit: phase,
+                    consecutive_errors: circuit.consecutive_errors,
+                    last_used: circuit.last_used_ago_ms.map(instant_ago),
+                },
+            );
+        }
+
+        self.session_map.clear();
+        for session in state.sessions {
+            self.session_map.insert(
+                (session.session_id, session.family),
+                SessionPin {
+                    account_id: session.account_id,
+                    last_touch: instant_ago(session.idle_ms),
+                },
+            );
+        }
+
+        self.local_costs.clear();
+        for cost in state.local_costs {
+            self.local_costs.insert(
+                cost.account_id,
+                LocalCost { month: (cost.year, cost.month), cost: cost.cost },
+            );
+        }
+
+        *self.round_robin_index.write().await = state.round_robin_index;
+        self.decision_cache.clear();
+        Ok(())
+    }
+
+    /// Clear session mappings (e.g., on config reload), returning how many were dropped
     pub fn clear_sessions(&self,
-    ) {
+    ) -> usize {
+        let cleared = self.session_map.len();
         self.session_map.clear();
+        cleared
+    }
+
+    /// Sticky session pins, most recently used first
+    pub async fn sessions(&self) -> Vec<SessionInfo> {
+        let accounts = self.accounts.read().await;
+        let mut pins: Vec<(SessionKey, SessionPin)> = self
+            .session_map
+            .iter()
+            .map(|e| (e.key().clone(), *e.value()))
+            .collect();
+        pins.sort_by_key(|(_, pin)| std::cmp::Reverse(pin.last_touch));
+
+        let ids = pins.iter().map(|((id, _), _)| id.as_str());
+        pins.iter()
+            .map(|((id, family), pin)| SessionInfo {
+                session_prefix: unique_prefix(id, ids.clone()),
+                family: family.clone(),
+                account_id: pin.account_id,
+                account_label: accounts
+                    .iter()
+                    .find(|s| s.account.id == pin.account_id)
+                    .map(|s| s.account.label.clone())
+                    .unwrap_or_else(|| "(removed)".to_string()),
+                idle_seconds: pin.last_touch.elapsed().as_secs(),
+            })
+            .collect()
+    }
+
+    /// Drop every pin of the session whose hash is, or uniquely starts with,
+    /// `session`, returning the full session hash
+    pub fn revoke_session(&self, session: &str) -> Result<String> {
+        let mut matches: Vec<String> = self
+            .session_map
+            .iter()
+            .map(|e| e.key().0.clone())
+            .filter(|id| id.starts_with(session))
+            .collect();
+        matches.sort();
+        matches.dedup();
+
+        let id = match matches.as_slice() {
+            _ if matches.iter().any(|id| id == session) => session.to_string(),
+            [id] => id.clone(),
+            [] => anyhow::bail!("No sticky session matches {}", session),
+            _ => anyhow::bail!("{} sticky sessions match {}; use a longer prefix", matches.len(), session),
+        };
+        self.session_map.retain(|(pinned, _), _| *pinned != id);
+        Ok(id)
     }
 }
 
@@ -389,9 +2226,7 @@ impl RoutingEngine {
 pub struct RoutingStats {
     pub total_accounts: usize,
     pub available_accounts: usize,
-    pub strategy: RoutingStrategy,
     pub open_circuits: usize,
-    pub active_sessions: usize,
 }
 
 #[cfg(test)]
@@ -412,6 +2247,20 @@ mod tests {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             last_used: None,
+            disabled_reason: None,
+            disabled_at: None,
+            preferred_strategy: None,
+            tags: vec![],
+            cost_multiplier: 1.0,
+            no_upstream_auth: false,
+            schedule: None,
+            standby: false,
+            tpm_limit: None,
+            region: None,
+            allowed_paths: vec![],
+            cost_center: None,
+            pinned: false,
+            locked: false,
         }
     }
 
@@ -438,6 +2287,7 @@ mod tests {
             daily_usage: 50.0,
             monthly_usage: 50.0,
             timestamp: chrono::Utc::now(),
+            observed_at: None,
         });
         usage_map.insert(id2, UsageSnapshot {
             account_id: id2,
@@ -449,6 +2299,7 @@ mod tests {
             daily_usage: 10.0,
             monthly_usage: 10.0,
             timestamp: chrono::Utc::now(),
+            observed_at: None,
         });
 
         engine.update_accounts(accounts, usage_map).await;
@@ -460,6 +2311,226 @@ mod tests {
         assert_eq!(decision.account_id, id2);
     }
 
+    #[tokio::test]
+    async fn test_rank_candidates_least_utilized() {
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+
+        let id1 = uuid::Uuid::new_v4();
+        let id2 = uuid::Uuid::new_v4();
+        let id3 = uuid::Uuid::new_v4();
+
+        let accounts = vec![
+            create_test_account(id1, 1, true),
+            create_test_account(id2, 2, true),
+            create_test_account(id3, 3, true),
+        ];
+
+        let mut usage_map = std::collections::HashMap::new();
+        for (id, used) in [(id1, 50.0), (id2, 10.0), (id3, 80.0)] {
+            let mut usage = UsageSnapshot::new(id);
+            usage.hard_limit = Some(100.0);
+            usage.monthly_usage = used;
+            usage_map.insert(id, usage);
+        }
+
+        engine.update_accounts(accounts, usage_map).await;
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+        let ranked = engine.rank_candidates(&ctx).await;
+
+        let order: Vec<_> = ranked.iter().map(|(id, _)| *id).collect();
+        assert_eq!(order, vec![id2, id1, id3]);
+        assert_eq!(ranked[0].1, 0.1);
+        assert_eq!(ranked[2].1, 0.8);
+
+        // Ranking must not affect the actual selection
+        let decision = engine.resolve_account(&ctx).await.unwrap();
+        assert_eq!(decision.account_id, id2);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_tie_breaks_are_reproducible() {
+        let accounts: Vec<Account> = (0..4)
+            .map(|_| create_test_account(uuid::Uuid::new_v4(), 0, true))
+            .collect();
+
+        let mut sequences = Vec::new();
+        for _ in 0..2 {
+            let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized).with_seed(Some(42));
+            engine
+                .update_accounts(accounts.clone(), std::collections::HashMap::new())
+                .await;
+
+            let ctx = RequestContext::new("gpt-4".to_string());
+            let mut picks = Vec::new();
+            for _ in 0..20 {
+                picks.push(engine.resolve_account(&ctx).await.unwrap().account_id);
+            }
+            sequences.push(picks);
+        }
+
+        assert_eq!(sequences[0], sequences[1]);
+
+        // All accounts are tied, so the RNG should actually spread picks
+        let distinct: std::collections::HashSet<_> = sequences[0].iter().collect();
+        assert!(distinct.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_usage_policies() {
+        let stale_id = uuid::Uuid::new_v4();
+        let fresh_id = uuid::Uuid::new_v4();
+
+        let accounts = vec![
+            create_test_account(stale_id, 1, true),
+            create_test_account(fresh_id, 1, true),
+        ];
+
+        // The stale account looks idle, but its data is two days old
+        let mut stale = UsageSnapshot::new(stale_id);
+        stale.hard_limit = Some(100.0);
+        stale.monthly_usage = 5.0;
+        stale.timestamp = chrono::Utc::now() - chrono::Duration::days(2);
+
+        let mut fresh = UsageSnapshot::new(fresh_id);
+        fresh.hard_limit = Some(100.0);
+        fresh.monthly_usage = 60.0;
+
+        let mut usage_map = std::collections::HashMap::new();
+        usage_map.insert(stale_id, stale);
+        usage_map.insert(fresh_id, fresh);
+
+        let max_age = Some(Duration::from_secs(3600));
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_usage_max_age(max_age, StaleUsagePolicy::Deprioritize);
+        engine.update_accounts(accounts.clone(), usage_map.clone()).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, fresh_id);
+        assert_eq!(engine.get_stats().await.available_accounts, 2);
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_usage_max_age(max_age, StaleUsagePolicy::Disable);
+        engine.update_accounts(accounts, usage_map).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, fresh_id);
+
+        let accounts = engine.accounts.read().await;
+        let stale_status = accounts.iter().find(|s| s.account.id == stale_id).unwrap();
+        assert_eq!(engine.exclusion_reason(stale_status, &ctx).as_deref(), Some("Usage data stale"));
+    }
+
+    #[tokio::test]
+    async fn test_usage_goes_stale_between_updates() {
+        let aging_id = uuid::Uuid::new_v4();
+        let fresh_id = uuid::Uuid::new_v4();
+        let unpolled_id = uuid::Uuid::new_v4();
+        let accounts = vec![
+            create_test_account(aging_id, 1, true),
+            create_test_account(fresh_id, 1, true),
+            create_test_account(unpolled_id, 1, true),
+        ];
+
+        // The aging snapshot turns an hour old a moment after the update
+        let max_age = chrono::Duration::hours(1);
+        let mut aging = UsageSnapshot::new(aging_id);
+        aging.hard_limit = Some(100.0);
+        aging.monthly_usage = 5.0;
+        aging.timestamp = chrono::Utc::now() - max_age + chrono::Duration::milliseconds(300);
+        let mut fresh = UsageSnapshot::new(fresh_id);
+        fresh.hard_limit = Some(100.0);
+        fresh.monthly_usage = 60.0;
+        let usage_map = [(aging_id, aging), (fresh_id, fresh)].into_iter().collect();
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_usage_max_age(max_age.to_std().ok(), StaleUsagePolicy::Disable);
+        engine.update_accounts(accounts, usage_map).await;
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        // The unpolled account has no usage to trust, so it never wins
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, aging_id);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, fresh_id);
+
+        let accounts = engine.accounts.read().await;
+        let unpolled = accounts.iter().find(|s| s.account.id == unpolled_id).unwrap();
+        assert_eq!(engine.exclusion_reason(unpolled, &ctx).as_deref(), Some("Usage data stale"));
+    }
+
+    #[tokio::test]
+    async fn test_soft_limit_policies() {
+        let ids = [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()];
+        let accounts: Vec<Account> = ids.iter().map(|id| create_test_account(*id, 1, true)).collect();
+
+        // Both accounts are past a $50 soft limit; the second one further,
+        // though it is the less utilized against its hard limit
+        let mut usage_map = std::collections::HashMap::new();
+        for (id, (used, hard)) in ids.iter().zip([(60.0, 100.0), (90.0, 1000.0)]) {
+            let mut usage = UsageSnapshot::new(*id);
+            usage.hard_limit = Some(hard);
+            usage.soft_limit = Some(50.0);
+            usage.monthly_usage = used;
+            usage_map.insert(*id, usage);
+        }
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+        engine.update_accounts(accounts.clone(), usage_map.clone()).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, ids[0]);
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_soft_limit_policy(SoftLimitPolicy::Allow);
+        engine.update_accounts(accounts.clone(), usage_map.clone()).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, ids[1]);
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_soft_limit_policy(SoftLimitPolicy::Reject);
+        engine.update_accounts(accounts.clone(), usage_map.clone()).await;
+        assert!(engine.resolve_account(&ctx).await.is_err());
+        let trace = engine.explain(&ctx).await;
+        assert!(trace.excluded.iter().all(|e| e.reason == "Past soft limit"));
+
+        // An account under its soft limit wins regardless of the policy
+        usage_map.get_mut(&ids[1]).unwrap().soft_limit = None;
+        engine.update_accounts(accounts, usage_map).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, ids[1]);
+    }
+
+    #[tokio::test]
+    async fn test_routing_matrix_marks_out_of_scope_models() {
+        let open_id = uuid::Uuid::new_v4();
+        let scoped_id = uuid::Uuid::new_v4();
+        let mut scoped = create_test_account(scoped_id, 1, true);
+        scoped.model_scope = vec!["gpt-4*".to_string()];
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+        engine
+            .update_accounts(
+                vec![create_test_account(open_id, 1, true), scoped],
+                std::collections::HashMap::new(),
+            )
+            .await;
+
+        let matrix = engine.routing_matrix().await;
+        assert_eq!(matrix.rows.len(), engine.catalog().models().len());
+
+        let cell = |model: &str, id: AccountId| {
+            let row = matrix.rows.iter().find(|r| r.model == model).unwrap();
+            row.cells.iter().find(|c| c.account_id == id).unwrap().clone()
+        };
+
+        let in_scope = cell("gpt-4", scoped_id);
+        assert!(in_scope.eligible);
+        assert_eq!(in_scope.metric, Some(0.0));
+
+        let out_of_scope = cell("gpt-3.5-turbo", scoped_id);
+        assert!(!out_of_scope.eligible);
+        assert_eq!(out_of_scope.metric, None);
+        assert!(out_of_scope.reason.unwrap().contains("outside the account's scope"));
+
+        assert!(cell("gpt-3.5-turbo", open_id).eligible);
+    }
+
     #[tokio::test]
     async fn test_priority_routing() {
         let engine = RoutingEngine::new(RoutingStrategy::Priority);
@@ -482,6 +2553,94 @@ mod tests {
         assert_eq!(decision.account_id, id2);
     }
 
+    #[tokio::test]
+    async fn test_cheapest_first_accounts_for_cost_multiplier() {
+        let base_id = uuid::Uuid::new_v4();
+        let fine_tune_id = uuid::Uuid::new_v4();
+        let accounts = vec![
+            create_test_account(fine_tune_id, 1, true).with_cost_multiplier(2.0),
+            create_test_account(base_id, 1, true),
+        ];
+
+        // The fine-tune account is less utilized, but costs twice as much
+        let mut usage_map = std::collections::HashMap::new();
+        for (id, monthly_usage) in [(fine_tune_id, 10.0), (base_id, 40.0)] {
+            let mut usage = UsageSnapshot::new(id);
+            usage.monthly_usage = monthly_usage;
+            usage.hard_limit = Some(100.0);
+            usage_map.insert(id, usage);
+        }
+
+        let engine = RoutingEngine::new(RoutingStrategy::CheapestFirst);
+        engine.update_accounts(accounts, usage_map).await;
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+        let decision = engine.resolve_account(&ctx).await.unwrap();
+        assert_eq!(decision.account_id, base_id);
+        assert_eq!(decision.cost_multiplier, 1.0);
+        assert!(matches!(
+            decision.reason.selection,
+            SelectionReason::CheapestFirst { cost_multiplier } if cost_multiplier == 1.0
+        ));
+
+        let ranked = engine.rank_candidates(&ctx).await;
+        assert_eq!(ranked, vec![(base_id, 1.0), (fine_tune_id, 2.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_update_accounts_diffs_and_keeps_state() {
+        let steady_id = uuid::Uuid::new_v4();
+        let dropped_id = uuid::Uuid::new_v4();
+        let new_id = uuid::Uuid::new_v4();
+        let steady = create_test_account(steady_id, 1, true);
+        let dropped = create_test_account(dropped_id, 1, true);
+        let added = create_test_account(new_id, 1, true);
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+
+        let diff = engine
+            .update_accounts(vec![steady.clone(), dropped.clone()], std::collections::HashMap::new())
+            .await;
+        assert_eq!(diff.added, vec![steady_id, dropped_id]);
+
+        for _ in 0..3 {
+            engine.report_error(steady_id, true);
+        }
+        engine.record_latency(dropped_id, Duration::from_millis(50));
+        let ctx = RequestContext::new("gpt-4".to_string()).with_session("s".to_string());
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, dropped_id);
+
+        let renamed = {
+            let mut account = steady.clone();
+            account.label = "Renamed".to_string();
+            account
+        };
+        let diff = engine
+            .update_accounts(
+                vec![steady.clone(), added.clone()],
+                std::collections::HashMap::new(),
+            )
+            .await;
+        assert_eq!(diff, AccountSetDiff {
+            added: vec![new_id],
+            removed: vec![dropped_id],
+            changed: vec![],
+        });
+
+        // The untouched account's circuit is still open; the dropped account's state is gone
+        assert_eq!(engine.get_stats().await.open_circuits, 1);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, new_id);
+        assert_eq!(engine.latency(dropped_id), None);
+
+        let diff = engine
+            .update_accounts(
+                vec![renamed, added],
+                std::collections::HashMap::new(),
+            )
+            .await;
+        assert_eq!(diff.changed, vec![steady_id]);
+        assert_eq!(engine.get_stats().await.open_circuits, 1);
+    }
+
     #[tokio::test]
     async fn test_disabled_account_filtering() {
         let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
@@ -503,4 +2662,982 @@ mod tests {
         // Should only select enabled account
         assert_eq!(decision.account_id, id2);
     }
+
+    #[tokio::test]
+    async fn test_size_rules_route_to_tiers() {
+        let small_id = uuid::Uuid::new_v4();
+        let large_id = uuid::Uuid::new_v4();
+        let other_id = uuid::Uuid::new_v4();
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized).with_size_rules(vec![
+            SizeRule {
+                min_tokens: None,
+                max_tokens: Some(4000),
+                account_ids: vec![small_id],
+            },
+            SizeRule {
+                min_tokens: Some(4000),
+                max_tokens: None,
+                account_ids: vec![large_id],
+            },
+        ]);
+
+        let accounts = vec![
+            create_test_account(small_id, 1, true),
+            create_test_account(large_id, 2, true),
+            create_test_account(other_id, 3, true),
+        ];
+        engine.update_accounts(accounts, std::collections::HashMap::new()).await;
+
+        let small = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(200);
+        assert_eq!(engine.resolve_account(&small).await.unwrap().account_id, small_id);
+
+        let large = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(50_000);
+        assert_eq!(engine.resolve_account(&large).await.unwrap().account_id, large_id);
+
+        // Without an estimate no rule applies
+        let unknown = RequestContext::new("gpt-4".to_string());
+        assert_eq!(engine.rank_candidates(&unknown).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reason_records_size_rule_and_strategy() {
+        let small_id = uuid::Uuid::new_v4();
+        let rule = SizeRule {
+            min_tokens: None,
+            max_tokens: Some(4000),
+            account_ids: vec![small_id],
+        };
+        let engine = RoutingEngine::new(RoutingStrategy::Priority).with_size_rules(vec![rule]);
+        let accounts = vec![
+            create_test_account(small_id, 1, true),
+            create_test_account(uuid::Uuid::new_v4(), 9, true),
+        ];
+        engine.update_accounts(accounts, std::collections::HashMap::new()).await;
+
+        let small = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(200);
+        let decision = engine.resolve_account(&small).await.unwrap();
+        assert_eq!(decision.account_id, small_id);
+        assert_eq!(
+            decision.reason,
+            RoutingReason {
+                strategy: RoutingStrategy::Priority,
+                selection: SelectionReason::Priority { priority: 1 },
+                layers: vec![RoutingLayer::SizeRule { min_tokens: None, max_tokens: Some(4000) }],
+            }
+        );
+        assert_eq!(decision.reason.to_string(), "priority 1 + size rule ..4000");
+
+        let json = serde_json::to_value(&decision.reason).unwrap();
+        assert_eq!(json["selection"]["kind"], "priority");
+        assert_eq!(json["layers"][0]["kind"], "size_rule");
+
+        // Without a matching rule only the strategy is recorded
+        let plain = RequestContext::new("gpt-4".to_string());
+        assert!(engine.resolve_account(&plain).await.unwrap().reason.layers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auto_balance_shifts_effective_priority() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority).with_auto_balance(true);
+
+        let busy = uuid::Uuid::new_v4();
+        let idle = uuid::Uuid::new_v4();
+        let accounts = vec![
+            create_test_account(busy, 10, true),
+            create_test_account(idle, 5, true),
+        ];
+
+        let usage_with = |busy_used: f64| {
+            let mut usage_map = std::collections::HashMap::new();
+            for (id, used) in [(busy, busy_used), (idle, 0.0)] {
+                let mut usage = UsageSnapshot::new(id);
+                usage.hard_limit = Some(100.0);
+                usage.monthly_usage = used;
+                usage_map.insert(id, usage);
+            }
+            usage_map
+        };
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        // Lightly used: base priority still wins
+        engine.update_accounts(accounts.clone(), usage_with(20.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, busy);
+
+        // Near its limit: demoted below the idle account
+        engine.update_accounts(accounts.clone(), usage_with(90.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, idle);
+
+        let statuses = engine.accounts.read().await;
+        let busy_status = statuses.iter().find(|s| s.account.id == busy).unwrap();
+        assert_eq!(busy_status.effective_priority, 1);
+        assert_eq!(busy_status.account.priority, 10);
+        assert_eq!(accounts[0].priority, 10);
+    }
+
+    #[tokio::test]
+    async fn test_failover_uses_configured_strategy() {
+        let failed_id = uuid::Uuid::new_v4();
+        let low_id = uuid::Uuid::new_v4();
+        let high_id = uuid::Uuid::new_v4();
+
+        let accounts = vec![
+            create_test_account(failed_id, 1, true),
+            create_test_account(low_id, 2, true),
+            create_test_account(high_id, 9, true),
+        ];
+
+        // The high priority account is busier, so least-utilized avoids it
+        let mut usage_map = std::collections::HashMap::new();
+        for (id, monthly_usage) in [(failed_id, 0.0), (low_id, 10.0), (high_id, 50.0)] {
+            let mut usage = UsageSnapshot::new(id);
+            usage.monthly_usage = monthly_usage;
+            usage.hard_limit = Some(100.0);
+            usage_map.insert(id, usage);
+        }
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+        engine.update_accounts(accounts.clone(), usage_map.clone()).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, failed_id);
+        let retry = engine.resolve_failover(&ctx, &[failed_id]).await.unwrap();
+        assert_eq!(retry.account_id, low_id);
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_failover_strategy(Some(RoutingStrategy::Priority));
+        engine.update_accounts(accounts, usage_map).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, failed_id);
+        let retry = engine.resolve_failover(&ctx, &[failed_id]).await.unwrap();
+        assert_eq!(retry.account_id, high_id);
+        assert_eq!(retry.reason.strategy, RoutingStrategy::Priority);
+        assert_eq!(retry.reason.layers, vec![RoutingLayer::Failover { failed: 1 }]);
+
+        assert!(engine.resolve_failover(&ctx, &[failed_id, low_id, high_id]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_capability_filtering_and_served_models() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+
+        let chat_id = uuid::Uuid::new_v4();
+        let embed_id = uuid::Uuid::new_v4();
+
+        let mut chat = create_test_account(chat_id, 10, true);
+        chat.model_scope = vec!["gpt-4*".to_string()];
+        let mut embed = create_test_account(embed_id, 1, true);
+        embed.model_scope = vec!["text-embedding-3*".to_string()];
+
+        engine
+            .update_accounts(vec![chat, embed], std::collections::HashMap::new())
+            .await;
+
+        // Without a capability the model name alone decides
+        let ctx = RequestContext::new("gpt-4o".to_string());
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, chat_id);
+
+        let ctx = RequestContext::new("text-embedding-3-small".to_string())
+            .with_capability(Some(ModelCapability::Embedding));
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, embed_id);
+
+        // A chat-only account is never picked for embeddings
+        let ctx = RequestContext::new("gpt-4-embedding".to_string())
+            .with_capability(Some(ModelCapability::Embedding));
+        assert!(engine.resolve_account(&ctx).await.is_err());
+
+        let served: Vec<String> = engine.served_models().await.into_iter().map(|m| m.id).collect();
+        assert_eq!(
+            served,
+            vec![
+                "gpt-4o",
+                "gpt-4o-mini",
+                "gpt-4-turbo",
+                "gpt-4",
+                "text-embedding-3-small",
+                "text-embedding-3-large",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_spend_cap_excludes_account_before_next_poll() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+
+        let capped_id = uuid::Uuid::new_v4();
+        let backup_id = uuid::Uuid::new_v4();
+
+        let mut capped = create_test_account(capped_id, 10, true);
+        capped.monthly_limit = Some(5.0);
+        let backup = create_test_account(backup_id, 1, true);
+
+        // OpenAI still reports no usage for the capped account
+        engine
+            .update_accounts(vec![capped, backup], std::collections::HashMap::new())
+            .await;
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, capped_id);
+
+        engine.record_local_cost(capped_id, 3.0);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, capped_id);
+
+        engine.record_local_cost(capped_id, 2.5);
+        assert_eq!(engine.local_cost(capped_id), 5.5);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, backup_id);
+    }
+
+    #[tokio::test]
+    async fn test_sticky_scope_per_model_family() {
+        let chat_id = uuid::Uuid::new_v4();
+        let embed_id = uuid::Uuid::new_v4();
+        let any_id = uuid::Uuid::new_v4();
+
+        let mut chat = create_test_account(chat_id, 1, true);
+        chat.model_scope = vec!["gpt-4*".to_string()];
+        let mut embed = create_test_account(embed_id, 1, true);
+        embed.model_scope = vec!["text-embedding-*".to_string()];
+        let any = create_test_account(any_id, 1, true);
+        let accounts = vec![chat, embed, any];
+
+        let mut usage_map = std::collections::HashMap::new();
+        for (id, monthly_usage) in [(embed_id, 10.0), (any_id, 20.0), (chat_id, 30.0)] {
+            let mut usage = UsageSnapshot::new(id);
+            usage.monthly_usage = monthly_usage;
+            usage.hard_limit = Some(100.0);
+            usage_map.insert(id, usage);
+        }
+
+        async fn route(engine: &RoutingEngine, model: &str) -> AccountId {
+            let ctx = RequestContext::new(model.to_string())
+                .with_session("conversation".to_string());
+            engine.resolve_account(&ctx).await.unwrap().account_id
+        }
+
+        // Global: the chat request re-pins the session away from the embedding account
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+        engine.update_accounts(accounts.clone(), usage_map.clone()).await;
+        assert_eq!(route(&engine, "text-embedding-3-small").await, embed_id);
+        assert_eq!(route(&engine, "gpt-4o").await, any_id);
+        assert_eq!(route(&engine, "text-embedding-3-small").await, any_id);
+        assert_eq!(engine.active_sessions(), 1);
+
+        // Per model: each family keeps its own pin
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky)
+            .with_sticky_scope(StickyScope::PerModel);
+        engine.update_accounts(accounts, usage_map).await;
+        assert_eq!(route(&engine, "text-embedding-3-small").await, embed_id);
+        assert_eq!(route(&engine, "gpt-4o").await, any_id);
+        assert_eq!(route(&engine, "text-embedding-3-large").await, embed_id);
+        assert_eq!(route(&engine, "gpt-4o-mini").await, any_id);
+        assert_eq!(engine.active_sessions(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sticky_decay_migrates_overloaded_session() {
+        let pinned_id = uuid::Uuid::new_v4();
+        let other_id = uuid::Uuid::new_v4();
+        let accounts = vec![
+            create_test_account(pinned_id, 1, true),
+            create_test_account(other_id, 1, true),
+        ];
+
+        let usage_with = |pinned_usage: f64| {
+            let mut usage_map = std::collections::HashMap::new();
+            for (id, monthly_usage) in [(pinned_id, pinned_usage), (other_id, 30.0)] {
+                let mut usage = UsageSnapshot::new(id);
+                usage.monthly_usage = monthly_usage;
+                usage.hard_limit = Some(100.0);
+                usage_map.insert(id, usage);
+            }
+            usage_map
+        };
+
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky).with_sticky_decay(Some(0.25));
+        let ctx = RequestContext::new("gpt-4".to_string()).with_session("conversation".to_string());
+
+        engine.update_accounts(accounts.clone(), usage_with(10.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, pinned_id);
+
+        // Within the margin the session stays put
+        engine.update_accounts(accounts.clone(), usage_with(50.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, pinned_id);
+
+        // Past the margin it moves, and the new pin holds
+        engine.update_accounts(accounts.clone(), usage_with(60.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, other_id);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, other_id);
+
+        // Without a margin the pin never decays
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+        engine.update_accounts(accounts.clone(), usage_with(10.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, pinned_id);
+        engine.update_accounts(accounts, usage_with(90.0)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, pinned_id);
+    }
+
+    #[tokio::test]
+    async fn test_preferred_strategy_overrides_engine_strategy() {
+        let sticky_id = uuid::Uuid::new_v4();
+        let plain_id = uuid::Uuid::new_v4();
+
+        let mut sticky = create_test_account(sticky_id, 1, true);
+        sticky.preferred_strategy = Some(RoutingStrategy::Sticky);
+        let plain = create_test_account(plain_id, 1, true);
+        let accounts = vec![sticky, plain];
+
+        let usage_with = |sticky_usage: f64| {
+            let mut usage_map = std::collections::HashMap::new();
+            for (id, monthly_usage) in [(sticky_id, sticky_usage), (plain_id, 50.0)] {
+                let mut usage = UsageSnapshot::new(id);
+                usage.monthly_usage = monthly_usage;
+                usage.hard_limit = Some(100.0);
+                usage_map.insert(id, usage);
+            }
+            usage_map
+        };
+
+        let session_ctx =
+            RequestContext::new("gpt-4".to_string()).with_session("fine-tune".to_string());
+        let plain_ctx = RequestContext::new("gpt-4".to_string());
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+        engine.update_accounts(accounts.clone(), usage_with(10.0)).await;
+        assert_eq!(engine.resolve_account(&session_ctx).await.unwrap().account_id, sticky_id);
+        assert_eq!(engine.active_sessions(), 1);
+
+        // Now busier than the alternative, but the session stays pinned
+        engine.update_accounts(accounts.clone(), usage_with(90.0)).await;
+        let decision = engine.resolve_account(&session_ctx).await.unwrap();
+        assert_eq!(decision.account_id, sticky_id);
+        assert!(matches!(decision.reason.selection, SelectionReason::Sticky { .. }));
+        assert_eq!(engine.resolve_account(&plain_ctx).await.unwrap().account_id, plain_id);
+
+        // An account preferring another strategy is never pinned under Sticky
+        let mut accounts = accounts;
+        accounts[0].preferred_strategy = Some(RoutingStrategy::LeastUtilized);
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+        engine.update_accounts(accounts.clone(), usage_with(10.0)).await;
+        assert_eq!(engine.resolve_account(&session_ctx).await.unwrap().account_id, sticky_id);
+        assert_eq!(engine.active_sessions(), 0);
+        engine.update_accounts(accounts, usage_with(90.0)).await;
+        assert_eq!(engine.resolve_account(&session_ctx).await.unwrap().account_id, plain_id);
+        assert_eq!(engine.active_sessions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_standby_promoted_when_primaries_exhausted_and_demoted_on_recovery() {
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+
+        let primary = uuid::Uuid::new_v4();
+        let standby = uuid::Uuid::new_v4();
+        let mut standby_account = create_test_account(standby, 0, true);
+        standby_account.standby = true;
+        engine
+            .update_accounts(
+                vec![create_test_account(primary, 0, true), standby_account],
+                std::collections::HashMap::new(),
+            )
+            .await;
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, primary);
+        let trace = engine.explain(&ctx).await;
+        assert_eq!(trace.excluded.len(), 1);
+        assert!(trace.excluded[0].reason.starts_with("Standby"));
+        assert_eq!(engine.standby_accounts().await.len(), 1);
+
+        // Primary tier fully unavailable: the standby takes over
+        for _ in 0..3 {
+            engine.report_error(primary, true);
+        }
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, standby);
+
+        // Primary recovers: traffic leaves the standby again
+        engine.report_success(primary);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, primary);
+    }
+
+    #[tokio::test]
+    async fn test_tpm_limit_spills_over_and_reconciles() {
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+
+        let limited = uuid::Uuid::new_v4();
+        let spare = uuid::Uuid::new_v4();
+        let mut limited_account = create_test_account(limited, 10, true);
+        limited_account.tpm_limit = Some(1000);
+        engine
+            .update_accounts(
+                vec![limited_account, create_test_account(spare, 1, true)],
+                std::collections::HashMap::new(),
+            )
+            .await;
+
+        let ctx = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(400);
+        let first = engine.resolve_account(&ctx).await.unwrap();
+        let second = engine.resolve_account(&ctx).await.unwrap();
+        assert_eq!(first.account_id, limited);
+        assert_eq!(second.account_id, limited);
+        assert_eq!(engine.tokens_last_minute(limited), 800);
+
+        // A third request would reach 1200 tokens this minute
+        let third = engine.resolve_account(&ctx).await.unwrap();
+        assert_eq!(third.account_id, spare);
+        assert!(third.token_charge.is_none());
+        assert_eq!(engine.tokens_last_minute(spare), 0);
+
+        // One response used far fewer tokens than estimated, the other failed
+        engine.reconcile_tokens(limited, first.token_charge, 100);
+        engine.release_tokens(second.token_charge);
+        assert_eq!(engine.tokens_last_minute(limited), 100);
+
+        // Corrections replace the charge in place rather than adding entries
+        engine.reconcile_tokens(limited, first.token_charge, 300);
+        assert_eq!(engine.tokens_last_minute(limited), 300);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, limited);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_reports_distribution() {
+        let ids: Vec<uuid::Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        let mut accounts: Vec<Account> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| create_test_account(*id, i as i32, true))
+            .collect();
+        accounts[0].model_scope = vec!["gpt-3.5-turbo".to_string()];
+
+        let engine = RoutingEngine::new(RoutingStrategy::RoundRobin);
+        engine
+            .update_accounts(accounts.clone(), std::collections::HashMap::new())
+            .await;
+        let report = engine.simulate("gpt-4", 300).await;
+        let counts: Vec<usize> = report.accounts.iter().map(|a| a.requests).collect();
+        // The first account is out of scope for the model
+        assert_eq!(counts, vec![0, 150, 150]);
+        assert_eq!(report.unrouted, 0);
+        assert!((report.variance() - 5000.0).abs() < 1e-9);
+        assert!((report.gini() - 1.0 / 3.0).abs() < 1e-9);
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        engine.update_accounts(accounts, std::collections::HashMap::new()).await;
+        let report = engine.simulate("gpt-4", 300).await;
+        let counts: Vec<usize> = report.accounts.iter().map(|a| a.requests).collect();
+        assert_eq!(counts, vec![0, 0, 300]);
+        assert!((report.gini() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_experiment_diverts_configured_share() {
+        let ids: Vec<uuid::Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        let mut accounts: Vec<Account> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| create_test_account(*id, i as i32, true))
+            .collect();
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_seed(Some(42))
+            .with_experiment(Some(ExperimentConfig { account_id: ids[0], percent: 10.0 }));
+        engine
+            .update_accounts(accounts.clone(), std::collections::HashMap::new())
+            .await;
+        let report = engine.simulate("gpt-4", 1000).await;
+        let counts: Vec<usize> = report.accounts.iter().map(|a| a.requests).collect();
+        // Priority would never pick the lowest-priority account on its own
+        assert!((70..=130).contains(&counts[0]), "diverted {}", counts[0]);
+        assert_eq!(counts[0] + counts[2], 1000);
+
+        // An unavailable experiment account falls back to normal routing
+        accounts[0].enabled = false;
+        engine.update_accounts(accounts, std::collections::HashMap::new()).await;
+        let report = engine.simulate("gpt-4", 200).await;
+        let counts: Vec<usize> = report.accounts.iter().map(|a| a.requests).collect();
+        assert_eq!(counts, vec![0, 0, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_many_matches_individual_resolution() {
+        let ids: Vec<uuid::Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        let mut accounts: Vec<Account> = ids
+            .iter()
+            .map(|id| create_test_account(*id, 1, true))
+            .collect();
+        accounts[2].model_scope = vec!["gpt-3.5-turbo".to_string()];
+
+        let ctxs: Vec<RequestContext> = [
+            ("gpt-4", Some("a")),
+            ("gpt-4", None),
+            ("gpt-4", Some("b")),
+            ("gpt-3.5-turbo", Some("a")),
+            ("gpt-4o", None),
+            ("gpt-4", Some("a")),
+            ("gpt-4", Some("b")),
+            ("gpt-4", None),
+        ]
+        .into_iter()
+        .map(|(model, session)| {
+            let ctx = RequestContext::new(model.to_string());
+            match session {
+                Some(session) => ctx.with_session(session.to_string()),
+                None => ctx,
+            }
+        })
+        .collect();
+
+        for strategy in [RoutingStrategy::RoundRobin, RoutingStrategy::Sticky] {
+            let batch_engine = RoutingEngine::new(strategy).with_seed(Some(7));
+            let single_engine = RoutingEngine::new(strategy).with_seed(Some(7));
+            batch_engine
+                .update_accounts(accounts.clone(), std::collections::HashMap::new())
+                .await;
+            single_engine
+                .update_accounts(accounts.clone(), std::collections::HashMap::new())
+                .await;
+
+            let batch: Vec<Option<AccountId>> = batch_engine
+                .resolve_many(&ctxs)
+                .await
+                .into_iter()
+                .map(|d| d.ok().map(|d| d.account_id))
+                .collect();
+            let mut single = Vec::new();
+            for ctx in &ctxs {
+                single.push(single_engine.resolve_account(ctx).await.ok().map(|d| d.account_id));
+            }
+
+            assert_eq!(batch, single, "{:?}", strategy);
+            // State advanced across the batch rather than repeating one pick
+            assert!(batch.iter().any(|id| *id != batch[0]), "{:?}", strategy);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_reuses_account_until_invalidated() {
+        let a = create_test_account(uuid::Uuid::new_v4(), 2, true);
+        let mut b = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let chat = RequestContext::new("gpt-4".to_string()).with_path("/v1/chat/completions");
+
+        let engine = RoutingEngine::new(RoutingStrategy::RoundRobin)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)));
+        engine
+            .update_accounts(vec![a.clone(), b.clone()], std::collections::HashMap::new())
+            .await;
+        let first = engine.resolve_account(&chat).await.unwrap().account_id;
+        for _ in 0..5 {
+            assert_eq!(engine.resolve_account(&chat).await.unwrap().account_id, first);
+        }
+
+        // A distinct request still takes the next round-robin turn, and
+        // failovers bypass the cache
+        let other = RequestContext::new("gpt-3.5-turbo".to_string()).with_path("/v1/chat/completions");
+        assert_ne!(engine.resolve_account(&other).await.unwrap().account_id, first);
+        assert_ne!(engine.resolve_failover(&chat, &[first]).await.unwrap().account_id, first);
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)));
+        engine
+            .update_accounts(vec![a.clone(), b.clone()], std::collections::HashMap::new())
+            .await;
+        assert_eq!(engine.resolve_account(&chat).await.unwrap().account_id, a.id);
+
+        // Updating the accounts drops the cached winner
+        b.priority = 5;
+        engine.update_accounts(vec![a, b.clone()], std::collections::HashMap::new()).await;
+        assert_eq!(engine.resolve_account(&chat).await.unwrap().account_id, b.id);
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_respects_size_rules() {
+        let a = create_test_account(uuid::Uuid::new_v4(), 2, true);
+        let b = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)))
+            .with_size_rules(vec![SizeRule {
+                min_tokens: Some(4000),
+                max_tokens: None,
+                account_ids: vec![b.id],
+            }]);
+        engine
+            .update_accounts(vec![a.clone(), b.clone()], std::collections::HashMap::new())
+            .await;
+
+        let small = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(200);
+        assert_eq!(engine.resolve_account(&small).await.unwrap().account_id, a.id);
+
+        // Same model and path, but the rule only lets the large request use B
+        let large = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(50_000);
+        let decision = engine.resolve_account(&large).await.unwrap();
+        assert_eq!(decision.account_id, b.id);
+        assert!(decision.reason.layers.iter().any(|l| matches!(l, RoutingLayer::SizeRule { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_drops_account_whose_usage_went_stale() {
+        let aging_id = uuid::Uuid::new_v4();
+        let fresh_id = uuid::Uuid::new_v4();
+        let accounts = vec![
+            create_test_account(aging_id, 1, true),
+            create_test_account(fresh_id, 1, true),
+        ];
+
+        let max_age = chrono::Duration::hours(1);
+        let mut aging = UsageSnapshot::new(aging_id);
+        aging.hard_limit = Some(100.0);
+        aging.monthly_usage = 5.0;
+        aging.timestamp = chrono::Utc::now() - max_age + chrono::Duration::milliseconds(300);
+        let mut fresh = UsageSnapshot::new(fresh_id);
+        fresh.hard_limit = Some(100.0);
+        fresh.monthly_usage = 60.0;
+        let usage_map = [(aging_id, aging), (fresh_id, fresh)].into_iter().collect();
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)))
+            .with_usage_max_age(max_age.to_std().ok(), StaleUsagePolicy::Deprioritize);
+        engine.update_accounts(accounts, usage_map).await;
+        let ctx = RequestContext::new("gpt-4".to_string());
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, aging_id);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, fresh_id);
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_leaves_soft_limited_account_on_recovery() {
+        let a = create_test_account(uuid::Uuid::new_v4(), 2, true);
+        let b = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let mut over = UsageSnapshot::new(b.id);
+        over.hard_limit = Some(100.0);
+        over.soft_limit = Some(50.0);
+        over.monthly_usage = 60.0;
+        let usage_map = [(b.id, over)].into_iter().collect();
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)));
+        engine.update_accounts(vec![a.clone(), b.clone()], usage_map).await;
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        // With A's circuit open the account past its soft limit is all that's left
+        for _ in 0..3 {
+            engine.report_error(a.id, true);
+        }
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, b.id);
+
+        engine.report_success(a.id);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, a.id);
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_demotes_standby_on_recovery() {
+        let primary = uuid::Uuid::new_v4();
+        let standby = uuid::Uuid::new_v4();
+        let mut standby_account = create_test_account(standby, 0, true);
+        standby_account.standby = true;
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)));
+        engine
+            .update_accounts(
+                vec![create_test_account(primary, 0, true), standby_account],
+                std::collections::HashMap::new(),
+            )
+            .await;
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        for _ in 0..3 {
+            engine.report_error(primary, true);
+        }
+        let decision = engine.resolve_account(&ctx).await.unwrap();
+        assert_eq!(decision.account_id, standby);
+        assert!(decision.reason.layers.contains(&RoutingLayer::Standby));
+
+        engine.report_success(primary);
+        let decision = engine.resolve_account(&ctx).await.unwrap();
+        assert_eq!(decision.account_id, primary);
+        assert!(decision.reason.layers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_still_rolls_the_experiment() {
+        let ids: Vec<uuid::Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        let accounts: Vec<Account> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| create_test_account(*id, i as i32, true))
+            .collect();
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_seed(Some(42))
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)))
+            .with_experiment(Some(ExperimentConfig { account_id: ids[0], percent: 50.0 }));
+        engine.update_accounts(accounts, std::collections::HashMap::new()).await;
+
+        let ctx = RequestContext::new("gpt-4".to_string());
+        let mut diverted = 0;
+        for _ in 0..100 {
+            if engine.resolve_account(&ctx).await.unwrap().account_id == ids[0] {
+                diverted += 1;
+            }
+        }
+        assert!((30..=70).contains(&diverted), "diverted {}", diverted);
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache_never_shares_sticky_pins() {
+        let mut sticky = create_test_account(uuid::Uuid::new_v4(), 2, true);
+        sticky.preferred_strategy = Some(RoutingStrategy::Sticky);
+        let other = create_test_account(uuid::Uuid::new_v4(), 1, true);
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)));
+        engine
+            .update_accounts(vec![sticky.clone(), other], std::collections::HashMap::new())
+            .await;
+
+        // A request without a session must not hand its decision to the
+        // sessions that follow, which each need a pin of their own
+        let ctx = RequestContext::new("gpt-4".to_string());
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, sticky.id);
+        for session in ["first", "second"] {
+            let ctx = RequestContext::new("gpt-4".to_string()).with_session(session.to_string());
+            assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, sticky.id);
+        }
+        assert_eq!(engine.active_sessions(), 2);
+
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky)
+            .with_decision_cache_ttl(Some(Duration::from_secs(60)));
+        engine
+            .update_accounts(vec![sticky.clone()], std::collections::HashMap::new())
+            .await;
+        engine.resolve_account(&RequestContext::new("gpt-4".to_string())).await.unwrap();
+        for session in ["first", "second"] {
+            let ctx = RequestContext::new("gpt-4".to_string()).with_session(session.to_string());
+            engine.resolve_account(&ctx).await.unwrap();
+        }
+        assert_eq!(engine.active_sessions(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_chat_routed_away_from_embeddings_only_account() {
+        let mut embeddings_only = create_test_account(uuid::Uuid::new_v4(), 2, true);
+        embeddings_only.allowed_paths = vec!["/v1/embeddings".to_string()];
+        let general = create_test_account(uuid::Uuid::new_v4(), 1, true);
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        engine
+            .update_accounts(
+                vec![embeddings_only.clone(), general.clone()],
+                std::collections::HashMap::new(),
+            )
+            .await;
+
+        let chat = RequestContext::new("gpt-4".to_string()).with_path("/v1/chat/completions");
+        let embeddings = RequestContext::new("gpt-4".to_string()).with_path("/v1/embeddings");
+        for _ in 0..10 {
+            assert_eq!(engine.resolve_account(&chat).await.unwrap().account_id, general.id);
+            assert_eq!(engine.resolve_account(&embeddings).await.unwrap().account_id, embeddings_only.id);
+        }
+
+        engine
+            .update_accounts(vec![embeddings_only], std::collections::HashMap::new())
+            .await;
+        assert!(engine.resolve_account(&chat).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_region_preference_breaks_ties_after_strategy() {
+        let region = |id, priority, region: &str| {
+            let mut account = create_test_account(id, priority, true);
+            account.region = Some(region.to_string());
+            account
+        };
+        let top = region(uuid::Uuid::new_v4(), 2, "us");
+        let eu = region(uuid::Uuid::new_v4(), 1, "eu");
+        let us = region(uuid::Uuid::new_v4(), 1, "us");
+        let ctx = RequestContext::new("gpt-4".to_string());
+
+        let engine = RoutingEngine::new(RoutingStrategy::Priority)
+            .with_preferred_regions(vec!["eu".to_string(), "us".to_string()]);
+        engine
+            .update_accounts(vec![top.clone(), eu.clone(), us.clone()], std::collections::HashMap::new())
+            .await;
+        // The strategy decides first; region only orders equals
+        for _ in 0..10 {
+            assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, top.id);
+        }
+
+        engine
+            .update_accounts(vec![eu.clone(), us.clone()], std::collections::HashMap::new())
+            .await;
+        for _ in 0..10 {
+            assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, eu.id);
+        }
+
+        let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized)
+            .with_preferred_regions(vec!["us".to_string()]);
+        engine
+            .update_accounts(vec![eu.clone(), us.clone()], std::collections::HashMap::new())
+            .await;
+        for _ in 0..10 {
+            assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, us.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_limit_models_routed() {
+        let listed = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let other = create_test_account(uuid::Uuid::new_v4(), 0, true);
+        let engine = RoutingEngine::new(RoutingStrategy::Priority);
+        engine
+            .update_accounts(vec![listed.clone(), other.clone()], std::collections::HashMap::new())
+            .await;
+
+        engine.set_capabilities(AccountCapabilities {
+            account_id: listed.id,
+            valid: true,
+            org_id: None,
+            available_models: vec!["gpt-4".to_string()],
+            supports_usage_api: false,
+            checked_at: chrono::Utc::now(),
+        });
+
+        let gpt4 = RequestContext::new("gpt-4".to_string());
+        assert_eq!(engine.resolve_account(&gpt4).await.unwrap().account_id, listed.id);
+
+        // A model the key didn't list goes to the unprobed account instead
+        let mini = RequestContext::new("gpt-4o-mini".to_string());
+        assert_eq!(engine.resolve_account(&mini).await.unwrap().account_id, other.id);
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_evicted_after_ttl() {
+        let ttl = Duration::from_secs(60);
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky).with_session_ttl(Some(ttl));
+        engine
+            .update_accounts(
+                vec![create_test_account(uuid::Uuid::new_v4(), 1, true)],
+                std::collections::HashMap::new(),
+            )
+            .await;
+        let session = |id: &str| RequestContext::new("gpt-4".to_string()).with_session(id.to_string());
+
+        engine.resolve_account(&session("idle")).await.unwrap();
+        engine.resolve_account(&session("busy")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let later = Instant::now();
+        // Using a pin refreshes it
+        engine.resolve_account(&session("busy")).await.unwrap();
+
+        // Nothing is idle long enough yet
+        assert_eq!(engine.evict_sessions_idle_at(later), 0);
+        assert_eq!(engine.active_sessions(), 2);
+
+        // A TTL after `later`, only the pin untouched since then is idle too long
+        assert_eq!(engine.evict_sessions_idle_at(later + ttl), 1);
+        assert_eq!(engine.active_sessions(), 1);
+        let busy = engine.session_key(&session("busy")).unwrap();
+        assert!(engine.session_map.contains_key(&busy));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_revoke_sessions() {
+        let account = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+        engine
+            .update_accounts(vec![account.clone()], std::collections::HashMap::new())
+            .await;
+
+        for session in ["session-alpha-1", "session-alpha-2", "other-session"] {
+            let ctx = RequestContext::new("gpt-4".to_string()).with_session(session.to_string());
+            engine.resolve_account(&ctx).await.unwrap();
+        }
+
+        let sessions = engine.sessions().await;
+        let mut prefixes: Vec<&str> = sessions.iter().map(|s| s.session_prefix.as_str()).collect();
+        prefixes.sort();
+        assert_eq!(prefixes, vec!["other-se", "session-alpha-1", "session-alpha-2"]);
+        assert!(sessions.iter().all(|s| s.account_id == account.id && s.account_label == account.label));
+
+        // A shared prefix is ambiguous; a unique one or the full hash revokes
+        assert!(engine.revoke_session("session-alpha").is_err());
+        assert_eq!(engine.revoke_session("other").unwrap(), "other-session");
+        assert_eq!(engine.revoke_session("session-alpha-2").unwrap(), "session-alpha-2");
+        assert!(engine.revoke_session("other").is_err());
+
+        // With its sibling gone the remaining session's prefix shortens again
+        let remaining: Vec<String> = engine.sessions().await.into_iter().map(|s| s.session_prefix).collect();
+        assert_eq!(remaining, vec!["session-"]);
+    }
+
+    #[tokio::test]
+    async fn test_exported_state_restores_into_fresh_engine() {
+        let a = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let b = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let accounts = vec![a.clone(), b.clone()];
+        let session = RequestContext::new("gpt-4".to_string()).with_session("s1".to_string());
+
+        let engine = RoutingEngine::new(RoutingStrategy::Sticky);
+        engine.update_accounts(accounts.clone(), std::collections::HashMap::new()).await;
+        let pinned = engine.resolve_account(&session).await.unwrap().account_id;
+        let unpinned = if pinned == a.id { b.id } else { a.id };
+        engine.open_circuit(unpinned);
+        engine.record_local_cost(a.id, 1.5);
+        *engine.round_robin_index.write().await = 7;
+        let exported = engine.export_state().await.unwrap();
+
+        let restored = RoutingEngine::new(RoutingStrategy::Sticky);
+        restored.import_state(&exported).await.unwrap();
+        restored.update_accounts(accounts, std::collections::HashMap::new()).await;
+
+        // Equal apart from ages, which grow between the two exports
+        let strip_ages = |json: &str| {
+            let mut state: EngineState = serde_json::from_str(json).unwrap();
+            state.circuits.sort_by_key(|c| c.account_id);
+            for circuit in &mut state.circuits {
+                circuit.open_for_ms = circuit.open_for_ms.map(|_| 0);
+                circuit.last_used_ago_ms = circuit.last_used_ago_ms.map(|_| 0);
+            }
+            for session in &mut state.sessions {
+                session.idle_ms = 0;
+            }
+            state
+        };
+        let state = strip_ages(&exported);
+        assert_eq!(strip_ages(&restored.export_state().await.unwrap()), state);
+        assert_eq!(state.round_robin_index, 7);
+        assert_eq!(state.sessions.len(), 1);
+
+        assert_eq!(restored.local_cost(a.id), 1.5);
+        let stats = restored.get_stats().await;
+        assert_eq!((stats.open_circuits, stats.available_accounts), (1, 1));
+        assert_eq!(restored.resolve_account(&session).await.unwrap().account_id, pinned);
+        assert!(restored.import_state("not json").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_large_response_deprioritizes_account() {
+        let busy = create_test_account(uuid::Uuid::new_v4(), 2, true);
+        let spare = create_test_account(uuid::Uuid::new_v4(), 1, true);
+        let engine = RoutingEngine::new(RoutingStrategy::Priority).with_large_request_cooldown(Some(
+            LargeRequestCooldown { min_tokens: 10_000, cooldown_secs: 60 },
+        ));
+        engine
+            .update_accounts(vec![busy.clone(), spare.clone()], std::collections::HashMap::new())
+            .await;
+        let ctx = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(12_000);
+
+        // A response under the threshold changes nothing
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, busy.id);
+        engine.reconcile_tokens(busy.id, None, 9_999);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, busy.id);
+
+        engine.reconcile_tokens(busy.id, None, 25_000);
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, spare.id);
+
+        // Small requests aren't spread out by the cooldown
+        let small = RequestContext::new("gpt-4".to_string()).with_estimated_tokens(200);
+        assert_eq!(engine.resolve_account(&small).await.unwrap().account_id, busy.id);
+
+        // With no alternative the cooling account still serves
+        engine.update_accounts(vec![busy.clone()], std::collections::HashMap::new()).await;
+        assert_eq!(engine.resolve_account(&ctx).await.unwrap().account_id, busy.id);
+    }
 }
@@ -1,53 +1,95 @@
 use aes_gcm::{
-    aead::{Aead, NewAead},
+    aead::{Aead, KeyInit},
     Aes256Gcm, Nonce,
 };
 use anyhow::{Context, Result};
 use argon2::{
     password_hash::{rand_core::RngCore, SaltString},
-    Argon2, PasswordHasher,
+    Argon2, Params, PasswordHasher,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{Datelike, NaiveDate, Utc};
 use rand::rngs::OsRng;
 use rusqlite::{params, Connection, OptionalExtension};
 use std::path::Path;
-use uuid::Uuid;
+use std::time::{Duration, Instant};
 
-use crate::models::{Account, AccountId, UsageSnapshot};
+use crate::models::{
+    Account, AccountCapabilities, AccountId, CostCenterReport, UsageDelta, UsageSnapshot,
+};
+use crate::routing::RoutingStrategy;
 
-/// Manages encrypted SQLite storage for accounts and usage data
-pub struct EncryptedStore {
-    conn: Connection,
-    cipher: Aes256Gcm,
-}
+/// Metadata key holding the proxy's lifetime request total
+const TOTAL_REQUEST_COUNT_KEY: &str = "total_request_count";
 
-impl EncryptedStore {
-    /// Initialize or open the encrypted database
-    pub fn open(db_path: &Path, master_key: &str) -> Result<Self> {
-        let conn = Connection::open(db_path).context("Failed to open database")?;
+/// Metadata key holding the routing engine snapshot from `export_state`
+const ENGINE_STATE_KEY: &str = "engine_state";
 
-        // Derive encryption key from master password
-        let cipher = Self::derive_cipher(master_key)?;
+/// Write failures callers can give actionable guidance for
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    /// SQLite reported the disk (or the database size limit) as full
+    #[error("Database disk is full; free up space and try again")]
+    DiskFull,
+    /// The database file couldn't be written
+    #[error("Database write failed ({0}); check that the database file and its directory are writable")]
+    Io(String),
+}
 
-        let store = Self { conn, cipher };
-        store.init_schema()?;
+impl StorageError {
+    /// Classify a SQLite error, if it is one of the typed write failures
+    fn from_sqlite(error: &rusqlite::Error) -> Option<Self> {
+        use rusqlite::ErrorCode;
 
-        Ok(store)
+        let rusqlite::Error::SqliteFailure(failure, message) = error else {
+            return None;
+        };
+        match failure.code {
+            ErrorCode::DiskFull => Some(StorageError::DiskFull),
+            ErrorCode::ReadOnly
+            | ErrorCode::SystemIoFailure
+            | ErrorCode::CannotOpen
+            | ErrorCode::PermissionDenied => Some(StorageError::Io(
+                message.clone().unwrap_or_else(|| failure.to_string()),
+            )),
+            _ => None,
+        }
     }
+}
 
-    /// Create an in-memory database (for testing)
-    pub fn open_in_memory(master_key: &str) -> Result<Self> {
-        let conn = Connection::open_in_memory().context("Failed to open in-memory database")?;
+/// Convert a write error, surfacing disk-full and I/O failures as `StorageError`
+fn write_error(error: rusqlite::Error) -> anyhow::Error {
+    match StorageError::from_sqlite(&error) {
+        Some(typed) => typed.into(),
+        None => error.into(),
+    }
+}
 
-        let cipher = Self::derive_cipher(master_key)?;
-        let store = Self { conn, cipher };
-        store.init_schema()?;
+/// Protects api_keys at rest: `encrypt` turns a key into the text stored in
+/// the database and `decrypt` recovers it.
+///
+/// `AesGcmSecrets` is the default; an OS keyring or external KMS can be
+/// plugged in with `EncryptedStore::open_with_secrets`.
+pub trait SecretStore: Send {
+    fn encrypt(&self, plaintext: &str) -> Result<String>;
+    fn decrypt(&self, stored: &str) -> Result<String>;
 
-        Ok(store)
+    /// Parameters of the key derivation from the master key, for diagnostics;
+    /// `None` for stores that don't derive their key
+    fn kdf_params(&self) -> Option<String> {
+        None
     }
+}
 
+/// AES-256-GCM with a key derived from the master password; the nonce is
+/// stored alongside the ciphertext
+pub struct AesGcmSecrets {
+    cipher: Aes256Gcm,
+}
+
+impl AesGcmSecrets {
     /// Derive AES-256-GCM cipher from master key using Argon2
-    fn derive_cipher(master_key: &str) -> Result<Aes256Gcm> {
+    pub fn from_master_key(master_key: &str) -> Result<Self> {
         // Generate a salt (in production, store this separately)
         let salt = SaltString::generate(&mut OsRng);
 
@@ -55,7 +97,7 @@ impl EncryptedStore {
         let argon2 = Argon2::default();
         let password_hash = argon2
             .hash_password(master_key.as_bytes(), &salt)
-            .context("Failed to hash password")?;
+            .map_err(|e| anyhow::anyhow!("Failed to hash password: {}", e))?;
 
         // Extract the hash output as key material
         let hash = password_hash
@@ -64,12 +106,14 @@ impl EncryptedStore {
 
         // Create key from hash bytes
         let key_bytes = hash.as_bytes();
-        let key = aes_gcm::Key::from_slice(&key_bytes[..32]);
+        let key = aes_gcm::Key::<Aes256Gcm>::from_slice(&key_bytes[..32]);
         let cipher = Aes256Gcm::new(key);
 
-        Ok(cipher)
+        Ok(Self { cipher })
     }
+}
 
+impl SecretStore for AesGcmSecrets {
     /// Encrypt data using AES-256-GCM
     fn encrypt(&self, plaintext: &str) -> Result<String> {
         let mut nonce_bytes = [0u8; 12];
@@ -110,6 +154,63 @@ impl EncryptedStore {
         String::from_utf8(plaintext).context("Invalid UTF-8 in decrypted data")
     }
 
+    fn kdf_params(&self) -> Option<String> {
+        // `from_master_key` derives with `Argon2::default()`
+        let params = Params::default();
+        Some(format!(
+            "Argon2id v19, m={} KiB, t={}, p={}",
+            params.m_cost(),
+            params.t_cost(),
+            params.p_cost()
+        ))
+    }
+}
+
+/// Manages encrypted SQLite storage for accounts and usage data
+pub struct EncryptedStore {
+    conn: Connection,
+    secrets: Box<dyn SecretStore>,
+}
+
+impl EncryptedStore {
+    /// Initialize or open the encrypted database
+    pub fn open(db_path: &Path, master_key: &str) -> Result<Self> {
+        Self::open_with_secrets(db_path, Box::new(AesGcmSecrets::from_master_key(master_key)?))
+    }
+
+    /// Initialize or open the database, protecting api_keys with `secrets`
+    pub fn open_with_secrets(db_path: &Path, secrets: Box<dyn SecretStore>) -> Result<Self> {
+        let conn = Connection::open(db_path).context("Failed to open database")?;
+
+        let store = Self { conn, secrets };
+        store.init_schema()?;
+
+        Ok(store)
+    }
+
+    /// Create an in-memory database (for testing)
+    pub fn open_in_memory(master_key: &str) -> Result<Self> {
+        Self::open_in_memory_with_secrets(Box::new(AesGcmSecrets::from_master_key(master_key)?))
+    }
+
+    /// Create an in-memory database protecting api_keys with `secrets`
+    pub fn open_in_memory_with_secrets(secrets: Box<dyn SecretStore>) -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory database")?;
+
+        let store = Self { conn, secrets };
+        store.init_schema()?;
+
+        Ok(store)
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        self.secrets.encrypt(plaintext)
+    }
+
+    fn decrypt(&self, stored: &str) -> Result<String> {
+        self.secrets.decrypt(stored)
+    }
+
     /// Initialize database schema
     fn init_schema(&self) -> Result<()> {
         self.conn
@@ -127,7 +228,21 @@ impl EncryptedStore {
                 enabled INTEGER DEFAULT 1,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
-                last_used TEXT
+                last_used TEXT,
+                disabled_reason TEXT,
+                disabled_at TEXT,
+                preferred_strategy TEXT, -- JSON string
+                tags TEXT, -- JSON array
+                cost_multiplier REAL DEFAULT 1.0,
+                no_upstream_auth INTEGER DEFAULT 0,
+                schedule TEXT,
+                standby INTEGER DEFAULT 0,
+                tpm_limit INTEGER,
+                region TEXT,
+                allowed_paths TEXT, -- JSON array
+                cost_center TEXT,
+                pinned INTEGER DEFAULT 0,
+                locked INTEGER DEFAULT 0
             );
 
             CREATE INDEX IF NOT EXISTS idx_accounts_enabled ON accounts(enabled);
@@ -144,12 +259,30 @@ impl EncryptedStore {
                 daily_usage REAL DEFAULT 0.0,
                 monthly_usage REAL DEFAULT 0.0,
                 timestamp TEXT NOT NULL,
+                observed_at TEXT,
                 FOREIGN KEY (account_id) REFERENCES accounts(id)
             );
 
             CREATE INDEX IF NOT EXISTS idx_usage_account ON usage_snapshots(account_id);
             CREATE INDEX IF NOT EXISTS idx_usage_timestamp ON usage_snapshots(timestamp);
 
+            CREATE TABLE IF NOT EXISTS request_counts (
+                account_id TEXT NOT NULL,
+                day TEXT NOT NULL, -- UTC date, YYYY-MM-DD
+                count INTEGER DEFAULT 0,
+                PRIMARY KEY (account_id, day)
+            );
+
+            CREATE TABLE IF NOT EXISTS account_capabilities (
+                account_id TEXT PRIMARY KEY,
+                valid INTEGER NOT NULL,
+                org_id TEXT,
+                available_models TEXT, -- JSON array
+                supports_usage_api INTEGER DEFAULT 0,
+                checked_at TEXT NOT NULL,
+                FOREIGN KEY (account_id) REFERENCES accounts(id)
+            );
+
             CREATE TABLE IF NOT EXISTS metadata (
                 key TEXT PRIMARY KEY,
                 value TEXT
@@ -158,13 +291,63 @@ impl EncryptedStore {
             )
             .context("Failed to initialize database schema")?;
 
+        self.migrate_schema()
+    }
+
+    /// Add columns introduced after the initial schema to existing databases
+    fn migrate_schema(&self) -> Result<()> {
+        let columns = self.table_columns("accounts")?;
+
+        for (column, ty) in [
+            ("disabled_reason", "TEXT"),
+            ("disabled_at", "TEXT"),
+            ("preferred_strategy", "TEXT"),
+            ("tags", "TEXT"),
+            ("cost_multiplier", "REAL DEFAULT 1.0"),
+            ("no_upstream_auth", "INTEGER DEFAULT 0"),
+            ("schedule", "TEXT"),
+            ("standby", "INTEGER DEFAULT 0"),
+            ("tpm_limit", "INTEGER"),
+            ("region", "TEXT"),
+            ("allowed_paths", "TEXT"),
+            ("cost_center", "TEXT"),
+            ("pinned", "INTEGER DEFAULT 0"),
+            ("locked", "INTEGER DEFAULT 0"),
+        ] {
+            if !columns.iter().any(|c| c == column) {
+                self.conn
+                    .execute(&format!("ALTER TABLE accounts ADD COLUMN {} {}", column, ty), [])
+                    .with_context(|| format!("Failed to add accounts.{} column", column))?;
+            }
+        }
+
+        if !self.table_columns("usage_snapshots")?.iter().any(|c| c == "observed_at") {
+            self.conn
+                .execute("ALTER TABLE usage_snapshots ADD COLUMN observed_at TEXT", [])
+                .context("Failed to add usage_snapshots.observed_at column")?;
+        }
+
         Ok(())
     }
 
+    fn table_columns(&self, table: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>("name"))?
+            .collect::<Result<_, _>>()?;
+        Ok(columns)
+    }
+
     /// Save or update an account
     pub fn save_account(&self, account: &Account) -> Result<()> {
         let encrypted_key = self.encrypt(&account.api_key)?;
         let model_scope_json = serde_json::to_string(&account.model_scope)?;
+        let preferred_strategy_json = account
+            .preferred_strategy
+            .map(|s| serde_json::to_string(&s))
+            .transpose()?;
+        let tags_json = serde_json::to_string(&account.tags)?;
+        let allowed_paths_json = serde_json::to_string(&account.allowed_paths)?;
 
         self.conn
             .execute(
@@ -172,8 +355,11 @@ impl EncryptedStore {
             INSERT INTO accounts (
                 id, label, api_key_encrypted, org_id, model_scope,
                 daily_limit, monthly_limit, priority, enabled,
-                created_at, updated_at, last_used
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                created_at, updated_at, last_used, disabled_reason, disabled_at,
+                preferred_strategy, tags, cost_multiplier, no_upstream_auth, schedule,
+                standby, tpm_limit, region, allowed_paths, cost_center, pinned,
+                locked
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26)
             ON CONFLICT(id) DO UPDATE SET
                 label = excluded.label,
                 api_key_encrypted = excluded.api_key_encrypted,
@@ -184,7 +370,21 @@ impl EncryptedStore {
                 priority = excluded.priority,
                 enabled = excluded.enabled,
                 updated_at = excluded.updated_at,
-                last_used = excluded.last_used
+                last_used = excluded.last_used,
+                disabled_reason = excluded.disabled_reason,
+                disabled_at = excluded.disabled_at,
+                preferred_strategy = excluded.preferred_strategy,
+                tags = excluded.tags,
+                cost_multiplier = excluded.cost_multiplier,
+                no_upstream_auth = excluded.no_upstream_auth,
+                schedule = excluded.schedule,
+                standby = excluded.standby,
+                tpm_limit = excluded.tpm_limit,
+                region = excluded.region,
+                allowed_paths = excluded.allowed_paths,
+                cost_center = excluded.cost_center,
+                pinned = excluded.pinned,
+                locked = excluded.locked
             "#,
                 params![
                     account.id.to_string(),
@@ -199,8 +399,23 @@ impl EncryptedStore {
                     account.created_at.to_rfc3339(),
                     account.updated_at.to_rfc3339(),
                     account.last_used.map(|t| t.to_rfc3339()),
+                    account.disabled_reason,
+                    account.disabled_at.map(|t| t.to_rfc3339()),
+                    preferred_strategy_json,
+                    tags_json,
+                    account.cost_multiplier,
+                    account.no_upstream_auth as i32,
+                    account.schedule,
+                    account.standby as i32,
+                    account.tpm_limit.map(|t| t as i64),
+                    account.region,
+                    allowed_paths_json,
+                    account.cost_center,
+                    account.pinned as i32,
+                    account.locked as i32,
                 ],
             )
+            .map_err(write_error)
             .context("Failed to save account")?;
 
         Ok(())
@@ -218,7 +433,7 @@ impl EncryptedStore {
                 rusqlite::Error::FromSqlConversionFailure(
                     0,
                     rusqlite::types::Type::Text,
-                    Box::new(e),
+                    e.into(),
                 )
             })?;
 
@@ -228,12 +443,12 @@ impl EncryptedStore {
                     rusqlite::Error::FromSqlConversionFailure(
                         0,
                         rusqlite::types::Type::Text,
-                        Box::new(e),
+                        e.into(),
                     )
                 })?;
 
             Ok(Account {
-                id: row.get::<String, _>("id")?.parse().unwrap(),
+                id: row.get::<_, String>("id")?.parse().unwrap(),
                 label: row.get("label")?,
                 api_key,
                 org_id: row.get("org_id")?,
@@ -241,12 +456,28 @@ impl EncryptedStore {
                 daily_limit: row.get("daily_limit")?,
                 monthly_limit: row.get("monthly_limit")?,
                 priority: row.get("priority")?,
-                enabled: row.get::<i32, _>("enabled")? != 0,
-                created_at: row.get::<String, _>("created_at")?.parse().unwrap(),
-                updated_at: row.get::<String, _>("updated_at")?.parse().unwrap(),
+                enabled: row.get::<_, i32>("enabled")? != 0,
+                created_at: row.get::<_, String>("created_at")?.parse().unwrap(),
+                updated_at: row.get::<_, String>("updated_at")?.parse().unwrap(),
                 last_used: row
-                    .get::<Option<String>, _>("last_used")?
+                    .get::<_, Option<String>>("last_used")?
+                    .map(|s| s.parse().unwrap()),
+                disabled_reason: row.get("disabled_reason")?,
+                disabled_at: row
+                    .get::<_, Option<String>>("disabled_at")?
                     .map(|s| s.parse().unwrap()),
+                preferred_strategy: preferred_strategy_from(row)?,
+                tags: string_list_from(row, "tags")?,
+                cost_multiplier: row.get::<_, Option<f64>>("cost_multiplier")?.unwrap_or(1.0),
+                no_upstream_auth: row.get::<_, Option<i32>>("no_upstream_auth")?.unwrap_or(0) != 0,
+                schedule: row.get("schedule")?,
+                standby: row.get::<_, Option<i32>>("standby")?.unwrap_or(0) != 0,
+                tpm_limit: row.get::<_, Option<i64>>("tpm_limit")?.map(|t| t as u64),
+                region: row.get("region")?,
+                allowed_paths: string_list_from(row, "allowed_paths")?,
+                cost_center: row.get("cost_center")?,
+                pinned: row.get::<_, Option<i32>>("pinned")?.unwrap_or(0) != 0,
+                locked: row.get::<_, Option<i32>>("locked")?.unwrap_or(0) != 0,
             })
         })?;
 
@@ -266,7 +497,7 @@ impl EncryptedStore {
                     rusqlite::Error::FromSqlConversionFailure(
                         0,
                         rusqlite::types::Type::Text,
-                        Box::new(e),
+                        e.into(),
                     )
                 })?;
 
@@ -276,12 +507,12 @@ impl EncryptedStore {
                         rusqlite::Error::FromSqlConversionFailure(
                             0,
                             rusqlite::types::Type::Text,
-                            Box::new(e),
+                            e.into(),
                         )
                     })?;
 
                 Ok(Account {
-                    id: row.get::<String, _>("id")?.parse().unwrap(),
+                    id: row.get::<_, String>("id")?.parse().unwrap(),
                     label: row.get("label")?,
                     api_key,
                     org_id: row.get("org_id")?,
@@ -289,12 +520,28 @@ impl EncryptedStore {
                     daily_limit: row.get("daily_limit")?,
                     monthly_limit: row.get("monthly_limit")?,
                     priority: row.get("priority")?,
-                    enabled: row.get::<i32, _>("enabled")? != 0,
-                    created_at: row.get::<String, _>("created_at")?.parse().unwrap(),
-                    updated_at: row.get::<String, _>("updated_at")?.parse().unwrap(),
+                    enabled: row.get::<_, i32>("enabled")? != 0,
+                    created_at: row.get::<_, String>("created_at")?.parse().unwrap(),
+                    updated_at: row.get::<_, String>("updated_at")?.parse().unwrap(),
                     last_used: row
-                        .get::<Option<String>, _>("last_used")?
+                        .get::<_, Option<String>>("last_used")?
                         .map(|s| s.parse().unwrap()),
+                    disabled_reason: row.get("disabled_reason")?,
+                    disabled_at: row
+                        .get::<_, Option<String>>("disabled_at")?
+                        .map(|s| s.parse().unwrap()),
+                    preferred_strategy: preferred_strategy_from(row)?,
+                    tags: string_list_from(row, "tags")?,
+                    cost_multiplier: row.get::<_, Option<f64>>("cost_multiplier")?.unwrap_or(1.0),
+                    no_upstream_auth: row.get::<_, Option<i32>>("no_upstream_auth")?.unwrap_or(0) != 0,
+                    schedule: row.get("schedule")?,
+                    standby: row.get::<_, Option<i32>>("standby")?.unwrap_or(0) != 0,
+                    tpm_limit: row.get::<_, Option<i64>>("tpm_limit")?.map(|t| t as u64),
+                    region: row.get("region")?,
+                    allowed_paths: string_list_from(row, "allowed_paths")?,
+                    cost_center: row.get("cost_center")?,
+                    pinned: row.get::<_, Option<i32>>("pinned")?.unwrap_or(0) != 0,
+                    locked: row.get::<_, Option<i32>>("locked")?.unwrap_or(0) != 0,
                 })
             })
             .optional()?;
@@ -302,18 +549,46 @@ impl EncryptedStore {
         Ok(account)
     }
 
-    /// Delete an account
-    pub fn delete_account(&self, id: AccountId) -> Result<bool> {
+    /// Replace an account's api_key, keeping its id, creation time and usage
+    /// history.
+    ///
+    /// Returns `false` if the account doesn't exist.
+    pub fn update_account_key(&self, id: AccountId, new_key: &str) -> Result<bool> {
+        let encrypted_key = self.encrypt(new_key)?;
+
         let rows = self
             .conn
-            .execute("DELETE FROM accounts WHERE id = ?1", [id.to_string()])?;
+            .execute(
+                "UPDATE accounts SET api_key_encrypted = ?2, updated_at = ?3 WHERE id = ?1",
+                params![id.to_string(), encrypted_key, Utc::now().to_rfc3339()],
+            )
+            .context("Failed to update account key")?;
 
-        // Also delete usage snapshots
+        Ok(rows > 0)
+    }
+
+    /// Delete an account
+    pub fn delete_account(&self, id: AccountId) -> Result<bool> {
+        // Delete usage snapshots first; they reference the account
         self.conn.execute(
             "DELETE FROM usage_snapshots WHERE account_id = ?1",
             [id.to_string()],
         )?;
 
+        self.conn.execute(
+            "DELETE FROM request_counts WHERE account_id = ?1",
+            [id.to_string()],
+        )?;
+
+        self.conn.execute(
+            "DELETE FROM account_capabilities WHERE account_id = ?1",
+            [id.to_string()],
+        )?;
+
+        let rows = self
+            .conn
+            .execute("DELETE FROM accounts WHERE id = ?1", [id.to_string()])?;
+
         Ok(rows > 0)
     }
 
@@ -324,8 +599,9 @@ impl EncryptedStore {
                 r#"
             INSERT INTO usage_snapshots (
                 account_id, tokens_used, cost_estimate, hard_limit,
-                soft_limit, remaining_budget, daily_usage, monthly_usage, timestamp
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                soft_limit, remaining_budget, daily_usage, monthly_usage, timestamp,
+                observed_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             "#,
                 params![
                     snapshot.account_id.to_string(),
@@ -337,44 +613,268 @@ impl EncryptedStore {
                     snapshot.daily_usage,
                     snapshot.monthly_usage,
                     snapshot.timestamp.to_rfc3339(),
+                    snapshot.observed_at.map(|at| at.to_rfc3339()),
                 ],
             )
+            .map_err(write_error)
             .context("Failed to save usage snapshot")?;
 
         Ok(())
     }
 
+    /// Save a snapshot advancing the account's latest one by `delta`, so usage
+    /// counted between polls survives a restart until the next poll supersedes it.
+    ///
+    /// Returns `None`, saving nothing, for an account that was never polled:
+    /// there are no real figures to advance.
+    pub fn save_usage_delta(&self, account_id: AccountId, delta: UsageDelta) -> Result<Option<UsageSnapshot>> {
+        let Some(latest) = self.load_latest_usage(account_id)? else {
+            return Ok(None);
+        };
+        let snapshot = latest.with_delta(delta);
+        self.save_usage_snapshot(&snapshot)?;
+
+        Ok(Some(snapshot))
+    }
+
     /// Load the latest usage snapshot for an account
     pub fn load_latest_usage(&self, account_id: AccountId) -> Result<Option<UsageSnapshot>> {
         let mut stmt = self.conn.prepare(
-            "SELECT * FROM usage_snapshots WHERE account_id = ?1 ORDER BY timestamp DESC LIMIT 1",
+            "SELECT * FROM usage_snapshots WHERE account_id = ?1 ORDER BY timestamp DESC, id DESC LIMIT 1",
         )?;
 
         let snapshot = stmt
-            .query_row([account_id.to_string()], |row| {
-                Ok(UsageSnapshot {
-                    account_id: row.get::<String, _>("account_id")?.parse().unwrap(),
-                    tokens_used: row.get::<i64, _>("tokens_used")? as u64,
-                    cost_estimate: row.get("cost_estimate")?,
-                    hard_limit: row.get("hard_limit")?,
-                    soft_limit: row.get("soft_limit")?,
-                    remaining_budget: row.get("remaining_budget")?,
-                    daily_usage: row.get("daily_usage")?,
-                    monthly_usage: row.get("monthly_usage")?,
-                    timestamp: row.get::<String, _>("timestamp")?.parse().unwrap(),
-                })
-            })
+            .query_row([account_id.to_string()], usage_from_row)
             .optional()?;
 
         Ok(snapshot)
     }
 
+    /// Load up to `limit` of an account's most recent usage snapshots, oldest first
+    pub fn load_usage_history(&self, account_id: AccountId, limit: usize) -> Result<Vec<UsageSnapshot>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM usage_snapshots WHERE account_id = ?1 ORDER BY timestamp DESC, id DESC LIMIT ?2",
+        )?;
+
+        let mut history = stmt
+            .query_map(params![account_id.to_string(), limit as i64], usage_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        history.reverse();
+
+        Ok(history)
+    }
+
+    /// Save an account's latest capability probe, replacing the previous one
+    pub fn save_capabilities(&self, capabilities: &AccountCapabilities) -> Result<()> {
+        let models_json = serde_json::to_string(&capabilities.available_models)?;
+
+        self.conn
+            .execute(
+                r#"
+            INSERT INTO account_capabilities (
+                account_id, valid, org_id, available_models, supports_usage_api, checked_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(account_id) DO UPDATE SET
+                valid = excluded.valid,
+                org_id = excluded.org_id,
+                available_models = excluded.available_models,
+                supports_usage_api = excluded.supports_usage_api,
+                checked_at = excluded.checked_at
+            "#,
+                params![
+                    capabilities.account_id.to_string(),
+                    capabilities.valid as i32,
+                    capabilities.org_id,
+                    models_json,
+                    capabilities.supports_usage_api as i32,
+                    capabilities.checked_at.to_rfc3339(),
+                ],
+            )
+            .context("Failed to save account capabilities")?;
+
+        Ok(())
+    }
+
+    /// Load the latest capability probe of every account that has one
+    pub fn load_capabilities(&self) -> Result<Vec<AccountCapabilities>> {
+        let mut stmt = self.conn.prepare("SELECT * FROM account_capabilities")?;
+
+        let capabilities = stmt
+            .query_map([], |row| {
+                let models_json: String = row.get("available_models")?;
+                let available_models = serde_json::from_str(&models_json).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        0,
+                        rusqlite::types::Type::Text,
+                        e.into(),
+                    )
+                })?;
+
+                Ok(AccountCapabilities {
+                    account_id: row.get::<_, String>("account_id")?.parse().unwrap(),
+                    valid: row.get::<_, i32>("valid")? != 0,
+                    org_id: row.get("org_id")?,
+                    available_models,
+                    supports_usage_api: row.get::<_, i32>("supports_usage_api")? != 0,
+                    checked_at: row.get::<_, String>("checked_at")?.parse().unwrap(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(capabilities)
+    }
+
+    /// Add `count` routed requests to an account's counter for `day`.
+    ///
+    /// Used by the proxy to flush batched counters in one write per account.
+    pub fn add_request_count(&self, account_id: AccountId, day: NaiveDate, count: u64) -> Result<()> {
+        self.conn
+            .execute(
+                r#"
+            INSERT INTO request_counts (account_id, day, count) VALUES (?1, ?2, ?3)
+            ON CONFLICT(account_id, day) DO UPDATE SET count = count + excluded.count
+            "#,
+                params![account_id.to_string(), day.to_string(), count as i64],
+            )
+            .context("Failed to update request count")?;
+
+        Ok(())
+    }
+
+    /// Number of requests routed to an account during the current UTC day
+    pub fn requests_today(&self, account_id: AccountId) -> Result<u64> {
+        self.requests_on(account_id, Utc::now().date_naive())
+    }
+
+    /// Number of requests routed to an account on a given UTC day
+    pub fn requests_on(&self, account_id: AccountId, day: NaiveDate) -> Result<u64> {
+        let count = self
+            .conn
+            .query_row(
+                "SELECT count FROM request_counts WHERE account_id = ?1 AND day = ?2",
+                [account_id.to_string(), day.to_string()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+
+        Ok(count.unwrap_or(0) as u64)
+    }
+
+    /// Requests routed to an account from `start` through `end` (UTC days)
+    pub fn requests_between(&self, account_id: AccountId, start: NaiveDate, end: NaiveDate) -> Result<u64> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(count), 0) FROM request_counts
+             WHERE account_id = ?1 AND day >= ?2 AND day <= ?3",
+            [account_id.to_string(), start.to_string(), end.to_string()],
+            |row| row.get(0),
+        )?;
+
+        Ok(count as u64)
+    }
+
+    /// Dollars an account spent from `start` through `end` (UTC days).
+    ///
+    /// Sums how far `monthly_usage` rose across the snapshots in the range,
+    /// starting from the last snapshot before it and from zero each new month.
+    pub fn spend_between(&self, account_id: AccountId, start: NaiveDate, end: NaiveDate) -> Result<f64> {
+        let after_end = end.succ_opt().unwrap_or(end);
+        let baseline = self
+            .conn
+            .query_row(
+                "SELECT * FROM usage_snapshots WHERE account_id = ?1 AND timestamp < ?2
+                 ORDER BY timestamp DESC, id DESC LIMIT 1",
+                [account_id.to_string(), start.to_string()],
+                usage_from_row,
+            )
+            .optional()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM usage_snapshots WHERE account_id = ?1 AND timestamp >= ?2 AND timestamp < ?3
+             ORDER BY timestamp ASC, id ASC",
+        )?;
+        let snapshots = stmt
+            .query_map(
+                [account_id.to_string(), start.to_string(), after_end.to_string()],
+                usage_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let month_of = |s: &UsageSnapshot| (s.as_of().year(), s.as_of().month());
+        let mut month = baseline.as_ref().map(month_of);
+        let mut high = baseline.map_or(0.0, |s| s.monthly_usage);
+        let mut spend = 0.0;
+        for snapshot in &snapshots {
+            if month != Some(month_of(snapshot)) {
+                month = Some(month_of(snapshot));
+                high = 0.0;
+            }
+            spend += (snapshot.monthly_usage - high).max(0.0);
+            high = high.max(snapshot.monthly_usage);
+        }
+
+        Ok(spend)
+    }
+
+    /// Spend and requests of every account over a date range, grouped by
+    /// cost center
+    pub fn cost_center_report(&self, start: NaiveDate, end: NaiveDate) -> Result<CostCenterReport> {
+        let accounts = self.load_accounts()?;
+        let totals = accounts
+            .iter()
+            .map(|account| {
+                Ok((
+                    account,
+                    self.spend_between(account.id, start, end)?,
+                    self.requests_between(account.id, start, end)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(CostCenterReport::from_accounts(start, end, totals))
+    }
+
+    /// Requests routed to an account across every recorded day
+    pub fn lifetime_requests(&self, account_id: AccountId) -> Result<u64> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(count), 0) FROM request_counts WHERE account_id = ?1",
+            [account_id.to_string()],
+            |row| row.get(0),
+        )?;
+
+        Ok(count as u64)
+    }
+
+    /// Proxy request total persisted by `save_total_request_count`, 0 if never saved
+    pub fn total_request_count(&self) -> Result<u64> {
+        match self.get_metadata(TOTAL_REQUEST_COUNT_KEY)? {
+            Some(value) => value
+                .parse()
+                .with_context(|| format!("Invalid stored request count {:?}", value)),
+            None => Ok(0),
+        }
+    }
+
+    /// Persist the proxy's lifetime request total so it survives restarts
+    pub fn save_total_request_count(&self, count: u64) -> Result<()> {
+        self.set_metadata(TOTAL_REQUEST_COUNT_KEY, &count.to_string())
+    }
+
+    /// Routing engine snapshot saved by `save_engine_state`, if any
+    pub fn engine_state(&self) -> Result<Option<String>> {
+        self.get_metadata(ENGINE_STATE_KEY)
+    }
+
+    /// Persist a `RoutingEngine::export_state` snapshot for the next run
+    pub fn save_engine_state(&self, state: &str) -> Result<()> {
+        self.set_metadata(ENGINE_STATE_KEY, state)
+    }
+
     /// Get database metadata
     pub fn get_metadata(&self, key: &str) -> Result<Option<String>> {
         let value = self
             .conn
             .query_row("SELECT value FROM metadata WHERE key = ?1", [key], |row| {
-                row.get::<String, _>(0)
+                row.get::<_, String>(0)
             })
             .optional()?;
 
@@ -390,23 +890,246 @@ impl EncryptedStore {
 
         Ok(())
     }
+
+    /// Compact the database file, returning how many bytes were reclaimed.
+    ///
+    /// Pruned snapshots and deleted accounts leave free pages behind that
+    /// SQLite only returns to the filesystem on `VACUUM`.
+    pub fn vacuum(&self) -> Result<u64> {
+        let before = database_size(&self.conn)?;
+
+        self.conn.execute_batch("VACUUM").context("Failed to vacuum database")?;
+        // Also empties the WAL file; a no-op for databases not in WAL mode
+        self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .context("Failed to checkpoint database")?;
+
+        let after = database_size(&self.conn)?;
+        Ok(before.saturating_sub(after))
+    }
+
+    /// Try to decrypt every stored api_key, without failing on the ones that
+    /// don't, and describe the key derivation in use
+    pub fn key_status(&self) -> Result<KeyStatus> {
+        let mut stmt = self.conn.prepare("SELECT label, api_key_encrypted FROM accounts")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let undecryptable: Vec<String> = rows
+            .iter()
+            .filter(|(_, stored)| self.decrypt(stored).is_err())
+            .map(|(label, _)| label.clone())
+            .collect();
+
+        Ok(KeyStatus {
+            accounts: rows.len(),
+            decryptable: rows.len() - undecryptable.len(),
+            undecryptable,
+            kdf: self.secrets.kdf_params(),
+        })
+    }
+
+    /// Delete every account, usage snapshot and request count in one
+    /// transaction. The schema and other store metadata are kept.
+    pub fn purge_all(&self) -> Result<PurgeSummary> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let usage_snapshots = tx.execute("DELETE FROM usage_snapshots", [])?;
+        tx.execute("DELETE FROM request_counts", [])?;
+        tx.execute("DELETE FROM account_capabilities", [])?;
+        tx.execute(
+            "DELETE FROM metadata WHERE key IN (?1, ?2)",
+            [TOTAL_REQUEST_COUNT_KEY, ENGINE_STATE_KEY],
+        )?;
+        let accounts = tx.execute("DELETE FROM accounts", [])?;
+
+        tx.commit().context("Failed to purge database")?;
+
+        Ok(PurgeSummary { accounts, usage_snapshots })
+    }
+}
+
+/// How the stored api_keys decrypt with the current master key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStatus {
+    pub accounts: usize,
+    pub decryptable: usize,
+    /// Labels of accounts whose key failed to decrypt
+    pub undecryptable: Vec<String>,
+    /// Key-derivation parameters, from `SecretStore::kdf_params`
+    pub kdf: Option<String>,
+}
+
+/// Rows removed by `EncryptedStore::purge_all`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PurgeSummary {
+    pub accounts: usize,
+    pub usage_snapshots: usize,
+}
+
+/// Timings from `benchmark`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StorageBench {
+    pub accounts: usize,
+    /// Opening the store, which derives the cipher key with Argon2 once;
+    /// saves and loads reuse it rather than deriving per row
+    pub open: Duration,
+    /// Encrypting and saving every account, one `save_account` each
+    pub save: Duration,
+    /// Loading and decrypting every account in one `load_accounts`
+    pub load: Duration,
+}
+
+impl StorageBench {
+    pub fn save_ops_per_sec(&self) -> f64 {
+        self.accounts as f64 / self.save.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn load_ops_per_sec(&self) -> f64 {
+        self.accounts as f64 / self.load.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Time opening a fresh database at `db_path`, then saving and loading
+/// `accounts` generated accounts. The file is left for the caller to remove.
+pub fn benchmark(db_path: &Path, master_key: &str, accounts: usize) -> Result<StorageBench> {
+    let started = Instant::now();
+    let store = EncryptedStore::open(db_path, master_key)?;
+    let open = started.elapsed();
+
+    let generated: Vec<Account> = (0..accounts)
+        .map(|i| Account::new(format!("bench-{}", i), format!("sk-bench-{:040}", i)))
+        .collect();
+
+    let started = Instant::now();
+    for account in &generated {
+        store.save_account(account)?;
+    }
+    let save = started.elapsed();
+
+    let started = Instant::now();
+    let loaded = store.load_accounts()?;
+    let load = started.elapsed();
+    anyhow::ensure!(
+        loaded.len() == accounts,
+        "Loaded {} of {} saved accounts",
+        loaded.len(),
+        accounts
+    );
+
+    Ok(StorageBench { accounts, open, save, load })
+}
+
+/// Database size in bytes, from its page count and page size
+fn database_size(conn: &Connection) -> Result<u64> {
+    let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get::<_, i64>(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get::<_, i64>(0))?;
+    Ok((pages * page_size) as u64)
+}
+
+/// Read the optional JSON-encoded `preferred_strategy` column of an accounts row
+fn preferred_strategy_from(row: &rusqlite::Row) -> rusqlite::Result<Option<RoutingStrategy>> {
+    row.get::<_, Option<String>>("preferred_strategy")?
+        .map(|json| {
+            serde_json::from_str(&json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+            })
+        })
+        .transpose()
+}
+
+/// Read a JSON array column such as `tags`; rows from before the column
+/// existed have none
+fn string_list_from(row: &rusqlite::Row, column: &str) -> rusqlite::Result<Vec<String>> {
+    row.get::<_, Option<String>>(column)?
+        .map(|json| {
+            serde_json::from_str(&json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+            })
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// Map a `usage_snapshots` row to a snapshot
+fn usage_from_row(row: &rusqlite::Row) -> rusqlite::Result<UsageSnapshot> {
+    Ok(UsageSnapshot {
+        account_id: row.get::<_, String>("account_id")?.parse().unwrap(),
+        tokens_used: row.get::<_, i64>("tokens_used")? as u64,
+        cost_estimate: row.get("cost_estimate")?,
+        hard_limit: row.get("hard_limit")?,
+        soft_limit: row.get("soft_limit")?,
+        remaining_budget: row.get("remaining_budget")?,
+        daily_usage: row.get("daily_usage")?,
+        monthly_usage: row.get("monthly_usage")?,
+        timestamp: row.get::<_, String>("timestamp")?.parse().unwrap(),
+        observed_at: row
+            .get::<_, Option<String>>("observed_at")?
+            .map(|at| at.parse().unwrap()),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Stand-in for a keyring: keeps secrets in memory and
+    /// stores only a handle
+    struct FakeSecrets {
+        secrets: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl SecretStore for FakeSecrets {
+        fn encrypt(&self, plaintext: &str) -> Result<String> {
+            let mut secrets = self.secrets.lock().unwrap();
+            secrets.push(plaintext.to_string());
+            Ok(format!("fake:{}", secrets.len() - 1))
+        }
+
+        fn decrypt(&self, stored: &str) -> Result<String> {
+            let index: usize = stored
+                .strip_prefix("fake:")
+                .and_then(|i| i.parse().ok())
+                .context("Not a fake secret handle")?;
+            self.secrets.lock().unwrap().get(index).cloned().context("Unknown secret")
+        }
+    }
+
+    #[test]
+    fn test_accounts_round_trip_through_secret_store() {
+        let secrets = FakeSecrets { secrets: std::sync::Mutex::new(Vec::new()) };
+        let store = EncryptedStore::open_in_memory_with_secrets(Box::new(secrets)).unwrap();
+
+        let account = Account::new("Keyring".to_string(), "sk-keyring-secret".to_string());
+        store.save_account(&account).unwrap();
+
+        let stored: String = store
+            .conn
+            .query_row("SELECT api_key_encrypted FROM accounts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, "fake:0");
+
+        let loaded = store.load_account(account.id).unwrap().unwrap();
+        assert_eq!(loaded.api_key, "sk-keyring-secret");
+        assert_eq!(store.load_accounts().unwrap()[0].api_key, "sk-keyring-secret");
+    }
+
     #[test]
     fn test_encrypted_storage() {
         let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
 
         // Create test account
-        let account = Account::new(
+        let mut account = Account::new(
             "Test Account".to_string(),
             "sk-test-secret-key-12345".to_string(),
         )
         .with_limits(Some(10.0), Some(100.0))
-        .with_priority(5);
+        .with_priority(5)
+        .with_region(Some("eu".to_string()))
+        .with_allowed_paths(vec!["/v1/embeddings".to_string()])
+        .with_cost_center(Some("research".to_string()));
+        account.pinned = true;
+        account.locked = true;
 
         // Save account
         store.save_account(&account).unwrap();
@@ -414,6 +1137,11 @@ mod tests {
         // Load and verify
         let loaded = store.load_account(account.id).unwrap().unwrap();
         assert_eq!(loaded.label, account.label);
+        assert_eq!(loaded.region.as_deref(), Some("eu"));
+        assert_eq!(loaded.allowed_paths, vec!["/v1/embeddings".to_string()]);
+        assert_eq!(loaded.cost_center.as_deref(), Some("research"));
+        assert!(loaded.pinned);
+        assert!(loaded.locked);
         assert_eq!(loaded.api_key, account.api_key);
         assert_eq!(loaded.daily_limit, Some(10.0));
         assert_eq!(loaded.priority, 5);
@@ -429,4 +1157,375 @@ mod tests {
         assert_eq!(loaded_snapshot.daily_usage, 5.0);
         assert_eq!(loaded_snapshot.monthly_usage, 50.0);
     }
+
+    #[test]
+    fn test_request_counts() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Counted".to_string(), "sk-test".to_string());
+        let other = Account::new("Other".to_string(), "sk-other".to_string());
+
+        assert_eq!(store.requests_today(account.id).unwrap(), 0);
+
+        let today = Utc::now().date_naive();
+        store.add_request_count(account.id, today, 2).unwrap();
+        store.add_request_count(account.id, today, 3).unwrap();
+
+        assert_eq!(store.requests_today(account.id).unwrap(), 5);
+        assert_eq!(store.requests_today(other.id).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_benchmark_reports_throughput() {
+        let path = std::env::temp_dir().join(format!("cam-bench-{}.db", uuid::Uuid::new_v4()));
+
+        let bench = benchmark(&path, "test_master_key", 5).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(bench.accounts, 5);
+        assert!(bench.save_ops_per_sec() > 0.0);
+        assert!(bench.load_ops_per_sec() > 0.0);
+    }
+
+    #[test]
+    fn test_request_counts_survive_reopen() {
+        let path = std::env::temp_dir().join(format!("cam-counts-{}.db", uuid::Uuid::new_v4()));
+        let account = Account::new("Counted".to_string(), "sk-test".to_string());
+
+        {
+            let store = EncryptedStore::open(&path, "test_master_key").unwrap();
+            assert_eq!(store.total_request_count().unwrap(), 0);
+            store.save_total_request_count(41).unwrap();
+            store.add_request_count(account.id, NaiveDate::from_ymd_opt(2024, 3, 30).unwrap(), 4).unwrap();
+            store.add_request_count(account.id, NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(), 6).unwrap();
+        }
+
+        let store = EncryptedStore::open(&path, "test_master_key").unwrap();
+        assert_eq!(store.total_request_count().unwrap(), 41);
+        assert_eq!(store.lifetime_requests(account.id).unwrap(), 10);
+
+        // Later runs save their running total on top of the restored one
+        store.save_total_request_count(42).unwrap();
+        assert_eq!(store.total_request_count().unwrap(), 42);
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_cost_center_report_aggregates_per_center() {
+        use chrono::TimeZone;
+
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let snapshot = |account: &Account, d: u32, monthly: f64| {
+            let mut snapshot = UsageSnapshot::new(account.id);
+            snapshot.monthly_usage = monthly;
+            snapshot.timestamp = Utc.with_ymd_and_hms(2024, 3, d, 12, 0, 0).unwrap();
+            snapshot
+        };
+
+        let research = |label: &str| {
+            Account::new(label.to_string(), "sk-test".to_string())
+                .with_cost_center(Some("research".to_string()))
+        };
+        let first = research("Research 1");
+        let second = research("Research 2");
+        let sales = Account::new("Sales".to_string(), "sk-test".to_string())
+            .with_cost_center(Some("sales".to_string()));
+        let unassigned = Account::new("Loose".to_string(), "sk-test".to_string());
+        for account in [&first, &second, &sales, &unassigned] {
+            store.save_account(account).unwrap();
+        }
+
+        // $10 before the range is the baseline, so only $15 falls inside it
+        store.save_usage_snapshot(&snapshot(&first, 1, 10.0)).unwrap();
+        store.save_usage_snapshot(&snapshot(&first, 5, 20.0)).unwrap();
+        store.save_usage_snapshot(&snapshot(&first, 9, 25.0)).unwrap();
+        store.save_usage_snapshot(&snapshot(&second, 6, 5.0)).unwrap();
+        store.save_usage_snapshot(&snapshot(&sales, 7, 7.5)).unwrap();
+        // After the range: not counted
+        store.save_usage_snapshot(&snapshot(&sales, 20, 30.0)).unwrap();
+        store.add_request_count(first.id, day(5), 3).unwrap();
+        store.add_request_count(second.id, day(6), 4).unwrap();
+        store.add_request_count(sales.id, day(20), 9).unwrap();
+
+        let report = store.cost_center_report(day(2), day(10)).unwrap();
+        let centers: Vec<_> = report
+            .centers
+            .iter()
+            .map(|c| (c.cost_center.as_deref(), c.accounts, c.spend, c.requests))
+            .collect();
+        assert_eq!(
+            centers,
+            vec![
+                (None, 1, 0.0, 0),
+                (Some("research"), 2, 20.0, 7),
+                (Some("sales"), 1, 7.5, 0),
+            ]
+        );
+
+        assert_eq!(
+            report.to_csv(),
+            "cost_center,accounts,spend,requests\n,1,0.00,0\nresearch,2,20.00,7\nsales,1,7.50,0\n"
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["centers"][1]["spend"], 20.0);
+    }
+
+    #[test]
+    fn test_key_status_counts_decryptable_accounts() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        for label in ["First", "Second"] {
+            store.save_account(&Account::new(label.to_string(), "sk-test".to_string())).unwrap();
+        }
+
+        let status = store.key_status().unwrap();
+        assert_eq!(status.accounts, 2);
+        assert_eq!(status.decryptable, 2);
+        assert!(status.undecryptable.is_empty());
+        assert!(status.kdf.unwrap().starts_with("Argon2id"));
+
+        store
+            .conn
+            .execute("UPDATE accounts SET api_key_encrypted = 'garbage' WHERE label = 'Second'", [])
+            .unwrap();
+        let status = store.key_status().unwrap();
+        assert_eq!(status.decryptable, 1);
+        assert_eq!(status.undecryptable, vec!["Second".to_string()]);
+    }
+
+    #[test]
+    fn test_write_failures_are_typed() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Writes".to_string(), "sk-test".to_string());
+        store.save_account(&account).unwrap();
+
+        // A read-only database refuses writes with an I/O error
+        store.conn.execute_batch("PRAGMA query_only = ON").unwrap();
+        let err = store.save_account(&account).unwrap_err();
+        assert!(matches!(err.downcast_ref::<StorageError>(), Some(StorageError::Io(_))));
+        assert!(err.to_string().contains("Failed to save account"));
+        store.conn.execute_batch("PRAGMA query_only = OFF").unwrap();
+
+        // Capping the page count makes the database "full"
+        store.conn.execute_batch("PRAGMA max_page_count = 1").unwrap();
+        let mut full = None;
+        for _ in 0..1000 {
+            if let Err(e) = store.save_usage_snapshot(&UsageSnapshot::new(account.id)) {
+                full = Some(e);
+                break;
+            }
+        }
+        let err = full.expect("writes should fail once the database is full");
+        assert!(matches!(err.downcast_ref::<StorageError>(), Some(StorageError::DiskFull)));
+    }
+
+    #[test]
+    fn test_update_account_key_keeps_history() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Rotated".to_string(), "sk-old-key".to_string());
+        store.save_account(&account).unwrap();
+        let mut usage = UsageSnapshot::new(account.id);
+        usage.monthly_usage = 12.5;
+        store.save_usage_snapshot(&usage).unwrap();
+
+        assert!(store.update_account_key(account.id, "sk-new-key").unwrap());
+
+        let rotated = store.load_account(account.id).unwrap().unwrap();
+        assert_eq!(rotated.api_key, "sk-new-key");
+        assert_eq!(rotated.label, account.label);
+        assert_eq!(rotated.created_at, account.created_at);
+        let history = store.load_usage_history(account.id, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].monthly_usage, 12.5);
+
+        assert!(!store.update_account_key(uuid::Uuid::new_v4(), "sk-new-key").unwrap());
+    }
+
+    #[test]
+    fn test_capabilities_round_trip() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Probed".to_string(), "sk-test".to_string());
+        store.save_account(&account).unwrap();
+
+        let mut capabilities = AccountCapabilities {
+            account_id: account.id,
+            valid: true,
+            org_id: Some("org-probed".to_string()),
+            available_models: vec!["gpt-4o".to_string()],
+            supports_usage_api: false,
+            checked_at: Utc::now(),
+        };
+        store.save_capabilities(&capabilities).unwrap();
+        capabilities.available_models.push("gpt-4o-mini".to_string());
+        store.save_capabilities(&capabilities).unwrap();
+
+        assert_eq!(store.load_capabilities().unwrap(), vec![capabilities]);
+
+        store.delete_account(account.id).unwrap();
+        assert!(store.load_capabilities().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unpolled_usage_flushed_and_reloaded() {
+        use crate::routing::RoutingEngine;
+
+        let path = std::env::temp_dir().join(format!("cam-usage-{}.db", uuid::Uuid::new_v4()));
+        let account = Account::new("Counted".to_string(), "sk-test".to_string());
+        let unpolled = Account::new("Unpolled".to_string(), "sk-other".to_string());
+        let mut polled = UsageSnapshot::new(account.id);
+        polled.timestamp = Utc::now() - chrono::Duration::hours(2);
+
+        {
+            let store = EncryptedStore::open(&path, "test_master_key").unwrap();
+            store.save_account(&account).unwrap();
+            store.save_account(&unpolled).unwrap();
+            polled.tokens_used = 1_000;
+            polled.monthly_usage = 4.0;
+            polled.daily_usage = 1.0;
+            polled.hard_limit = Some(100.0);
+            polled.remaining_budget = Some(96.0);
+            store.save_usage_snapshot(&polled).unwrap();
+
+            // Responses proxied after the poll, flushed on shutdown
+            let engine = RoutingEngine::new(RoutingStrategy::LeastUtilized);
+            engine.record_unpolled_usage(account.id, 300, 0.5);
+            engine.record_unpolled_usage(account.id, 200, 0.25);
+            engine.record_unpolled_usage(unpolled.id, 100, 0.1);
+            for (id, delta) in engine.take_unpolled_usage() {
+                let saved = store.save_usage_delta(id, delta).unwrap();
+                assert_eq!(saved.is_some(), id == account.id);
+            }
+            assert!(engine.take_unpolled_usage().is_empty());
+        }
+
+        let store = EncryptedStore::open(&path, "test_master_key").unwrap();
+        let loaded = store.load_latest_usage(account.id).unwrap().unwrap();
+        assert_eq!(loaded.tokens_used, 1_500);
+        assert!((loaded.monthly_usage - 4.75).abs() < 1e-9);
+        assert!((loaded.daily_usage - 1.75).abs() < 1e-9);
+        assert_eq!(loaded.remaining_budget, Some(95.25));
+        assert_eq!(loaded.hard_limit, Some(100.0));
+
+        // The flush doesn't make a two-hour-old poll look fresh
+        assert_eq!(loaded.timestamp, polled.timestamp);
+        assert!(loaded.observed_at.is_some());
+        assert!(loaded.is_stale(chrono::Duration::hours(1)));
+
+        // No snapshot is invented for an account that was never polled
+        assert!(store.load_latest_usage(unpolled.id).unwrap().is_none());
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_request_counts_roll_over_by_day() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let account = Account::new("Counted".to_string(), "sk-test".to_string());
+
+        let yesterday = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let today = yesterday.succ_opt().unwrap();
+
+        store.add_request_count(account.id, yesterday, 7).unwrap();
+        store.add_request_count(account.id, today, 2).unwrap();
+
+        assert_eq!(store.requests_on(account.id, yesterday).unwrap(), 7);
+        assert_eq!(store.requests_on(account.id, today).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_auto_disable_reason_persists() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let mut account = Account::new("Quota".to_string(), "sk-test".to_string());
+        store.save_account(&account).unwrap();
+
+        account.auto_disable("Monthly budget exhausted");
+        store.save_account(&account).unwrap();
+
+        let reloaded = store.load_account(account.id).unwrap().unwrap();
+        assert!(!reloaded.enabled);
+        assert_eq!(reloaded.disabled_reason.as_deref(), Some("Monthly budget exhausted"));
+        assert!(reloaded.disabled_at.is_some());
+        assert_eq!(store.load_accounts().unwrap()[0].disabled_reason, reloaded.disabled_reason);
+
+        // Re-enabling by hand clears the automatic reason
+        let mut enabled = reloaded;
+        enabled.set_enabled(true);
+        store.save_account(&enabled).unwrap();
+
+        let reloaded = store.load_account(account.id).unwrap().unwrap();
+        assert!(reloaded.enabled);
+        assert_eq!(reloaded.disabled_reason, None);
+        assert_eq!(reloaded.disabled_at, None);
+    }
+
+    #[test]
+    fn test_migrate_adds_disabled_columns() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        store
+            .conn
+            .execute_batch("ALTER TABLE accounts DROP COLUMN disabled_reason; ALTER TABLE accounts DROP COLUMN disabled_at;")
+            .unwrap();
+
+        store.migrate_schema().unwrap();
+
+        let account = Account::new("Migrated".to_string(), "sk-test".to_string());
+        store.save_account(&account).unwrap();
+        assert!(store.load_account(account.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_vacuum_keeps_data_intact() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let kept = Account::new("Kept".to_string(), "sk-kept".to_string());
+        let pruned = Account::new("Pruned".to_string(), "sk-pruned".to_string());
+        store.save_account(&kept).unwrap();
+        store.save_account(&pruned).unwrap();
+
+        for _ in 0..2000 {
+            store.save_usage_snapshot(&UsageSnapshot::new(pruned.id)).unwrap();
+        }
+        let mut latest = UsageSnapshot::new(kept.id);
+        latest.monthly_usage = 42.0;
+        store.save_usage_snapshot(&latest).unwrap();
+
+        store.delete_account(pruned.id).unwrap();
+        store.vacuum().unwrap();
+
+        let integrity: String = store
+            .conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))
+            .unwrap();
+        assert_eq!(integrity, "ok");
+
+        assert_eq!(store.load_accounts().unwrap(), vec![kept.clone()]);
+        assert_eq!(store.load_latest_usage(kept.id).unwrap().unwrap().monthly_usage, 42.0);
+    }
+
+    #[test]
+    fn test_purge_all_empties_store_but_keeps_schema() {
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let accounts: Vec<Account> = (0..3)
+            .map(|i| Account::new(format!("Account {}", i), format!("sk-account-{}", i)))
+            .collect();
+        for account in &accounts {
+            store.save_account(account).unwrap();
+            store.save_usage_snapshot(&UsageSnapshot::new(account.id)).unwrap();
+            store.add_request_count(account.id, Utc::now().date_naive(), 1).unwrap();
+        }
+        store.set_metadata("schema_note", "kept").unwrap();
+
+        let summary = store.purge_all().unwrap();
+        assert_eq!(summary, PurgeSummary { accounts: 3, usage_snapshots: 3 });
+
+        assert!(store.load_accounts().unwrap().is_empty());
+        assert!(store.load_latest_usage(accounts[0].id).unwrap().is_none());
+        assert_eq!(store.requests_today(accounts[0].id).unwrap(), 0);
+        assert_eq!(store.get_metadata("schema_note").unwrap().as_deref(), Some("kept"));
+
+        // The tables are still there and usable
+        store.save_account(&accounts[0]).unwrap();
+        assert_eq!(store.load_accounts().unwrap(), vec![accounts[0].clone()]);
+    }
 }
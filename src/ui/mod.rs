@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
@@ -6,30 +6,39 @@ use crossterm::{
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols,
-    text::{Line, Span, Text},
+    text::{Line, Span},
     widgets::{
-        Block, Borders, Cell, Clear, Gauge, List, ListItem, Paragraph, Row, Sparkline, Table,
-        Tabs, Wrap,
+        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Tabs,
+        Wrap,
     },
     Frame, Terminal,
 };
+use std::collections::HashSet;
 use std::io;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
 use tracing::{error, info};
 use uuid::Uuid;
 
 use crate::{
-    config::Config,
-    models::{Account, UsageSnapshot},
-    routing::{RoutingEngine, RoutingStats, RoutingStrategy},
-    storage::EncryptedStore,
+    config::{BudgetThreshold, Config},
+    models::{Account, FieldError, RateLimitSnapshot, UsageSnapshot},
+    routing::RoutingEngine,
+    storage::{EncryptedStore, StorageError},
 };
 
+/// Number of tabs in the header
+const TAB_COUNT: usize = 4;
+
+/// Most recent snapshots plotted on the history tab
+const HISTORY_LIMIT: usize = 500;
+
+/// Group for accounts without tags in the grouped accounts view
+const UNTAGGED_GROUP: &str = "untagged";
+
 /// CLI Application
 pub struct CliApp {
     store: EncryptedStore,
@@ -37,8 +46,12 @@ pub struct CliApp {
     config: Config,
     accounts: Vec<Account>,
     usage_data: std::collections::HashMap<Uuid, UsageSnapshot>,
+    rate_limits: std::collections::HashMap<Uuid, RateLimitSnapshot>,
+    usage_history: Vec<UsageSnapshot>, // selected account, oldest first
     selected_tab: usize,
     selected_account: usize,
+    grouped_view: bool,                // accounts tab grouped by first tag
+    expanded_groups: HashSet<String>,  // groups showing their accounts
     show_add_dialog: bool,
     show_delete_confirm: bool,
     new_account_label: String,
@@ -58,8 +71,12 @@ impl CliApp {
             config,
             accounts: Vec::new(),
             usage_data: std::collections::HashMap::new(),
+            rate_limits: std::collections::HashMap::new(),
+            usage_history: Vec::new(),
             selected_tab: 0,
             selected_account: 0,
+            grouped_view: false,
+            expanded_groups: HashSet::new(),
             show_add_dialog: false,
             show_delete_confirm: false,
             new_account_label: String::new(),
@@ -116,7 +133,8 @@ impl CliApp {
     async fn refresh_data(&mut self,
     ) {
         match self.store.load_accounts() {
-            Ok(accounts) => {
+            Ok(mut accounts) => {
+                pinned_first(&mut accounts);
                 self.accounts = accounts;
 
                 // Load usage for each account
@@ -126,10 +144,27 @@ impl CliApp {
                     }
                 }
 
+                self.load_selected_history();
+
+                // Probes written by a running proxy
+                match self.store.load_capabilities() {
+                    Ok(probes) => probes
+                        .into_iter()
+                        .for_each(|c| self.routing_engine.set_capabilities(c)),
+                    Err(e) => error!("Failed to load account capabilities: {}", e),
+                }
+
                 // Update routing engine
                 self.routing_engine
                     .update_accounts(self.accounts.clone(), self.usage_data.clone())
                     .await;
+                self.rate_limits = self
+                    .routing_engine
+                    .account_statuses()
+                    .await
+                    .into_iter()
+                    .filter_map(|s| Some((s.account.id, s.rate_limit?)))
+                    .collect();
             }
             Err(e) => {
                 error!("Failed to load accounts: {}", e);
@@ -137,6 +172,20 @@ impl CliApp {
         }
     }
 
+    /// Load usage history for the selected account
+    fn load_selected_history(&mut self) {
+        self.usage_history = match self.accounts.get(self.selected_account) {
+            Some(account) => self
+                .store
+                .load_usage_history(account.id, HISTORY_LIMIT)
+                .unwrap_or_else(|e| {
+                    error!("Failed to load usage history: {}", e);
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+    }
+
     /// Handle keyboard events
     async fn handle_key_event(
         &mut self,
@@ -154,40 +203,70 @@ impl CliApp {
         match key.code {
             KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
             KeyCode::Tab | KeyCode::Right => {
-                self.selected_tab = (self.selected_tab + 1) % 3;
+                self.selected_tab = (self.selected_tab + 1) % TAB_COUNT;
             }
             KeyCode::BackTab | KeyCode::Left => {
-                self.selected_tab = (self.selected_tab + 2) % 3;
+                self.selected_tab = (self.selected_tab + TAB_COUNT - 1) % TAB_COUNT;
             }
             KeyCode::Char('a') => {
                 self.show_add_dialog = true;
                 self.new_account_label.clear();
                 self.new_account_key.clear();
             }
-            KeyCode::Char('d') => {
-                if !self.accounts.is_empty() {
-                    self.show_delete_confirm = true;
-                }
+            KeyCode::Char('d') if !self.accounts.is_empty() => {
+                self.show_delete_confirm = true;
             }
             KeyCode::Char('e') => {
                 self.toggle_account_enabled().await?;
             }
+            KeyCode::Char('p') if self.selected_tab == 0 => {
+                self.toggle_account_pinned().await?;
+            }
             KeyCode::Char('r') => {
                 self.refresh_data().await;
                 self.status_message = Some("Data refreshed".to_string());
             }
-            KeyCode::Up => {
-                if self.selected_tab == 0 && !self.accounts.is_empty() {
-                    self.selected_account =
-                        self.selected_account.saturating_sub(1);
+            KeyCode::Char('g') if self.selected_tab == 0 => {
+                self.grouped_view = !self.grouped_view;
+            }
+            KeyCode::Char('x') if self.selected_tab == 0 && self.grouped_view => {
+                if let Some(account) = self.accounts.get(self.selected_account) {
+                    let group = group_name(account).to_string();
+                    if !self.expanded_groups.remove(&group) {
+                        self.expanded_groups.insert(group);
+                    }
                 }
             }
-            KeyCode::Down => {
-                if self.selected_tab == 0 && !self.accounts.is_empty() {
-                    self.selected_account =
-                        (self.selected_account + 1).min(self.accounts.len() - 1);
+            KeyCode::Up | KeyCode::Down if self.selected_tab == 0 && self.grouped_view => {
+                let groups = group_accounts(&self.accounts, &self.usage_data);
+                let stops = group_stops(&groups, &self.expanded_groups);
+                let current = groups
+                    .iter()
+                    .find(|g| g.members.contains(&self.selected_account))
+                    .and_then(|g| {
+                        stops.iter().position(|&i| i == self.selected_account).or_else(|| {
+                            stops.iter().position(|&i| i == g.members[0])
+                        })
+                    });
+
+                if let Some(current) = current {
+                    let next = if key.code == KeyCode::Up {
+                        current.saturating_sub(1)
+                    } else {
+                        (current + 1).min(stops.len() - 1)
+                    };
+                    self.selected_account = stops[next];
+                    self.load_selected_history();
                 }
             }
+            KeyCode::Up if matches!(self.selected_tab, 0 | 3) && !self.accounts.is_empty() => {
+                self.selected_account = self.selected_account.saturating_sub(1);
+                self.load_selected_history();
+            }
+            KeyCode::Down if matches!(self.selected_tab, 0 | 3) && !self.accounts.is_empty() => {
+                self.selected_account = (self.selected_account + 1).min(self.accounts.len() - 1);
+                self.load_selected_history();
+            }
             _ => {}
         }
 
@@ -203,11 +282,11 @@ impl CliApp {
             KeyCode::Esc => {
                 self.show_add_dialog = false;
             }
-            KeyCode::Enter => {
-                if !self.new_account_label.is_empty() && !self.new_account_key.is_empty() {
-                    self.add_account().await?;
-                    self.show_add_dialog = false;
-                }
+            KeyCode::Enter
+                if !self.new_account_label.is_empty() && !self.new_account_key.is_empty() =>
+            {
+                self.add_account().await?;
+                self.show_add_dialog = false;
             }
             KeyCode::Tab => {
                 // Toggle between fields
@@ -260,7 +339,15 @@ impl CliApp {
             self.new_account_key.clone(),
         );
 
-        self.store.save_account(&account)?;
+        if let Err(errors) = account.validate() {
+            self.status_message = Some(format!("Invalid account: {}", FieldError::join(&errors)));
+            return Ok(());
+        }
+
+        if let Err(e) = self.store.save_account(&account) {
+            self.status_message = Some(storage_status(e)?);
+            return Ok(());
+        }
         self.refresh_data().await;
         self.status_message = Some(format!("Added account: {}", account.label));
 
@@ -276,6 +363,11 @@ impl CliApp {
             let id = account.id;
             let label = account.label.clone();
 
+            if let Err(errors) = account.validate_removal() {
+                self.status_message = Some(format!("Cannot delete {}: {}", label, FieldError::join(&errors)));
+                return Ok(());
+            }
+
             self.store.delete_account(id)?;
             self.refresh_data().await;
 
@@ -296,17 +388,66 @@ impl CliApp {
         &mut self,
     ) -> Result<()> {
         if let Some(account) = self.accounts.get_mut(self.selected_account) {
-            account.enabled = !account.enabled;
-            self.store.save_account(account)?;
+            account.set_enabled(!account.enabled);
+            if let Err(e) = self.store.save_account(account) {
+                self.status_message = Some(storage_status(e)?);
+                return Ok(());
+            }
+            let account = account.clone();
             self.refresh_data().await;
 
             let status = if account.enabled { "enabled" } else { "disabled" };
+            let mut message = format!("{} {}", account.label, status);
+
+            if account.enabled && self.config.routing.warmup_on_enable {
+                let outcome = match self.warm_up(&account).await {
+                    Ok(latency) => format!(", warmed up in {} ms", latency.as_millis()),
+                    Err(e) => format!(", warmup failed: {}", e),
+                };
+                message.push_str(&outcome);
+            }
+
+            self.status_message = Some(message);
+        }
+
+        Ok(())
+    }
+
+    /// Pin the selected account to the top of the list, or unpin it
+    async fn toggle_account_pinned(&mut self) -> Result<()> {
+        if let Some(account) = self.accounts.get_mut(self.selected_account) {
+            account.pinned = !account.pinned;
+            if let Err(e) = self.store.save_account(account) {
+                self.status_message = Some(storage_status(e)?);
+                return Ok(());
+            }
+            let account = account.clone();
+            self.refresh_data().await;
+
+            // Keep the cursor on the account as it moves
+            if let Some(i) = self.accounts.iter().position(|a| a.id == account.id) {
+                self.selected_account = i;
+                self.load_selected_history();
+            }
+
+            let status = if account.pinned { "pinned" } else { "unpinned" };
             self.status_message = Some(format!("{} {}", account.label, status));
         }
 
         Ok(())
     }
 
+    /// Prime a newly enabled account and seed its latency in the routing engine
+    async fn warm_up(&self, account: &Account) -> Result<Duration> {
+        let client = self.config.openai_client()?;
+
+        let latency = client.warm_up(account).await?;
+        self.routing_engine.record_latency(account.id, latency);
+        self.routing_engine.report_success(account.id);
+
+        Ok(latency)
+    }
+
     /// Draw the UI
     fn draw(&self,
         f: &mut Frame,
@@ -328,6 +469,7 @@ impl CliApp {
             0 => self.draw_accounts_tab(f, chunks[1]),
             1 => self.draw_routing_tab(f, chunks[1]),
             2 => self.draw_status_tab(f, chunks[1]),
+            3 => self.draw_history_tab(f, chunks[1]),
             _ => {}
         }
 
@@ -350,7 +492,7 @@ impl CliApp {
         f: &mut Frame,
         area: Rect,
     ) {
-        let titles = vec!["Accounts", "Routing", "Status"];
+        let titles = vec!["Accounts", "Routing", "Status", "History"];
         let tabs = Tabs::new(titles)
             .select(self.selected_tab)
             .style(Style::default().fg(Color::White))
@@ -380,36 +522,70 @@ impl CliApp {
             .split(area);
 
         // Account list
-        let items: Vec<ListItem> = self
-            .accounts
-            .iter()
-            .enumerate()
-            .map(|(i, acc)| {
-                let usage = self.usage_data.get(&acc.id);
-                let util = usage.map(|u| u.utilization_ratio()).unwrap_or(0.0);
-
-                let status = if acc.enabled { "●" } else { "○" };
-                let style = if i == self.selected_account {
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
-                } else if !acc.enabled {
-                    Style::default().fg(Color::Gray)
+        let account_item = |i: usize, acc: &Account, indent: &str| {
+            let usage = self.usage_data.get(&acc.id);
+            let util = usage.map(|u| u.utilization_ratio()).unwrap_or(0.0);
+
+            let status = if acc.enabled { "●" } else { "○" };
+            let pin = if acc.pinned { "📌 " } else { "" };
+            let style = if i == self.selected_account {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if !acc.enabled {
+                Style::default().fg(Color::Gray)
+            } else {
+                Style::default()
+            };
+
+            let text = format!(
+                "{}{} {}{} (P:{}) - {:.1}%",
+                indent, status, pin, acc.label, acc.priority, util * 100.0
+            );
+
+            ListItem::new(text).style(style)
+        };
+
+        let (items, title): (Vec<ListItem>, _) = if self.grouped_view {
+            let mut items = Vec::new();
+            for group in group_accounts(&self.accounts, &self.usage_data) {
+                let expanded = self.expanded_groups.contains(&group.name);
+                let selected = !expanded && group.members.contains(&self.selected_account);
+                let style = if selected {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
                 } else {
-                    Style::default()
+                    Style::default().add_modifier(Modifier::BOLD)
                 };
 
                 let text = format!(
-                    "{} {} (P:{}) - {:.1}%",
-                    status, acc.label, acc.priority, util * 100.0
+                    "{} {} ({}) - {:.1}% - ${:.2} left",
+                    if expanded { "▾" } else { "▸" },
+                    group.name,
+                    group.members.len(),
+                    group.utilization * 100.0,
+                    group.remaining_budget
                 );
+                items.push(ListItem::new(text).style(style));
 
-                ListItem::new(text).style(style)
-            })
-            .collect();
+                if expanded {
+                    for &i in &group.members {
+                        items.push(account_item(i, &self.accounts[i], "  "));
+                    }
+                }
+            }
+            (items, "Accounts by tag [g:ungroup x:expand a:add d:delete e:toggle]")
+        } else {
+            let items = self
+                .accounts
+                .iter()
+                .enumerate()
+                .map(|(i, acc)| account_item(i, acc, ""))
+                .collect();
+            (items, "Accounts [a:add d:delete e:toggle g:group]")
+        };
 
         let list = List::new(items)
-            .block(Block::default().title("Accounts [a:add d:delete e:toggle]").borders(Borders::ALL));
+            .block(Block::default().title(title).borders(Borders::ALL));
 
         f.render_widget(list, chunks[0]);
 
@@ -458,6 +634,64 @@ impl CliApp {
         f.render_widget(paragraph, area);
     }
 
+    /// Draw usage history chart for the selected account
+    fn draw_history_tab(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+    ) {
+        let Some(account) = self.accounts.get(self.selected_account) else {
+            let paragraph = Paragraph::new("No accounts configured")
+                .block(Block::default().title("Usage History").borders(Borders::ALL));
+            f.render_widget(paragraph, area);
+            return;
+        };
+
+        let title = format!("Usage History: {} [↑↓:account]", account.label);
+        let points = usage_chart_series(&self.usage_history);
+
+        if points.is_empty() {
+            let paragraph = Paragraph::new("No usage history recorded yet")
+                .block(Block::default().title(title).borders(Borders::ALL));
+            f.render_widget(paragraph, area);
+            return;
+        }
+
+        let (x_bounds, y_bounds) = chart_bounds(&points);
+        let first = self.usage_history.first().map(|u| u.timestamp);
+        let last = self.usage_history.last().map(|u| u.timestamp);
+        let time_label = |t: Option<chrono::DateTime<chrono::Utc>>| {
+            Span::raw(t.map(|t| t.format("%m-%d %H:%M").to_string()).unwrap_or_default())
+        };
+
+        let dataset = Dataset::default()
+            .name("Monthly usage ($)")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&points);
+
+        let chart = Chart::new(vec![dataset])
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .x_axis(
+                Axis::default()
+                    .title("Time")
+                    .bounds(x_bounds)
+                    .labels(vec![time_label(first), time_label(last)]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("$")
+                    .bounds(y_bounds)
+                    .labels(vec![
+                        Span::raw(format!("{:.2}", y_bounds[0])),
+                        Span::raw(format!("{:.2}", y_bounds[1])),
+                    ]),
+            );
+
+        f.render_widget(chart, area);
+    }
+
     /// Draw footer with help and status
     fn draw_footer(
         &self,
@@ -465,7 +699,7 @@ impl CliApp {
         area: Rect,
     ) {
         let help_text =
-            "q:Quit | Tab:Next Tab | ↑↓:Navigate | a:Add | d:Delete | e:Toggle | r:Refresh";
+            "q:Quit | Tab:Next Tab | ↑↓:Navigate | a:Add | d:Delete | e:Toggle | p:Pin | r:Refresh";
 
         let text = if let Some(status) = &self.status_message {
             format!("{} | Status: {}", help_text, status)
@@ -473,7 +707,23 @@ impl CliApp {
             help_text.to_string()
         };
 
-        let paragraph = Paragraph::new(text)
+        let mut lines = vec![Line::from(text)];
+
+        if let Some(threshold) = &self.config.ui.low_budget_alert {
+            let low = low_budget_accounts(&self.accounts, &self.usage_data, threshold);
+            if !low.is_empty() {
+                let labels: Vec<&str> = low.iter().map(|a| a.label.as_str()).collect();
+                lines.insert(
+                    0,
+                    Line::from(Span::styled(
+                        format!("⚠ Low budget: {}", labels.join(", ")),
+                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                    )),
+                );
+            }
+        }
+
+        let paragraph = Paragraph::new(lines)
             .style(Style::default().fg(Color::Gray))
             .alignment(Alignment::Center)
             .block(Block::default().borders(Borders::TOP));
@@ -545,13 +795,56 @@ impl CliApp {
             format!("Label: {}", account.label),
             format!("Priority: {}", account.priority),
             format!("Enabled: {}", account.enabled),
+            format!(
+                "Requests Today: {}",
+                self.store.requests_today(account.id).unwrap_or(0)
+            ),
             String::new(),
         ];
 
+        if let Some(reason) = &account.disabled_reason {
+            let since = account
+                .disabled_at
+                .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_default();
+            lines.push(format!("Disabled: {} {}", reason, since));
+        }
+
+        if let Some(last_error) = self.routing_engine.last_error(account.id) {
+            lines.push(format!("Last Error: {}", last_error));
+        }
+
+        if let Some(rate_limit) = self.rate_limits.get(&account.id) {
+            lines.push(format!("Rate Limit: {}", rate_limit));
+        }
+
+        if let Some(latency) = self.routing_engine.latency(account.id) {
+            lines.push(format!("Latency: {} ms", latency.as_millis()));
+        }
+
         if let Some(org) = &account.org_id {
             lines.push(format!("Org ID: {}", org));
         }
 
+        if !account.tags.is_empty() {
+            lines.push(format!("Tags: {}", account.tags.join(", ")));
+        }
+
+        if let Some(caps) = self.routing_engine.capabilities(account.id) {
+            lines.push(format!(
+                "Probed: {} ({})",
+                if caps.valid { "valid" } else { "invalid" },
+                caps.checked_at.format("%Y-%m-%d %H:%M UTC")
+            ));
+            if !caps.available_models.is_empty() {
+                lines.push(format!("  Models: {}", caps.available_models.len()));
+            }
+            lines.push(format!(
+                "  Usage API: {}",
+                if caps.supports_usage_api { "yes" } else { "no" }
+            ));
+        }
+
         if let Some(daily) = account.daily_limit {
             lines.push(format!("Daily Limit: ${:.2}", daily));
         }
@@ -576,6 +869,142 @@ impl CliApp {
     }
 }
 
+/// Status line for a storage write failure the user can act on; any other
+/// error is passed back to the caller
+fn storage_status(error: anyhow::Error) -> Result<String> {
+    match error.downcast_ref::<StorageError>() {
+        Some(storage) => Ok(storage.to_string()),
+        None => Err(error),
+    }
+}
+
+/// Accounts whose latest usage snapshot breaches the low-budget threshold
+fn low_budget_accounts<'a>(
+    accounts: &'a [Account],
+    usage_data: &std::collections::HashMap<Uuid, UsageSnapshot>,
+    threshold: &BudgetThreshold,
+) -> Vec<&'a Account> {
+    accounts
+        .iter()
+        .filter(|a| {
+            usage_data
+                .get(&a.id)
+                .map(|u| threshold.is_breached_by(u))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Accounts sharing a first tag, with totals over their latest snapshots
+#[derive(Debug, Clone, PartialEq)]
+struct AccountGroup {
+    name: String,
+    members: Vec<usize>, // indices into the account list
+    /// Total monthly usage over total hard limit of members that have one
+    utilization: f64,
+    remaining_budget: f64,
+}
+
+/// Group an account belongs to in the grouped view
+fn group_name(account: &Account) -> &str {
+    account.tags.first().map(String::as_str).unwrap_or(UNTAGGED_GROUP)
+}
+
+/// Group accounts by their first tag, sorted by name with untagged accounts last
+fn group_accounts(
+    accounts: &[Account],
+    usage_data: &std::collections::HashMap<Uuid, UsageSnapshot>,
+) -> Vec<AccountGroup> {
+    let mut groups: Vec<AccountGroup> = Vec::new();
+
+    for (i, account) in accounts.iter().enumerate() {
+        let name = group_name(account);
+        let group = match groups.iter().position(|g| g.name == name) {
+            Some(pos) => &mut groups[pos],
+            None => {
+                groups.push(AccountGroup {
+                    name: name.to_string(),
+                    members: Vec::new(),
+                    utilization: 0.0,
+                    remaining_budget: 0.0,
+                });
+                groups.last_mut().unwrap()
+            }
+        };
+        group.members.push(i);
+    }
+
+    for group in &mut groups {
+        let (mut used, mut limit) = (0.0, 0.0);
+        for &i in &group.members {
+            let Some(usage) = usage_data.get(&accounts[i].id) else {
+                continue;
+            };
+            if let Some(hard) = usage.hard_limit.filter(|h| *h > 0.0) {
+                used += usage.monthly_usage;
+                limit += hard;
+            }
+            group.remaining_budget += usage.remaining_budget.unwrap_or(0.0);
+        }
+        group.utilization = if limit > 0.0 { (used / limit).clamp(0.0, 1.0) } else { 0.0 };
+    }
+
+    groups.sort_by(|a, b| {
+        (a.name == UNTAGGED_GROUP, &a.name).cmp(&(b.name == UNTAGGED_GROUP, &b.name))
+    });
+    groups
+}
+
+/// Move pinned accounts to the front, keeping the stored order (priority,
+/// then age) within pinned and unpinned accounts
+fn pinned_first(accounts: &mut [Account]) {
+    accounts.sort_by_key(|account| !account.pinned);
+}
+
+/// Accounts the cursor can land on in the grouped view: every member of an
+/// expanded group, and the first member standing in for a collapsed one
+fn group_stops(groups: &[AccountGroup], expanded: &HashSet<String>) -> Vec<usize> {
+    groups
+        .iter()
+        .flat_map(|g| {
+            if expanded.contains(&g.name) {
+                g.members.clone()
+            } else {
+                g.members.iter().take(1).copied().collect()
+            }
+        })
+        .collect()
+}
+
+/// Chart points for usage history: hours since the first snapshot vs monthly usage.
+///
+/// Snapshots are sorted by time, so out-of-order or sparse history still
+/// plots as a single left-to-right line.
+fn usage_chart_series(history: &[UsageSnapshot]) -> Vec<(f64, f64)> {
+    let mut sorted: Vec<&UsageSnapshot> = history.iter().collect();
+    sorted.sort_by_key(|u| u.timestamp);
+
+    let Some(start) = sorted.first().map(|u| u.timestamp) else {
+        return Vec::new();
+    };
+
+    sorted
+        .iter()
+        .map(|u| {
+            let hours = (u.timestamp - start).num_seconds() as f64 / 3600.0;
+            (hours, u.monthly_usage)
+        })
+        .collect()
+}
+
+/// Axis bounds covering all points, widened so a single point still has a range
+fn chart_bounds(points: &[(f64, f64)]) -> ([f64; 2], [f64; 2]) {
+    let max_x = points.iter().map(|p| p.0).fold(0.0, f64::max);
+    let max_y = points.iter().map(|p| p.1).fold(0.0, f64::max);
+
+    ([0.0, max_x.max(1.0)], [0.0, (max_y * 1.1).max(1.0)])
+}
+
 /// Helper function to create a centered rect
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -596,3 +1025,178 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::RoutingStrategy;
+
+    #[test]
+    fn test_low_budget_accounts() {
+        let accounts: Vec<Account> = ["Low", "Healthy", "Unlimited", "Unknown"]
+            .iter()
+            .map(|label| Account::new(label.to_string(), "sk-test".to_string()))
+            .collect();
+
+        let mut usage_data = std::collections::HashMap::new();
+        for (account, remaining, hard_limit) in [
+            (&accounts[0], Some(3.0), Some(100.0)),
+            (&accounts[1], Some(60.0), Some(100.0)),
+            (&accounts[2], Some(1.0), None),
+        ] {
+            let mut usage = UsageSnapshot::new(account.id);
+            usage.remaining_budget = remaining;
+            usage.hard_limit = hard_limit;
+            usage_data.insert(account.id, usage);
+        }
+
+        let labels = |threshold: BudgetThreshold| -> Vec<String> {
+            low_budget_accounts(&accounts, &usage_data, &threshold)
+                .iter()
+                .map(|a| a.label.clone())
+                .collect()
+        };
+
+        assert_eq!(labels(BudgetThreshold::Absolute(5.0)), vec!["Low", "Unlimited"]);
+        // Percentages need a hard limit to compare against
+        assert_eq!(labels(BudgetThreshold::Percent(10.0)), vec!["Low"]);
+        assert!(labels(BudgetThreshold::Absolute(0.5)).is_empty());
+    }
+
+    #[test]
+    fn test_pinned_accounts_sort_first() {
+        let account = |label: &str, priority: i32, pinned: bool| {
+            let mut account = Account::new(label.to_string(), "sk-test".to_string())
+                .with_priority(priority);
+            account.pinned = pinned;
+            account
+        };
+        // As loaded: priority descending
+        let mut accounts = vec![
+            account("High", 9, false),
+            account("Mid", 5, false),
+            account("Pinned mid", 5, true),
+            account("Pinned low", 1, true),
+            account("Low", 1, false),
+        ];
+
+        pinned_first(&mut accounts);
+        let labels: Vec<&str> = accounts.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, vec!["Pinned mid", "Pinned low", "High", "Mid", "Low"]);
+    }
+
+    #[test]
+    fn test_group_accounts_totals() {
+        let accounts = vec![
+            Account::new("Prod A".to_string(), "sk-a".to_string())
+                .with_tags(vec!["prod".to_string(), "us".to_string()]),
+            Account::new("Loose".to_string(), "sk-b".to_string()),
+            Account::new("Dev".to_string(), "sk-c".to_string()).with_tags(vec!["dev".to_string()]),
+            Account::new("Prod B".to_string(), "sk-d".to_string())
+                .with_tags(vec!["prod".to_string()]),
+        ];
+
+        let mut usage_data = std::collections::HashMap::new();
+        for (account, monthly, hard_limit, remaining) in [
+            (&accounts[0], 30.0, Some(100.0), Some(70.0)),
+            (&accounts[3], 90.0, Some(100.0), Some(10.0)),
+            (&accounts[2], 5.0, None, Some(2.5)),
+        ] {
+            let mut usage = UsageSnapshot::new(account.id);
+            usage.monthly_usage = monthly;
+            usage.hard_limit = hard_limit;
+            usage.remaining_budget = remaining;
+            usage_data.insert(account.id, usage);
+        }
+
+        let groups = group_accounts(&accounts, &usage_data);
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["dev", "prod", UNTAGGED_GROUP]);
+
+        let prod = &groups[1];
+        assert_eq!(prod.members, vec![0, 3]);
+        assert!((prod.utilization - 0.6).abs() < 1e-9);
+        assert!((prod.remaining_budget - 80.0).abs() < 1e-9);
+
+        // No hard limit and no snapshot both contribute nothing
+        assert_eq!(groups[0].utilization, 0.0);
+        assert_eq!(groups[0].remaining_budget, 2.5);
+        assert_eq!(groups[2].members, vec![1]);
+        assert_eq!(groups[2].remaining_budget, 0.0);
+
+        let expanded = HashSet::from(["prod".to_string()]);
+        assert_eq!(group_stops(&groups, &HashSet::new()), vec![2, 0, 1]);
+        assert_eq!(group_stops(&groups, &expanded), vec![2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn test_usage_chart_series() {
+        assert!(usage_chart_series(&[]).is_empty());
+
+        let id = Uuid::new_v4();
+        let start = chrono::Utc::now();
+        let snapshot = |hours: i64, monthly_usage: f64| {
+            let mut usage = UsageSnapshot::new(id);
+            usage.timestamp = start + chrono::Duration::hours(hours);
+            usage.monthly_usage = monthly_usage;
+            usage
+        };
+
+        // Out of order with a gap between readings
+        let history = vec![snapshot(30, 12.5), snapshot(0, 2.0), snapshot(2, 4.0)];
+        let points = usage_chart_series(&history);
+        assert_eq!(points, vec![(0.0, 2.0), (2.0, 4.0), (30.0, 12.5)]);
+        assert_eq!(chart_bounds(&points), ([0.0, 30.0], [0.0, 12.5 * 1.1]));
+
+        // A lone snapshot still gets a non-empty range on both axes
+        let points = usage_chart_series(&[snapshot(5, 0.0)]);
+        assert_eq!(points, vec![(0.0, 0.0)]);
+        assert_eq!(chart_bounds(&points), ([0.0, 1.0], [0.0, 1.0]));
+    }
+
+    #[tokio::test]
+    async fn test_enabling_account_sends_warmup() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let warmups = Arc::new(AtomicUsize::new(0));
+        let counter = warmups.clone();
+        let upstream = axum::Router::new().route(
+            "/v1/models",
+            axum::routing::get(move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    axum::Json(serde_json::json!({ "object": "list", "data": [] }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.unwrap();
+        });
+
+        let store = EncryptedStore::open_in_memory("test_master_key").unwrap();
+        let mut account = Account::new("Dormant".to_string(), "sk-dormant".to_string());
+        account.set_enabled(false);
+        store.save_account(&account).unwrap();
+
+        let mut config = Config::default();
+        config.routing.warmup_on_enable = true;
+        config.proxy.openai_base_url = format!("http://{}", upstream_addr);
+
+        let engine = Arc::new(RoutingEngine::new(RoutingStrategy::LeastUtilized));
+        let mut app = CliApp::new(store, engine.clone(), config);
+        app.refresh_data().await;
+
+        app.toggle_account_enabled().await.unwrap();
+        assert_eq!(warmups.load(Ordering::SeqCst), 1);
+        assert!(engine.latency(account.id).is_some());
+        let details = app.format_account_details(&app.accounts[0], None);
+        assert!(details.contains("Latency: "), "{}", details);
+
+        // Disabling doesn't warm up
+        app.toggle_account_enabled().await.unwrap();
+        assert_eq!(warmups.load(Ordering::SeqCst), 1);
+    }
+}
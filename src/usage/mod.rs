@@ -1,33 +1,263 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Datelike, TimeZone, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use reqwest::{Client, Method, RequestBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tracing::{debug, instrument, warn};
+use tracing::{debug, info, instrument, warn};
 
-use crate::models::{Account, UsageSnapshot};
+use crate::models::{Account, AccountCapabilities, AccountId, UsageSnapshot};
+
+/// Consecutive poll errors backed off exponentially before polling drops to
+/// the maximum interval
+const MAX_BACKOFF_ERRORS: u32 = 5;
+
+/// Cooldown used when a 429 carries no usable `Retry-After` header
+const DEFAULT_RATE_LIMIT_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Errors from usage polling that callers act on
+#[derive(Debug, thiserror::Error)]
+pub enum PollError {
+    /// OpenAI rate-limited the usage endpoints; wait this long before polling again
+    #[error("Usage API rate limited, retry after {0:?}")]
+    RateLimited(std::time::Duration),
+    /// The user-level usage endpoint did not accept the key at all
+    #[error("Usage API rejected the key: {0}")]
+    KeyRejected(reqwest::StatusCode),
+}
+
+impl PollError {
+    /// Build a `RateLimited` error from a 429 response's `Retry-After` header
+    fn from_rate_limited(resp: &reqwest::Response) -> Self {
+        let retry_after = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after)
+            .unwrap_or(DEFAULT_RATE_LIMIT_COOLDOWN);
+
+        PollError::RateLimited(retry_after)
+    }
+}
+
+/// Parse a `Retry-After` value, either delay-seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<std::time::Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(std::time::Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let delta = date.with_timezone(&Utc) - Utc::now();
+    Some(delta.to_std().unwrap_or_default())
+}
+
+/// Whether an error returned by the client is a rate limit
+fn rate_limit_of(error: &anyhow::Error) -> Option<std::time::Duration> {
+    match error.downcast_ref::<PollError>()? {
+        PollError::RateLimited(wait) => Some(*wait),
+        PollError::KeyRejected(_) => None,
+    }
+}
+
+/// An org-level usage endpoint refused the key (401/403)
+#[derive(Debug, thiserror::Error)]
+#[error("{endpoint} refused the key: {status}")]
+struct EndpointDenied {
+    endpoint: &'static str,
+    status: reqwest::StatusCode,
+}
+
+impl EndpointDenied {
+    fn check(endpoint: &'static str, status: reqwest::StatusCode) -> Option<Self> {
+        matches!(
+            status,
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+        )
+        .then_some(Self { endpoint, status })
+    }
+}
+
+/// Which usage endpoints an account's key can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageScope {
+    /// Organization billing (usage and subscription) plus token usage
+    Organization,
+    /// Only the user-level token usage endpoint
+    User,
+}
+
+impl UsageScope {
+    /// Scope implied by the key prefix, if any: admin keys read org
+    /// billing, project and service-account keys can't. Other keys have to
+    /// be probed.
+    pub fn from_key(api_key: &str) -> Option<Self> {
+        if api_key.starts_with("sk-admin-") {
+            Some(UsageScope::Organization)
+        } else if api_key.starts_with("sk-proj-") || api_key.starts_with("sk-svcacct-") {
+            Some(UsageScope::User)
+        } else {
+            None
+        }
+    }
+}
 
 /// OpenAI API client for fetching usage and billing information
 pub struct OpenAIClient {
     http: Client,
     base_url: String,
+    probe_model: String,
+    /// Fall back to the (billed) completion probe when a key can't list models
+    completion_probe: bool,
+    /// Organization for accounts without an `org_id` of their own
+    default_org_id: Option<String>,
+    /// Scope that last worked per account, so refused endpoints are skipped
+    scopes: dashmap::DashMap<uuid::Uuid, UsageScope>,
+    /// Date parameter shapes tried, in order, for billing usage
+    billing_date_formats: Vec<BillingDateFormat>,
+    /// Date format that last worked per account, tried first next time
+    billing_formats: dashmap::DashMap<uuid::Uuid, BillingDateFormat>,
+}
+
+/// How a billing usage query encodes its date range. Some org endpoints
+/// reject one shape with a 400 and accept another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingDateFormat {
+    /// `start_date`/`end_date` as `YYYY-MM-DD`, end inclusive
+    Dates,
+    /// `start_time`/`end_time` as unix seconds, end exclusive
+    Epoch,
+    /// `Epoch` plus `bucket_width=1d`
+    DailyBuckets,
+}
+
+impl BillingDateFormat {
+    /// Every format, in the order tried by default
+    pub const ALL: [BillingDateFormat; 3] =
+        [BillingDateFormat::Dates, BillingDateFormat::Epoch, BillingDateFormat::DailyBuckets];
+
+    /// Query parameters for a range in this format
+    fn query(&self, range: UsageRange) -> Vec<(&'static str, String)> {
+        let epoch = |date: NaiveDate| date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp();
+        let end_exclusive = range.end.succ_opt().unwrap_or(range.end);
+
+        match self {
+            BillingDateFormat::Dates => vec![
+                ("start_date", range.start.format("%Y-%m-%d").to_string()),
+                ("end_date", range.end.format("%Y-%m-%d").to_string()),
+            ],
+            BillingDateFormat::Epoch => vec![
+                ("start_time", epoch(range.start).to_string()),
+                ("end_time", epoch(end_exclusive).to_string()),
+            ],
+            BillingDateFormat::DailyBuckets => {
+                let mut query = BillingDateFormat::Epoch.query(range);
+                query.push(("bucket_width", "1d".to_string()));
+                query
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BillingDateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BillingDateFormat::Dates => "dates",
+            BillingDateFormat::Epoch => "epoch",
+            BillingDateFormat::DailyBuckets => "daily_buckets",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Longest span, in days, the billing usage endpoint is queried for at once
+pub const MAX_USAGE_RANGE_DAYS: i64 = 100;
+
+/// Date range for billing usage queries, as sent in `start_date`/`end_date`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl UsageRange {
+    /// A validated range; `start` must not be after `end`, and the span is
+    /// capped at `MAX_USAGE_RANGE_DAYS`
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self> {
+        if start > end {
+            anyhow::bail!("Usage range start {} is after its end {}", start, end);
+        }
+        if (end - start).num_days() > MAX_USAGE_RANGE_DAYS {
+            anyhow::bail!(
+                "Usage range {} to {} spans more than {} days",
+                start, end, MAX_USAGE_RANGE_DAYS
+            );
+        }
+
+        Ok(Self { start, end })
+    }
+
+    /// The current month up to today (UTC)
+    pub fn current_month() -> Self {
+        let today = Utc::now().date_naive();
+        Self { start: today.with_day(1).unwrap_or(today), end: today }
+    }
 }
 
 impl OpenAIClient {
     const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+    pub const DEFAULT_PROBE_MODEL: &str = "gpt-4o-mini";
 
     pub fn new() -> Self {
         Self {
             http: Client::new(),
             base_url: Self::DEFAULT_BASE_URL.to_string(),
+            probe_model: Self::DEFAULT_PROBE_MODEL.to_string(),
+            completion_probe: true,
+            default_org_id: None,
+            scopes: dashmap::DashMap::new(),
+            billing_date_formats: BillingDateFormat::ALL.to_vec(),
+            billing_formats: dashmap::DashMap::new(),
         }
     }
 
+    /// Send requests through `http`, e.g. one built with the outbound TLS settings
+    pub fn with_http_client(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
     pub fn with_base_url(mut self, url: String) -> Self {
         self.base_url = url;
         self
     }
 
+    /// Organization sent for accounts that don't set one
+    pub fn with_default_org_id(mut self, org_id: Option<String>) -> Self {
+        self.default_org_id = org_id;
+        self
+    }
+
+    /// Model used for the completion probe when `/v1/models` is not accessible
+    pub fn with_probe_model(mut self, model: String) -> Self {
+        self.probe_model = model;
+        self
+    }
+
+    /// Whether keys that can't list models are checked with a completion
+    /// probe, which costs a token each time; without it they count as invalid
+    pub fn with_completion_probe(mut self, enabled: bool) -> Self {
+        self.completion_probe = enabled;
+        self
+    }
+
+    /// Date formats tried, in order, when billing usage answers 400; an
+    /// empty list keeps the default order
+    pub fn with_billing_date_formats(mut self, formats: Vec<BillingDateFormat>) -> Self {
+        if !formats.is_empty() {
+            self.billing_date_formats = formats;
+        }
+        self
+    }
+
     /// Build authenticated request for an account
     fn build_request(&self, account: &Account, method: Method, path: &str,
     ) -> RequestBuilder {
@@ -36,7 +266,7 @@ impl OpenAIClient {
 
         req = req.header("Authorization", format!("Bearer {}", account.api_key));
 
-        if let Some(org_id) = &account.org_id {
+        if let Some(org_id) = account.org_id.as_ref().or(self.default_org_id.as_ref()) {
             req = req.header("OpenAI-Organization", org_id);
         }
 
@@ -50,8 +280,62 @@ impl OpenAIClient {
     ) -> Result<UsageSnapshot> {
         let mut snapshot = UsageSnapshot::new(account.id);
 
-        // Try to fetch usage from various endpoints
-        match self.fetch_billing_usage(account).await {
+        // Org billing endpoints only for keys that can (or might) read them
+        if self.usage_scope(account) != Some(UsageScope::User) {
+            match self.fetch_org_usage(account, &mut snapshot).await {
+                Ok(()) => {
+                    self.scopes.insert(account.id, UsageScope::Organization);
+                }
+                Err(e) if e.is::<EndpointDenied>() => {
+                    debug!(
+                        "Org usage not readable for {} ({}); using user-level usage from now on",
+                        account.label, e
+                    );
+                    self.scopes.insert(account.id, UsageScope::User);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Try to fetch token usage from the newer usage endpoint
+        match self.fetch_token_usage(account).await {
+            Ok(token_usage) => {
+                snapshot.tokens_used = token_usage.total_tokens;
+                snapshot.cost_estimate = token_usage.total_cost;
+                debug!(
+                    "Fetched token usage for {}: {} tokens, ${:.4}",
+                    account.label, snapshot.tokens_used, snapshot.cost_estimate
+                );
+            }
+            Err(e) if e.is::<PollError>() => return Err(e),
+            Err(e) => {
+                debug!("Token usage endpoint not available for {}: {}", account.label, e);
+            }
+        }
+
+        snapshot.timestamp = Utc::now();
+        Ok(snapshot)
+    }
+
+    /// Usage scope for an account: what last worked, else what the key
+    /// prefix implies
+    pub fn usage_scope(&self, account: &Account) -> Option<UsageScope> {
+        self.scopes
+            .get(&account.id)
+            .map(|scope| *scope)
+            .or_else(|| UsageScope::from_key(&account.api_key))
+    }
+
+    /// Fill billing usage and limits from the org-level endpoints.
+    ///
+    /// Fails only on rate limits and when the key is refused; other errors
+    /// are logged and leave the fields unset.
+    async fn fetch_org_usage(&self,
+        account: &Account,
+        snapshot: &mut UsageSnapshot,
+    ) -> Result<()> {
+        // Billing usage for the current month
+        match self.fetch_billing_usage(account, UsageRange::current_month()).await {
             Ok(usage) => {
                 snapshot.monthly_usage = usage.total_usage / 100.0; // Convert cents to dollars
                 debug!(
@@ -59,6 +343,7 @@ impl OpenAIClient {
                     account.label, snapshot.monthly_usage
                 );
             }
+            Err(e) if rate_limit_of(&e).is_some() || e.is::<EndpointDenied>() => return Err(e),
             Err(e) => {
                 warn!("Failed to fetch billing usage for {}: {}", account.label, e);
             }
@@ -80,58 +365,88 @@ impl OpenAIClient {
                     account.label, snapshot.hard_limit, snapshot.monthly_usage
                 );
             }
+            Err(e) if rate_limit_of(&e).is_some() || e.is::<EndpointDenied>() => return Err(e),
             Err(e) => {
                 warn!("Failed to fetch subscription for {}: {}", account.label, e);
             }
         }
 
-        // Try to fetch token usage from the newer usage endpoint
-        match self.fetch_token_usage(account).await {
-            Ok(token_usage) => {
-                snapshot.tokens_used = token_usage.total_tokens;
-                snapshot.cost_estimate = token_usage.total_cost;
-                debug!(
-                    "Fetched token usage for {}: {} tokens, ${:.4}",
-                    account.label, snapshot.tokens_used, snapshot.cost_estimate
-                );
-            }
-            Err(e) => {
-                debug!("Token usage endpoint not available for {}: {}", account.label, e);
-            }
-        }
+        Ok(())
+    }
 
-        snapshot.timestamp = Utc::now();
-        Ok(snapshot)
+    /// Total spend in dollars for an account over a date range
+    pub async fn fetch_usage_range(&self,
+        account: &Account,
+        range: UsageRange,
+    ) -> Result<f64> {
+        let usage = self.fetch_billing_usage(account, range).await?;
+        Ok(usage.total_usage / 100.0) // Convert cents to dollars
     }
 
-    /// Fetch billing usage (v1/dashboard/billing/usage)
+    /// Fetch billing usage (v1/dashboard/billing/usage).
+    ///
+    /// Starts with the date format that last worked for the account and
+    /// moves to the next configured one when the endpoint answers 400.
     async fn fetch_billing_usage(&self,
         account: &Account,
+        range: UsageRange,
     ) -> Result<BillingUsageResponse> {
-        // Get current billing period
-        let now = Utc::now();
-        let start_date = now.with_day(1).unwrap_or(now).format("%Y-%m-%d").to_string();
-        let end_date = now.format("%Y-%m-%d").to_string();
+        let mut formats = self.billing_date_formats.clone();
+        if let Some(known) = self.billing_formats.get(&account.id).map(|f| *f) {
+            if let Some(pos) = formats.iter().position(|f| *f == known) {
+                formats[..=pos].rotate_right(1);
+            }
+        }
 
-        let resp = self
-            .build_request(account, Method::GET, "/v1/dashboard/billing/usage")
-            .query(&[("start_date", start_date), ("end_date", end_date)])
-            .send()
-            .await
-            .context("Failed to send billing usage request")?;
+        let mut rejected = None;
+        for format in formats {
+            let resp = self
+                .build_request(account, Method::GET, "/v1/dashboard/billing/usage")
+                .query(&format.query(range))
+                .send()
+                .await
+                .context("Failed to send billing usage request")?;
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Billing usage API error: {} - {}", status, text);
-        }
+            if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(PollError::from_rate_limited(&resp).into());
+            }
 
-        let usage: BillingUsageResponse = resp
-            .json()
-            .await
-            .context("Failed to parse billing usage response")?;
+            if let Some(denied) = EndpointDenied::check("billing usage", resp.status()) {
+                return Err(denied.into());
+            }
+
+            if resp.status() == reqwest::StatusCode::BAD_REQUEST {
+                let text = resp.text().await.unwrap_or_default();
+                debug!(
+                    "Billing usage for {} rejected {} dates: {}",
+                    account.label, format, text
+                );
+                rejected = Some(text);
+                continue;
+            }
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let text = resp.text().await.unwrap_or_default();
+                anyhow::bail!("Billing usage API error: {} - {}", status, text);
+            }
 
-        Ok(usage)
+            let usage: BillingUsageResponse = resp
+                .json()
+                .await
+                .context("Failed to parse billing usage response")?;
+
+            if self.billing_formats.insert(account.id, format) != Some(format) {
+                info!("Billing usage for {} accepted {} dates", account.label, format);
+            }
+
+            return Ok(usage);
+        }
+
+        anyhow::bail!(
+            "Billing usage API error: 400 Bad Request for every date format - {}",
+            rejected.unwrap_or_default()
+        )
     }
 
     /// Fetch subscription info (v1/dashboard/billing/subscription)
@@ -144,6 +459,14 @@ impl OpenAIClient {
             .await
             .context("Failed to send subscription request")?;
 
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(PollError::from_rate_limited(&resp).into());
+        }
+
+        if let Some(denied) = EndpointDenied::check("subscription", resp.status()) {
+            return Err(denied.into());
+        }
+
         if !resp.status().is_success() {
             let status = resp.status();
             let text = resp.text().await.unwrap_or_default();
@@ -169,6 +492,15 @@ impl OpenAIClient {
             .await
             .context("Failed to send token usage request")?;
 
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(PollError::from_rate_limited(&resp).into());
+        }
+
+        // Unlike a 403, which only withholds usage, a 401 means the key itself is bad
+        if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(PollError::KeyRejected(resp.status()).into());
+        }
+
         if resp.status() == 404 {
             anyhow::bail!("Token usage endpoint not available (404)");
         }
@@ -218,26 +550,134 @@ impl OpenAIClient {
             .request(Method::GET, format!("{}/v1/models", self.base_url))
             .header("Authorization", format!("Bearer {}", api_key));
 
-        if let Some(org) = org_id {
+        if let Some(org) = org_id.or(self.default_org_id.as_deref()) {
             req = req.header("OpenAI-Organization", org);
         }
 
         let resp = req.send().await.context("Failed to validate API key")?;
+        let status = resp.status();
+
+        // Restricted keys may be unable to list models but still serve
+        // completions, so an auth failure here is not conclusive
+        let denied = status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN;
+        if denied && self.completion_probe {
+            debug!("/v1/models returned {}, falling back to completion probe", status);
+            return self.validate_with_completion(api_key, org_id).await;
+        }
+
+        if !status.is_success() {
+            anyhow::bail!("Invalid API key or organization: {}", status);
+        }
+
+        Ok(AccountInfo {
+            org_id: Self::org_from_headers(resp.headers()),
+            is_valid: true,
+        })
+    }
+
+    /// Send a cheap authenticated request to confirm an account is live,
+    /// returning how long it took
+    #[instrument(skip(self, account), fields(account_id = %account.id))]
+    pub async fn warm_up(&self,
+        account: &Account,
+    ) -> Result<std::time::Duration> {
+        let started = std::time::Instant::now();
+        let resp = self
+            .build_request(account, Method::GET, "/v1/models")
+            .send()
+            .await
+            .context("Warmup request failed")?;
+
+        if !resp.status().is_success() {
+            anyhow::bail!("Warmup request returned {}", resp.status());
+        }
+
+        Ok(started.elapsed())
+    }
+
+    /// Probe what an account's key can do: whether it works, its
+    /// organization, the models it can list and whether it reads org usage
+    #[instrument(skip(self, account), fields(account_id = %account.id))]
+    pub async fn probe_capabilities(&self,
+        account: &Account,
+    ) -> Result<AccountCapabilities> {
+        let resp = self
+            .build_request(account, Method::GET, "/v1/models")
+            .send()
+            .await
+            .context("Failed to list models")?;
+        let status = resp.status();
+
+        let (valid, org_id, available_models) = if status.is_success() {
+            let org_id = Self::org_from_headers(resp.headers());
+            let list: ModelListResponse = resp.json().await.context("Failed to parse model list")?;
+            (true, org_id, list.data.into_iter().map(|m| m.id).collect())
+        } else if self.completion_probe
+            && (status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN)
+        {
+            // Same fallback as `validate_key`: restricted keys can't list models
+            match self.validate_with_completion(&account.api_key, account.org_id.as_deref()).await {
+                Ok(info) => (true, info.org_id, Vec::new()),
+                Err(e) => {
+                    debug!("Capability probe found {} invalid: {}", account.label, e);
+                    (false, None, Vec::new())
+                }
+            }
+        } else {
+            anyhow::bail!("Model list returned {}", status);
+        };
+
+        Ok(AccountCapabilities {
+            account_id: account.id,
+            valid,
+            org_id,
+            available_models,
+            supports_usage_api: valid && self.usage_scope(account) == Some(UsageScope::Organization),
+            checked_at: Utc::now(),
+        })
+    }
+
+    /// Validate a key by sending a 1-token completion to the probe model
+    async fn validate_with_completion(&self,
+        api_key: &str,
+        org_id: Option<&str>,
+    ) -> Result<AccountInfo> {
+        let body = serde_json::json!({
+            "model": self.probe_model,
+            "messages": [{ "role": "user", "content": "ping" }],
+            "max_tokens": 1,
+        });
+
+        let mut req = self
+            .http
+            .request(Method::POST, format!("{}/v1/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", api_key))
+            .json(&body);
+
+        if let Some(org) = org_id.or(self.default_org_id.as_deref()) {
+            req = req.header("OpenAI-Organization", org);
+        }
+
+        let resp = req.send().await.context("Failed to send completion probe")?;
 
         if !resp.status().is_success() {
             let status = resp.status();
             anyhow::bail!("Invalid API key or organization: {}", status);
         }
 
-        // Extract org info from headers if available
-        let org_header = resp.headers().get("openai-organization");
-        let org_id = org_header.and_then(|v| v.to_str().ok()).map(|s| s.to_string());
-
         Ok(AccountInfo {
-            org_id,
+            org_id: Self::org_from_headers(resp.headers()),
             is_valid: true,
         })
     }
+
+    /// Extract org info from response headers if available
+    fn org_from_headers(headers: &reqwest::header::HeaderMap) -> Option<String> {
+        headers
+            .get("openai-organization")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    }
 }
 
 impl Default for OpenAIClient {
@@ -249,84 +689,27 @@ impl Default for OpenAIClient {
 /// Response from billing usage endpoint
 #[derive(Debug, Deserialize)]
 struct BillingUsageResponse {
-    pub object: String,
-    #[serde(rename = "daily_costs")]
-    pub daily_costs: Vec<DailyCost>,
     #[serde(rename = "total_usage")]
     pub total_usage: f64, // In cents
 }
 
-#[derive(Debug, Deserialize)]
-struct DailyCost {
-    pub timestamp: i64,
-    #[serde(rename = "line_items")]
-    pub line_items: Vec<LineItem>,
-}
-
-#[derive(Debug, Deserialize)]
-struct LineItem {
-    pub name: String,
-    pub cost: f64,
-}
-
 /// Response from subscription endpoint
 #[derive(Debug, Deserialize)]
 struct SubscriptionResponse {
-    #[serde(rename = "object")]
-    pub object: String,
-    #[serde(rename = "has_payment_method")]
-    pub has_payment_method: bool,
-    #[serde(rename = "canceled")]
-    pub canceled: bool,
-    #[serde(rename = "canceled_at")]
-    pub canceled_at: Option<i64>,
-    #[serde(rename = "delinquent")]
-    pub delinquent: Option<bool>,
-    #[serde(rename = "access_until")]
-    pub access_until: i64,
     #[serde(rename = "soft_limit_usd")]
     pub soft_limit_usd: Option<f64>,
     #[serde(rename = "hard_limit_usd")]
     pub hard_limit_usd: Option<f64>,
-    #[serde(rename = "system_hard_limit_usd")]
-    pub system_hard_limit_usd: Option<f64>,
-    #[serde(rename = "soft_limit")]
-    pub soft_limit: Option<f64>,
-    #[serde(rename = "hard_limit")]
-    pub hard_limit: Option<f64>,
-    #[serde(rename = "plan")]
-    pub plan: Option<PlanInfo>,
-}
-
-#[derive(Debug, Deserialize)]
-struct PlanInfo {
-    pub title: String,
-    pub id: String,
 }
 
 /// Response from token usage endpoint
 #[derive(Debug, Deserialize)]
 struct TokenUsageResponse {
-    pub object: String,
     pub data: Vec<TokenUsageData>,
 }
 
 #[derive(Debug, Deserialize)]
 struct TokenUsageData {
-    #[serde(rename = "organization_id")]
-    pub organization_id: String,
-    #[serde(rename = "organization_name")]
-    pub organization_name: String,
-    #[serde(rename = "aggregation_timestamp")]
-    pub aggregation_timestamp: i64,
-    #[serde(rename = "n_requests")]
-    pub n_requests: i64,
-    #[serde(rename = "operation")]
-    pub operation: String,
-    #[serde(rename = "snapshot_id")]
-    pub snapshot_id: String,
-    #[serde(rename = "model")]
-    pub model: Option<String>,
     #[serde(rename = "n_generated_tokens")]
     pub n_generated_tokens: u64,
     #[serde(rename = "n_context_tokens")]
@@ -339,6 +722,17 @@ struct TokenUsageSummary {
     pub total_cost: f64,
 }
 
+/// Response from the model list endpoint
+#[derive(Debug, Deserialize)]
+struct ModelListResponse {
+    pub data: Vec<ModelListEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelListEntry {
+    pub id: String,
+}
+
 /// Account validation info
 #[derive(Debug, Clone)]
 pub struct AccountInfo {
@@ -346,11 +740,60 @@ pub struct AccountInfo {
     pub is_valid: bool,
 }
 
+/// Account capability probes, reused until they are older than the TTL
+pub struct CapabilityCache {
+    ttl: chrono::Duration,
+    entries: dashmap::DashMap<AccountId, AccountCapabilities>,
+}
+
+impl CapabilityCache {
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self {
+            ttl: chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX),
+            entries: dashmap::DashMap::new(),
+        }
+    }
+
+    /// Seed the cache, e.g. with probes persisted by an earlier run
+    pub fn insert(&self, capabilities: AccountCapabilities) {
+        self.entries.insert(capabilities.account_id, capabilities);
+    }
+
+    /// Cached probe for an account, if it is still within the TTL
+    pub fn get(&self, account_id: AccountId) -> Option<AccountCapabilities> {
+        self.entries
+            .get(&account_id)
+            .filter(|c| c.is_fresh(self.ttl))
+            .map(|c| c.clone())
+    }
+
+    /// Cached probe for an account, probing again once it has expired.
+    ///
+    /// Returns whether a new probe was made alongside the result, so callers
+    /// know to persist it.
+    pub async fn get_or_probe(&self,
+        client: &OpenAIClient,
+        account: &Account,
+    ) -> Result<(AccountCapabilities, bool)> {
+        if let Some(cached) = self.get(account.id) {
+            return Ok((cached, false));
+        }
+
+        let probed = client.probe_capabilities(account).await?;
+        self.insert(probed.clone());
+        Ok((probed, true))
+    }
+}
+
 /// Usage poller that periodically updates usage data for all accounts
 pub struct UsagePoller {
     client: OpenAIClient,
     min_interval: std::time::Duration,
     max_interval: std::time::Duration,
+    /// Global pause after a 429; applies to every account
+    cooldown_until: parking_lot::Mutex<Option<std::time::Instant>>,
+    /// Idle time after which polling drops to `max_interval`
+    idle_pause: Option<std::time::Duration>,
 }
 
 impl UsagePoller {
@@ -359,15 +802,47 @@ impl UsagePoller {
             client: OpenAIClient::new(),
             min_interval: std::time::Duration::from_secs(60),
             max_interval: std::time::Duration::from_secs(3600),
+            cooldown_until: parking_lot::Mutex::new(None),
+            idle_pause: None,
         }
     }
 
+    /// Bounds for the poll interval; `max` also caps error backoff
+    pub fn with_intervals(mut self, min: std::time::Duration, max: std::time::Duration) -> Self {
+        self.min_interval = min;
+        self.max_interval = max.max(min);
+        self
+    }
+
+    /// Back off to the maximum interval after `pause` without proxied traffic
+    pub fn with_idle_pause(mut self, pause: Option<std::time::Duration>) -> Self {
+        self.idle_pause = pause;
+        self
+    }
+
+    pub fn with_client(mut self, client: OpenAIClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Time left before polling may resume after a rate limit
+    pub fn cooldown_remaining(&self) -> Option<std::time::Duration> {
+        let until = (*self.cooldown_until.lock())?;
+        until.checked_duration_since(std::time::Instant::now())
+    }
+
     /// Poll usage for a single account with exponential backoff
     pub async fn poll_account(
         &self,
         account: &Account,
         last_error: Option<&std::time::Instant>,
     ) -> Result<UsageSnapshot> {
+        // Honor a global rate-limit cooldown before touching the API again
+        if let Some(wait) = self.cooldown_remaining() {
+            debug!("Usage polling paused for {:?} after rate limit", wait);
+            tokio::time::sleep(wait).await;
+        }
+
         // Implement backoff if there was a recent error
         if let Some(last_err) = last_error {
             let elapsed = last_err.elapsed();
@@ -376,16 +851,46 @@ impl UsagePoller {
             }
         }
 
-        self.client.fetch_usage(account).await
+        let result = self.client.fetch_usage(account).await;
+
+        if let Err(e) = &result {
+            if let Some(wait) = rate_limit_of(e) {
+                // An upstream can ask for any delay; never stall polling past the max interval
+                let wait = wait.min(self.max_interval);
+                warn!("Usage API rate limited, pausing all polling for {:?}", wait);
+                *self.cooldown_until.lock() = Some(std::time::Instant::now() + wait);
+            }
+        }
+
+        result
     }
 
-    /// Calculate next poll interval based on consecutive errors
+    /// Calculate next poll interval based on consecutive errors: the
+    /// minimum plus `2^errors` seconds, and the maximum past
+    /// `MAX_BACKOFF_ERRORS` errors
     pub fn next_interval(&self,
         consecutive_errors: u32,
     ) -> std::time::Duration {
-        let backoff = std::time::Duration::from_secs(2_u64.pow(consecutive_errors.min(5)));
+        if consecutive_errors > MAX_BACKOFF_ERRORS {
+            return self.max_interval;
+        }
+        let backoff = match consecutive_errors {
+            0 => std::time::Duration::ZERO,
+            n => std::time::Duration::from_secs(2_u64.pow(n)),
+        };
         std::cmp::min(self.min_interval + backoff, self.max_interval)
     }
+
+    /// Interval until the next poll, given how long the proxy has been idle
+    pub fn effective_interval(&self,
+        consecutive_errors: u32,
+        idle_for: std::time::Duration,
+    ) -> std::time::Duration {
+        match self.idle_pause {
+            Some(pause) if idle_for >= pause => self.max_interval,
+            _ => self.next_interval(consecutive_errors),
+        }
+    }
 }
 
 impl Default for UsagePoller {
@@ -394,9 +899,48 @@ impl Default for UsagePoller {
     }
 }
 
+/// USD per million (prompt, completion) tokens; first matching prefix wins
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("text-embedding-ada-002", 0.10, 0.0),
+];
+
+/// Cost of a number of prompt and completion tokens; `None` if the model isn't priced
+pub fn token_cost(model: &str, prompt_tokens: u64, completion_tokens: u64) -> Option<f64> {
+    let (_, prompt_price, completion_price) = MODEL_PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))?;
+
+    Some(
+        (prompt_tokens as f64 * prompt_price + completion_tokens as f64 * completion_price)
+            / 1_000_000.0,
+    )
+}
+
+/// Estimated cost of a response from the `usage` object OpenAI returns.
+///
+/// `None` when the response carries no token usage or the model isn't priced.
+pub fn response_cost(model: &str, response: &serde_json::Value) -> Option<f64> {
+    let usage = response.get("usage")?;
+    let prompt = usage.get("prompt_tokens")?.as_u64()?;
+    let completion = usage
+        .get("completion_tokens")
+        .and_then(|t| t.as_u64())
+        .unwrap_or(0);
+
+    token_cost(model, prompt, completion)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_usage_poller_interval() {
@@ -413,4 +957,470 @@ mod tests {
         // Max errors - capped at max_interval
         assert_eq!(poller.next_interval(10).as_secs(), 3600);
     }
+
+    /// Serve a mock OpenAI API on an ephemeral port and return its base URL
+    async fn spawn_mock(app: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_cached_until_ttl_expires() {
+        use axum::routing::get;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counter = probes.clone();
+        let app = axum::Router::new().route(
+            "/v1/models",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { axum::Json(serde_json::json!({ "data": [{ "id": "gpt-4o" }] })) }
+            }),
+        );
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+        let account = Account::new("Probed".to_string(), "sk-admin-probed".to_string());
+        let cache = CapabilityCache::new(std::time::Duration::from_millis(200));
+
+        let (first, probed) = cache.get_or_probe(&client, &account).await.unwrap();
+        assert!(probed);
+        assert!(first.valid);
+        assert!(first.supports_usage_api);
+        assert_eq!(first.available_models, vec!["gpt-4o".to_string()]);
+
+        // Within the TTL the cached result is reused
+        let (second, probed) = cache.get_or_probe(&client, &account).await.unwrap();
+        assert!(!probed);
+        assert_eq!(second, first);
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+
+        // After it the account is probed again
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        let (third, probed) = cache.get_or_probe(&client, &account).await.unwrap();
+        assert!(probed);
+        assert!(third.checked_at > first.checked_at);
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_key_falls_back_to_completion_probe() {
+        use axum::{http::StatusCode, routing::{get, post}, Json};
+
+        let app = axum::Router::new()
+            .route("/v1/models", get(|| async { StatusCode::FORBIDDEN }))
+            .route(
+                "/v1/chat/completions",
+                post(|Json(body): Json<serde_json::Value>| async move {
+                    if body["model"] == "probe-model" && body["max_tokens"] == 1 {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::BAD_REQUEST
+                    }
+                }),
+            );
+
+        let client = OpenAIClient::new()
+            .with_base_url(spawn_mock(app).await)
+            .with_probe_model("probe-model".to_string());
+
+        let info = client.validate_key("sk-restricted", None).await.unwrap();
+        assert!(info.is_valid);
+    }
+
+    #[tokio::test]
+    async fn test_completion_probe_can_be_disabled() {
+        use axum::{http::StatusCode, routing::{get, post}};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counter = probes.clone();
+        let app = axum::Router::new()
+            .route("/v1/models", get(|| async { StatusCode::FORBIDDEN }))
+            .route(
+                "/v1/chat/completions",
+                post(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    async { StatusCode::OK }
+                }),
+            );
+
+        let client = OpenAIClient::new()
+            .with_base_url(spawn_mock(app).await)
+            .with_completion_probe(false);
+
+        assert!(client.validate_key("sk-restricted", None).await.is_err());
+        assert_eq!(probes.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_default_org_applies_to_accounts_without_one() {
+        use axum::{http::{HeaderMap, StatusCode}, routing::get};
+
+        // Answers with whichever organization the request carried
+        let app = axum::Router::new().route(
+            "/v1/models",
+            get(|headers: HeaderMap| async move {
+                match headers.get("OpenAI-Organization").map(|v| v.to_str().unwrap()) {
+                    Some("org-default") => StatusCode::OK,
+                    Some("org-own") => StatusCode::ACCEPTED,
+                    _ => StatusCode::BAD_REQUEST,
+                }
+            }),
+        );
+        let base_url = spawn_mock(app).await;
+
+        let account = Account::new("Unset".to_string(), "sk-unset".to_string());
+        let client = OpenAIClient::new().with_base_url(base_url.clone());
+        assert!(client.warm_up(&account).await.is_err());
+
+        let client = client.with_default_org_id(Some("org-default".to_string()));
+        let resp = client.build_request(&account, Method::GET, "/v1/models").send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+
+        // An account's own organization wins over the default
+        let account = account.with_org_id("org-own".to_string());
+        let resp = client.build_request(&account, Method::GET, "/v1/models").send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_validate_key_rejects_when_probe_fails() {
+        use axum::{http::StatusCode, routing::{get, post}};
+
+        let app = axum::Router::new()
+            .route("/v1/models", get(|| async { StatusCode::UNAUTHORIZED }))
+            .route("/v1/chat/completions", post(|| async { StatusCode::UNAUTHORIZED }));
+
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+
+        assert!(client.validate_key("sk-invalid", None).await.is_err());
+    }
+
+    #[test]
+    fn test_idle_pause_backs_off_polling() {
+        use std::time::Duration;
+
+        let poller = UsagePoller::new()
+            .with_intervals(Duration::from_secs(300), Duration::from_secs(3600))
+            .with_idle_pause(Some(Duration::from_secs(900)));
+
+        // Active: recent traffic keeps the normal schedule
+        assert_eq!(poller.effective_interval(0, Duration::from_secs(10)).as_secs(), 300);
+        assert_eq!(poller.effective_interval(0, Duration::from_secs(899)).as_secs(), 300);
+
+        // Idle: no traffic for the pause period drops to the max interval
+        assert_eq!(poller.effective_interval(0, Duration::from_secs(900)).as_secs(), 3600);
+        assert_eq!(poller.effective_interval(0, Duration::from_secs(7200)).as_secs(), 3600);
+
+        // Traffic resuming restores it
+        assert_eq!(poller.effective_interval(0, Duration::ZERO).as_secs(), 300);
+
+        // Without an idle pause the interval never depends on traffic
+        let always = UsagePoller::new()
+            .with_intervals(Duration::from_secs(300), Duration::from_secs(3600));
+        assert_eq!(always.effective_interval(0, Duration::from_secs(7200)).as_secs(), 300);
+    }
+
+    #[test]
+    fn test_usage_range_validation() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+        assert!(UsageRange::new(date("2024-03-01"), date("2024-03-31")).is_ok());
+        assert!(UsageRange::new(date("2024-03-05"), date("2024-03-05")).is_ok());
+        assert!(UsageRange::new(date("2024-03-31"), date("2024-03-01")).is_err());
+        assert!(UsageRange::new(date("2024-01-01"), date("2024-06-01")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_usage_range_sends_dates() {
+        use axum::{extract::Query, routing::get, Json};
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(None::<HashMap<String, String>>));
+        let recorder = seen.clone();
+        let app = axum::Router::new().route(
+            "/v1/dashboard/billing/usage",
+            get(move |Query(query): Query<HashMap<String, String>>| {
+                let recorder = recorder.clone();
+                async move {
+                    *recorder.lock().unwrap() = Some(query);
+                    Json(serde_json::json!({
+                        "object": "list",
+                        "daily_costs": [],
+                        "total_usage": 4200.0,
+                    }))
+                }
+            }),
+        );
+
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+        let account = Account::new("Test".to_string(), "sk-test".to_string());
+        let range = UsageRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(),
+        )
+        .unwrap();
+
+        let spend = client.fetch_usage_range(&account, range).await.unwrap();
+        assert_eq!(spend, 42.0);
+
+        let query = seen.lock().unwrap().clone().unwrap();
+        assert_eq!(query["start_date"], "2024-01-15");
+        assert_eq!(query["end_date"], "2024-02-10");
+    }
+
+    #[tokio::test]
+    async fn test_billing_usage_falls_back_to_epoch_dates() {
+        use axum::{extract::Query, http::StatusCode, routing::get, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Rejects `start_date`, accepts `start_time`
+        let date_hits = Arc::new(AtomicUsize::new(0));
+        let counter = date_hits.clone();
+        let app = axum::Router::new().route(
+            "/v1/dashboard/billing/usage",
+            get(move |Query(query): Query<HashMap<String, String>>| {
+                let counter = counter.clone();
+                async move {
+                    if query.contains_key("start_date") {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        return Err(StatusCode::BAD_REQUEST);
+                    }
+                    assert_eq!(query["start_time"], "1705276800");
+                    assert_eq!(query["end_time"], "1707609600");
+                    Ok(Json(serde_json::json!({
+                        "object": "list",
+                        "daily_costs": [],
+                        "total_usage": 1250.0,
+                    })))
+                }
+            }),
+        );
+
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+        let account = Account::new("Test".to_string(), "sk-test".to_string());
+        let range = UsageRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(client.fetch_usage_range(&account, range).await.unwrap(), 12.5);
+        assert_eq!(date_hits.load(Ordering::SeqCst), 1);
+
+        // The working format is remembered, so the rejected one isn't retried
+        assert_eq!(client.fetch_usage_range(&account, range).await.unwrap(), 12.5);
+        assert_eq!(date_hits.load(Ordering::SeqCst), 1);
+
+        // Only the rejected format configured: the 400 surfaces as an error
+        let strict = OpenAIClient::new()
+            .with_base_url(client.base_url.clone())
+            .with_billing_date_formats(vec![BillingDateFormat::Dates]);
+        assert!(strict.fetch_usage_range(&account, range).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_usage_picks_endpoints_by_key_scope() {
+        use axum::{http::{HeaderMap, StatusCode}, routing::get, Json};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        fn is_admin(headers: &HeaderMap) -> bool {
+            headers
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("Bearer sk-admin-"))
+        }
+
+        let billing_hits = Arc::new(AtomicUsize::new(0));
+        let hits = billing_hits.clone();
+        let app = axum::Router::new()
+            .route(
+                "/v1/dashboard/billing/usage",
+                get(move |headers: HeaderMap| {
+                    let hits = hits.clone();
+                    async move {
+                        hits.fetch_add(1, Ordering::SeqCst);
+                        if !is_admin(&headers) {
+                            return Err(StatusCode::UNAUTHORIZED);
+                        }
+                        Ok(Json(serde_json::json!({
+                            "object": "list",
+                            "daily_costs": [],
+                            "total_usage": 4200.0,
+                        })))
+                    }
+                }),
+            )
+            .route(
+                "/v1/dashboard/billing/subscription",
+                get(|headers: HeaderMap| async move {
+                    if !is_admin(&headers) {
+                        return Err(StatusCode::UNAUTHORIZED);
+                    }
+                    Ok(Json(serde_json::json!({
+                        "object": "billing_subscription",
+                        "has_payment_method": true,
+                        "canceled": false,
+                        "access_until": 0,
+                        "hard_limit_usd": 120.0,
+                    })))
+                }),
+            )
+            .route(
+                "/v1/usage",
+                get(|| async { Json(serde_json::json!({ "object": "list", "data": [] })) }),
+            );
+
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+
+        // Admin keys read org billing
+        let admin = Account::new("Admin".to_string(), "sk-admin-abc".to_string());
+        let snapshot = client.fetch_usage(&admin).await.unwrap();
+        assert_eq!(snapshot.monthly_usage, 42.0);
+        assert_eq!(snapshot.hard_limit, Some(120.0));
+        assert_eq!(client.usage_scope(&admin), Some(UsageScope::Organization));
+        assert_eq!(billing_hits.load(Ordering::SeqCst), 1);
+
+        // Project keys never touch the org endpoints
+        let project = Account::new("Project".to_string(), "sk-proj-abc".to_string());
+        let snapshot = client.fetch_usage(&project).await.unwrap();
+        assert_eq!(snapshot.hard_limit, None);
+        assert_eq!(billing_hits.load(Ordering::SeqCst), 1);
+
+        // Unknown keys are probed once, then remembered as user-level
+        let legacy = Account::new("Legacy".to_string(), "sk-legacy".to_string());
+        assert_eq!(client.usage_scope(&legacy), None);
+        client.fetch_usage(&legacy).await.unwrap();
+        assert_eq!(client.usage_scope(&legacy), Some(UsageScope::User));
+        assert_eq!(billing_hits.load(Ordering::SeqCst), 2);
+        client.fetch_usage(&legacy).await.unwrap();
+        assert_eq!(billing_hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_key_fails_the_poll() {
+        use axum::{http::{HeaderMap, StatusCode}, routing::get, Json};
+
+        // Revoked keys get a 401, restricted ones a 403
+        let app = axum::Router::new().route(
+            "/v1/usage",
+            get(|headers: HeaderMap| async move {
+                match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+                    Some("Bearer sk-proj-revoked") => Err(StatusCode::UNAUTHORIZED),
+                    Some("Bearer sk-proj-restricted") => Err(StatusCode::FORBIDDEN),
+                    _ => Ok(Json(serde_json::json!({ "object": "list", "data": [] }))),
+                }
+            }),
+        );
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+
+        let revoked = Account::new("Revoked".to_string(), "sk-proj-revoked".to_string());
+        let error = client.fetch_usage(&revoked).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<PollError>(),
+            Some(PollError::KeyRejected(StatusCode::UNAUTHORIZED))
+        ));
+        assert_eq!(rate_limit_of(&error), None);
+
+        let restricted = Account::new("Restricted".to_string(), "sk-proj-restricted".to_string());
+        assert!(client.fetch_usage(&restricted).await.is_ok());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(std::time::Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(std::time::Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_poller_honors_retry_after_globally() {
+        use axum::{http::StatusCode, response::IntoResponse, routing::get};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Rate-limit the first billing request, then serve normally
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = axum::Router::new().route(
+            "/v1/dashboard/billing/usage",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "1")]).into_response()
+                    } else {
+                        axum::Json(serde_json::json!({
+                            "object": "list",
+                            "daily_costs": [],
+                            "total_usage": 1250.0,
+                        }))
+                        .into_response()
+                    }
+                }
+            }),
+        );
+
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+        let poller = UsagePoller::new().with_client(client);
+        let first = Account::new("First".to_string(), "sk-first".to_string());
+        let second = Account::new("Second".to_string(), "sk-second".to_string());
+
+        let err = poller.poll_account(&first, None).await.unwrap_err();
+        assert_eq!(rate_limit_of(&err), Some(std::time::Duration::from_secs(1)));
+        assert!(poller.cooldown_remaining().is_some());
+
+        // A different account still waits out the global cooldown
+        let started = std::time::Instant::now();
+        let usage = poller.poll_account(&second, None).await.unwrap();
+        assert!(started.elapsed() >= std::time::Duration::from_millis(900));
+        assert_eq!(usage.monthly_usage, 12.5);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_poller_caps_retry_after_at_max_interval() {
+        use axum::{http::StatusCode, response::IntoResponse, routing::get};
+
+        let app = axum::Router::new().route(
+            "/v1/dashboard/billing/usage",
+            get(|| async {
+                (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "86400")]).into_response()
+            }),
+        );
+
+        let client = OpenAIClient::new().with_base_url(spawn_mock(app).await);
+        let poller = UsagePoller::new()
+            .with_client(client)
+            .with_intervals(std::time::Duration::from_secs(1), std::time::Duration::from_secs(5));
+        let account = Account::new("Test".to_string(), "sk-test".to_string());
+
+        poller.poll_account(&account, None).await.unwrap_err();
+        let remaining = poller.cooldown_remaining().unwrap();
+        assert!(remaining <= std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_response_cost() {
+        let response = serde_json::json!({
+            "usage": { "prompt_tokens": 1_000_000, "completion_tokens": 500_000 }
+        });
+
+        let cost = |model: &str| response_cost(model, &response).unwrap();
+        assert!((cost("gpt-4o-mini-2024-07-18") - 0.45).abs() < 1e-9);
+        assert!((cost("gpt-4o") - 7.50).abs() < 1e-9);
+        assert_eq!(response_cost("unknown-model", &response), None);
+        assert_eq!(response_cost("gpt-4o", &serde_json::json!({ "id": "x" })), None);
+    }
 }